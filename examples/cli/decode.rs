@@ -0,0 +1,29 @@
+use crate::Test;
+use cmd_lib::run_fun;
+
+pub fn decode(test: &mut Test, feeless: &str) -> anyhow::Result<()> {
+    test.run("Decode a telemetry request.", || {
+        Ok(run_fun!(
+            $feeless decode 52431212120C0000
+        )?)
+    })
+    .contains("TelemetryReq 0..8 complete")
+    .contains("5..6 message_type 0C");
+
+    test.run("Decode a message followed by part of a header.", || {
+        Ok(run_fun!(
+            $feeless decode 52431212120C000052431212
+        )?)
+    })
+    .contains("Unknown 8..12 partial, 4 bytes missing");
+
+    test.run("Decode as JSON.", || {
+        Ok(run_fun!(
+            $feeless decode --json 5243121212080000
+        )?)
+    })
+    .contains(r#""message_type": "FrontierReq""#)
+    .contains(r#""missing": 40"#);
+
+    Ok(())
+}
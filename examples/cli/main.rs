@@ -1,9 +1,12 @@
 #![forbid(unsafe_code)]
 #![cfg_attr(feature = "deny_warnings", deny(warnings))]
 
+mod decode;
 mod keys;
+mod multisig;
 mod signing;
 mod units;
+mod vanity;
 mod wallet;
 
 use ansi_term::Color;
@@ -52,6 +55,9 @@ fn run() -> anyhow::Result<()> {
     wallet::wallet(&mut test, &feeless)?;
     signing::signing(&mut test, &feeless)?;
     units::units(&mut test, &feeless)?;
+    vanity::vanity(&mut test, &feeless)?;
+    multisig::multisig(&mut test, &feeless)?;
+    decode::decode(&mut test, &feeless)?;
 
     test.end()?;
 
@@ -0,0 +1,58 @@
+use crate::Test;
+use cmd_lib::run_fun;
+use std::fs::remove_file;
+
+pub fn multisig(test: &mut Test, feeless: &str) -> anyhow::Result<()> {
+    let alice = run_fun!($feeless private new)?;
+    let bob = run_fun!($feeless private new)?;
+    let alice_public = run_fun!($feeless private to-public $alice)?;
+    let bob_address = run_fun!($feeless private to-address $bob)?;
+    let hash = "7F0E444C69F77A49BD0BE89DB92C38FE713E0963165CCA12FAF5712D7657120F";
+    let (alice_session, bob_session) = ("alice.multisig", "bob.multisig");
+    let _ = remove_file(alice_session);
+    let _ = remove_file(bob_session);
+
+    let address = test
+        .run("Aggregate a public key and an address", || {
+            Ok(run_fun!($feeless multisig address $alice_public $bob_address)?)
+        })
+        .contains("nano_")
+        .output
+        .to_owned();
+
+    test.run("Sign a block hash in three rounds", || {
+        let alice_commit = run_fun!(
+            $feeless multisig commit -p $alice --hash $hash -s $alice_session $alice_public $bob_address
+        )?;
+        let bob_commit = run_fun!(
+            $feeless multisig commit -p $bob --hash $hash -s $bob_session $alice_public $bob_address
+        )?;
+        let alice_nonce =
+            run_fun!($feeless multisig reveal -s $alice_session $alice_commit $bob_commit)?;
+        let bob_nonce =
+            run_fun!($feeless multisig reveal -s $bob_session $alice_commit $bob_commit)?;
+        let alice_partial = run_fun!(
+            $feeless multisig sign -p $alice -s $alice_session $alice_nonce $bob_nonce
+        )?;
+        let bob_partial =
+            run_fun!($feeless multisig sign -p $bob -s $bob_session $alice_nonce $bob_nonce)?;
+        let signature =
+            run_fun!($feeless multisig aggregate --hash $hash $alice_partial $bob_partial)?;
+        Ok(run_fun!(
+            $feeless verify --address $address --signature $signature --hash $hash
+        )?)
+    })
+    .equals("OK");
+
+    #[cfg(unix)]
+    test.run("Keep the secret nonce of a session private", || {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(alice_session)?.permissions().mode();
+        Ok(format!("{:o}", mode & 0o777))
+    })
+    .equals("600");
+
+    remove_file(alice_session)?;
+    remove_file(bob_session)?;
+    Ok(())
+}
@@ -45,5 +45,45 @@ pub fn signing(test: &mut Test, feeless: &str) -> anyhow::Result<()> {
 
     remove_file(armor_path)?;
 
+    let message = "I own this address.\n-----BEGIN NANO ADDRESS-----\n";
+    for format in &["armor", "json", "compact"] {
+        let path = format!("test.{}", format);
+        test.run(&format!("Sign a multi-line message as {}", format), || {
+            Ok(run_fun!(
+                $feeless wallet sign $message --format $format > $path
+            )?)
+        });
+
+        test.run(
+            &format!("Verify a multi-line message as {}", format),
+            || {
+                Ok(run_fun!(
+                    $feeless verify --armor < $path
+                )?)
+            },
+        )
+        .equals("OK");
+
+        remove_file(&path)?;
+    }
+
+    let other = run_fun!($feeless private new)?;
+    let other_address = run_fun!($feeless private to-address $other)?;
+    let encrypted_path = "test.encrypted";
+    test.run("Encrypt a message to another address", || {
+        Ok(run_fun!(
+            $feeless wallet encrypt $other_address "a memo\nover two lines" > $encrypted_path
+        )?)
+    });
+
+    test.run("Decrypt a message that was sent", || {
+        Ok(run_fun!(
+            $feeless wallet decrypt - < $encrypted_path
+        )?)
+    })
+    .equals("a memo\nover two lines");
+
+    remove_file(encrypted_path)?;
+
     Ok(())
 }
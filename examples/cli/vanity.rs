@@ -0,0 +1,59 @@
+use crate::Test;
+use cmd_lib::run_cmd;
+use std::fs::{read_to_string, remove_file};
+
+pub fn vanity(test: &mut Test, feeless: &str) -> anyhow::Result<()> {
+    test.run("Resume a search for several patterns.", || {
+        let path = "vanity-resume.csv";
+        let _ = remove_file(path);
+        run_cmd!($feeless vanity private -s -l 1 -o $path a b)?;
+        // Both patterns are already in the file, so nothing else is added.
+        run_cmd!($feeless vanity private -s -l 1 -o $path a b)?;
+        let output = read_to_string(path)?;
+        remove_file(path)?;
+        Ok(format!("{} lines\n{}", output.lines().count(), output))
+    })
+    .contains("3 lines\npattern,address,index,secret\n");
+
+    test.run(
+        "Count rows already in the output towards the total.",
+        || {
+            let path = "vanity-total.csv";
+            let _ = remove_file(path);
+            run_cmd!($feeless vanity private -s --total 2 -o $path a)?;
+            run_cmd!($feeless vanity private -s --total 2 -o $path a)?;
+            Ok(format!("{} lines", read_to_string(path)?.lines().count()))
+        },
+    )
+    .equals("3 lines");
+
+    #[cfg(unix)]
+    test.run("Keep the found secrets private", || {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata("vanity-total.csv")?.permissions().mode();
+        Ok(format!("{:o}", mode & 0o777))
+    })
+    .equals("600");
+    remove_file("vanity-total.csv")?;
+
+    test.run("Scan account indexes of seeds, writing JSON.", || {
+        Ok(cmd_lib::run_fun!(
+            $feeless vanity seed -e -l 1 --index 3 --scan 2 -f json z
+        )?)
+    })
+    .contains(r#""pattern":"z""#);
+
+    test.run("Find an offset for a public key and combine it.", || {
+        let private = cmd_lib::run_fun!($feeless private new)?;
+        let public = cmd_lib::run_fun!($feeless private to-public $private)?;
+        let found = cmd_lib::run_fun!($feeless vanity split-key -p $public -e -l 1 z)?;
+        // Log lines come first.
+        let last = found.lines().last().unwrap_or_default();
+        let (address, offset) = last.split_once(',').unwrap_or_default();
+        let combined = cmd_lib::run_fun!($feeless private combine $private -o $offset -a)?;
+        Ok(format!("{}", combined == address))
+    })
+    .equals("true");
+
+    Ok(())
+}
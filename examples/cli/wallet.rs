@@ -43,5 +43,60 @@ pub(crate) fn wallet(test: &mut Test, feeless: &str) -> anyhow::Result<()> {
     )
     .contains("nano_3tr7wk6ebc6ujptdnf471d8knnfaz1r469u83biws5s5jntb3hpe8oh65ogi");
 
+    test.run(
+        "Export every wallet into a backup and import it into another network's wallet file.",
+        || {
+            Ok(run_fun!(
+                $feeless wallet export backup --data-dir $data_dir | $feeless wallet import backup - --data-dir $data_dir -n test
+            )?)
+        },
+    )
+    .contains("0000");
+
+    test.run("Addresses survive a backup round trip.", || {
+        Ok(run_fun!(
+            $feeless wallet address 5 --data-dir $data_dir -n test
+        )?)
+    })
+    .contains("nano_3tr7wk6ebc6ujptdnf471d8knnfaz1r469u83biws5s5jntb3hpe8oh65ogi");
+
+    test.run("Export a seed in a QR code friendly format.", || {
+        Ok(run_fun!(
+            $feeless wallet import seed --data-dir $data_dir -n beta --default 0000000000000000000000000000000000000000000000000000000000000000;
+            $feeless wallet export qr --data-dir $data_dir -n beta
+        )?)
+    })
+    .contains("nanoseed:0000");
+
+    let words = "edge defense waste choose enrich upon flee junk siren film clown finish luggage leader kid quick brick print evidence swap drill paddle truly occur";
+    let id = "1111111111111111111111111111111111111111111111111111111111111111";
+    test.run(
+        "A phrase wallet that needs its passphrase every time it is used.",
+        || {
+            Ok(run_fun!(
+                $feeless wallet import phrase --data-dir $data_dir -n beta --id $id --prompt-passphrase $words;
+                $feeless wallet address --data-dir $data_dir -n beta --id $id --passphrase secret
+            )?)
+        },
+    )
+    .contains("nano_3nmtog49cd5m3isj5zk7x66yg3kzdp3eagxsf8ue46dhmi9ksy1f9j3kmatf");
+
+    let id = "2222222222222222222222222222222222222222222222222222222222222222";
+    test.run("A phrase wallet using the phrase as a seed.", || {
+        Ok(run_fun!(
+            $feeless wallet import phrase --data-dir $data_dir -n beta --id $id --derivation seed $words;
+            $feeless wallet address --data-dir $data_dir -n beta --id $id
+        )?)
+    })
+    .contains("nano_3msh5b7t9fsge5dna87xgj5pszhqzx4xbx4jj98k1uiq9s7qzipuxburz59w");
+
+    let request = r#"{"action": "public", "index": 0}"#;
+    test.run("Answer signer requests over stdin.", || {
+        Ok(run_fun!(
+            echo $request | $feeless wallet signer --data-dir $data_dir -n beta
+        )?)
+    })
+    .contains(r#"{"public":"C008B814A7D269A1FA3C6528B19201A24D797912DB9996FF02A1FF356E45552B"}"#);
+
     Ok(())
 }
@@ -16,7 +16,7 @@ use crate::node::Header;
 pub(crate) use state_block::deserialize_to_unsure_link;
 
 use crate::encoding::blake2b;
-use crate::keys::public::to_address;
+use crate::keys::public::{from_address, to_address};
 use crate::network::Network;
 use crate::{Private, Public, Raw, Signature, Work};
 use anyhow::{anyhow, Context};
@@ -112,7 +112,7 @@ impl Wire for BlockHolder {
                 BlockHolder::State(Wire::deserialize(header, data).context(context)?)
             }
             BlockType::Send => BlockHolder::Send(Wire::deserialize(header, data).context(context)?),
-            _ => todo!(),
+            block_type => return Err(anyhow!("{}: Unsupported {:?} block", context, block_type)),
         };
         Ok(holder)
     }
@@ -125,7 +125,7 @@ impl Wire for BlockHolder {
         match header.as_ref().unwrap().ext().block_type()? {
             BlockType::State => StateBlock::len(header),
             BlockType::Send => SendBlock::len(header),
-            _ => todo!(),
+            block_type => Err(anyhow!("Unsupported {:?} block", block_type)),
         }
     }
 }
@@ -175,7 +175,7 @@ impl FromStr for Previous {
 ///
 /// When processing blocks from the network, this should be created after going through the
 /// controller since certain fields such as "amount" won't be available immediately.
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Block {
     #[serde(rename = "type")]
     block_type: BlockType,
@@ -210,7 +210,7 @@ pub struct Block {
     state: ValidationState,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ValidationState {
     Published,
     PresumedValid,
@@ -402,6 +402,10 @@ impl Block {
         &self.previous
     }
 
+    pub fn link(&self) -> &Link {
+        &self.link
+    }
+
     /// For an open or recv block, get the sender's block hash, otherwise Err.
     pub fn source(&self) -> anyhow::Result<&BlockHash> {
         if self.block_type != BlockType::Open {
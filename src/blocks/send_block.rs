@@ -65,8 +65,8 @@ impl Wire for SendBlock {
         let previous = BlockHash::try_from(data.slice(BlockHash::LEN)?)?;
         let destination = Public::try_from(data.slice(Public::LEN)?)?;
         let balance = Raw::try_from(data.slice(Raw::LEN)?)?;
-        let work = Some(Work::try_from(data.slice(Work::LEN)?)?);
         let signature = Some(Signature::try_from(data.slice(Signature::LEN)?)?);
+        let work = Some(Work::try_from(data.slice(Work::LEN)?)?);
 
         Ok(Self {
             previous,
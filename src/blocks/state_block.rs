@@ -395,6 +395,13 @@ impl Link {
         Ok(Link::Unsure(UnsureLink(slice)))
     }
 
+    /// Forget what this link refers to, keeping only the raw bytes.
+    pub fn to_unsure(&self) -> Self {
+        let mut slice = [0u8; Self::LEN];
+        slice.copy_from_slice(self.as_bytes());
+        Link::Unsure(UnsureLink(slice))
+    }
+
     pub fn as_bytes(&self) -> &[u8] {
         match self {
             Link::Nothing => &[0u8; Self::LEN],
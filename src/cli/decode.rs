@@ -0,0 +1,61 @@
+use crate::cli::StringOrStdin;
+use crate::node::{dissect, DissectStatus};
+use anyhow::Context;
+use clap::Clap;
+
+/// Decode Nano protocol messages from hex, showing the bytes of each field.
+#[derive(Clap)]
+pub(crate) struct DecodeOpts {
+    /// One or more messages in hex, starting with a header. Use `-` to read from stdin.
+    hex: StringOrStdin<String>,
+
+    /// Output the decoded messages as JSON.
+    #[clap(long)]
+    json: bool,
+}
+
+impl DecodeOpts {
+    pub fn handle(&self) -> anyhow::Result<()> {
+        let hex = self.hex.to_owned().resolve()?;
+        let hex: String = hex.split_whitespace().collect();
+        let data = hex::decode(&hex).context("Decoding hex")?;
+        let messages = dissect(&data);
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&messages)?);
+            return Ok(());
+        }
+
+        for message in messages {
+            let message_type = match message.message_type {
+                Some(t) => format!("{:?}", t),
+                None => "Unknown".to_owned(),
+            };
+            let status = match &message.status {
+                DissectStatus::Complete => "complete".to_owned(),
+                DissectStatus::Partial { missing } => format!("partial, {} bytes missing", missing),
+                DissectStatus::Invalid { error } => format!("invalid: {}", error),
+            };
+            println!(
+                "{} {}..{} {}",
+                message_type,
+                message.offset,
+                message.offset + message.len,
+                status
+            );
+            for field in &message.fields {
+                println!(
+                    "  {}..{} {} {}",
+                    field.offset,
+                    field.offset + field.len,
+                    field.name,
+                    field.hex
+                );
+            }
+            if let Some(payload) = &message.payload {
+                println!("  {}", payload);
+            }
+        }
+        Ok(())
+    }
+}
@@ -2,6 +2,9 @@
 mod pcap;
 
 mod address;
+#[cfg(feature = "node")]
+mod decode;
+mod multisig;
 mod phrase;
 mod private;
 mod public;
@@ -11,24 +14,33 @@ mod vanity;
 mod verify;
 mod wallet;
 mod work;
+#[cfg(feature = "work_server")]
+mod work_server;
 
 #[cfg(feature = "rpc_client")]
 use crate::rpc::client::RPCClientOpts;
+#[cfg(feature = "ws_client")]
+use crate::rpc::websocket::WebSocketOpts;
 
 #[cfg(feature = "pcap")]
 use crate::cli::pcap::PcapDumpOpts;
 
 #[cfg(feature = "node")]
-use crate::node::Node;
+use crate::cli::decode::DecodeOpts;
+#[cfg(feature = "node")]
+use crate::node::{Node, Recorder};
 
 use crate::cli::unit::UnitOpts;
 use crate::cli::vanity::VanityOpts;
 use crate::cli::verify::VerifyOpts;
 use crate::cli::wallet::WalletOpts;
 use crate::cli::work::WorkOpts;
+#[cfg(feature = "work_server")]
+use crate::cli::work_server::WorkServerOpts;
 use address::AddressOpts;
 use anyhow::anyhow;
 use clap::Clap;
+use multisig::MultisigOpts;
 use phrase::PhraseOpts;
 use private::PrivateOpts;
 use public::PublicOpts;
@@ -91,9 +103,19 @@ enum Command {
     /// Generate proof of work.
     Work(WorkOpts),
 
+    #[cfg(feature = "work_server")]
+    /// Serve work_generate, work_cancel and work_validate over HTTP.
+    WorkServer(WorkServerOpts),
+    #[cfg(not(feature = "work_server"))]
+    /// Serve work_generate, work_cancel and work_validate over HTTP. (DISABLED)
+    WorkServer,
+
     /// Find a secret that can generate a custom vanity address.
     Vanity(VanityOpts),
 
+    /// Accounts that need several keys to sign, with an aggregated key.
+    Multisig(MultisigOpts),
+
     #[cfg(feature = "rpc_client")]
     /// RPC client that can call a function against a Nano RPC server.
     Call(RPCClientOpts),
@@ -101,6 +123,20 @@ enum Command {
     /// RPC client that can call a function against a Nano RPC server. (DISABLED)
     Call,
 
+    #[cfg(feature = "ws_client")]
+    /// Subscribe to events from the WebSocket server of a Nano node.
+    Ws(WebSocketOpts),
+    #[cfg(not(feature = "ws_client"))]
+    /// Subscribe to events from the WebSocket server of a Nano node. (DISABLED)
+    Ws,
+
+    #[cfg(feature = "node")]
+    /// Decode Nano protocol messages from hex.
+    Decode(DecodeOpts),
+    #[cfg(not(feature = "node"))]
+    /// Decode Nano protocol messages from hex. (DISABLED)
+    Decode,
+
     #[cfg(feature = "pcap")]
     /// Tool to analyse network capture dumps for Nano packets.
     Pcap(PcapDumpOpts),
@@ -114,12 +150,15 @@ struct NodeOpts {
     /// Comma separated list of IP:PORT pairs. Overrides default initial nodes.
     #[clap(short, long)]
     override_peers: Option<Vec<String>>,
-}
 
-#[derive(Clap)]
-struct PcapLogToCsvArgs {
-    src: PathBuf,
-    dst: PathBuf,
+    /// Record all traffic to this pcapng file, which can be read by `feeless pcap` or Wireshark.
+    #[clap(long)]
+    record: Option<PathBuf>,
+
+    /// Start a new recording file after this many megabytes, e.g. `traffic.1.pcapng`. Only used
+    /// with `--record`.
+    #[clap(long, default_value = "100")]
+    record_max_mb: u64,
 }
 
 pub async fn run() -> anyhow::Result<()> {
@@ -139,20 +178,39 @@ pub async fn run() -> anyhow::Result<()> {
 
     match opts.command {
         #[cfg(feature = "node")]
-        Command::Node(o) => Node::start(o.override_peers).await,
+        Command::Node(o) => {
+            let max_bytes = o.record_max_mb * 1024 * 1024;
+            let recorder = o.record.map(|path| Recorder::new(path, max_bytes));
+            Node::start(o.override_peers, recorder).await
+        }
         #[cfg(not(feature = "node"))]
         Command::Node => panic!("Compile with the `node` feature to enable this."),
 
+        #[cfg(feature = "node")]
+        Command::Decode(o) => o.handle(),
+        #[cfg(not(feature = "node"))]
+        Command::Decode => panic!("Compile with the `node` feature to enable this."),
+
         #[cfg(feature = "pcap")]
         Command::Pcap(o) => o.handle().await,
         #[cfg(not(feature = "pcap"))]
         Command::Pcap => panic!("Compile with the `pcap` feature to enable this."),
 
+        #[cfg(feature = "work_server")]
+        Command::WorkServer(o) => o.handle().await,
+        #[cfg(not(feature = "work_server"))]
+        Command::WorkServer => panic!("Compile with the `work_server` feature to enable this."),
+
         #[cfg(feature = "rpc_client")]
         Command::Call(o) => Ok(o.handle().await?),
         #[cfg(not(feature = "rpc_client"))]
         Command::Call => panic!("Compile with the `rpc_client` feature to enable this."),
 
+        #[cfg(feature = "ws_client")]
+        Command::Ws(o) => o.handle().await,
+        #[cfg(not(feature = "ws_client"))]
+        Command::Ws => panic!("Compile with the `ws_client` feature to enable this."),
+
         Command::Wallet(wallet) => wallet.handle().await,
         Command::Seed(seed) => seed.handle(),
         Command::Private(private) => private.handle(),
@@ -162,6 +220,7 @@ pub async fn run() -> anyhow::Result<()> {
         Command::Unit(unit) => unit.handle(),
         Command::Work(work) => work.handle(),
         Command::Vanity(vanity) => vanity.handle().await,
+        Command::Multisig(multisig) => multisig.handle(),
         Command::Verify(verify) => verify.handle(),
     }
 }
@@ -0,0 +1,192 @@
+use crate::blocks::BlockHash;
+use crate::cli::StringOrStdin;
+use crate::multisig::{AggregatedKey, CommitMessage, NonceMessage, PartialSignature, Session};
+use crate::{Address, Private, Public};
+use anyhow::Context;
+use clap::Clap;
+use serde::de::DeserializeOwned;
+use std::fs::OpenOptions;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Clap)]
+pub struct MultisigOpts {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+impl MultisigOpts {
+    pub fn handle(&self) -> anyhow::Result<()> {
+        match &self.command {
+            Command::Address(o) => {
+                let key = AggregatedKey::new(o.keys.to_owned())?;
+                println!("{}", key.to_address());
+            }
+            Command::Commit(o) => {
+                if o.session.exists() {
+                    return Err(anyhow::anyhow!(
+                        "Session {:?} already exists. Never reuse a session for another signature.",
+                        o.session
+                    ));
+                }
+                let key = AggregatedKey::new(o.keys.to_owned())?;
+                let private = o.private.to_owned().resolve()?;
+                let session = Session::new(key, &private, o.hash.to_owned())?;
+                save(&o.session, &session)?;
+                println!("{}", serde_json::to_string(&session.commit())?);
+            }
+            Command::Reveal(o) => {
+                let mut session = load(&o.session)?;
+                let commitments: Vec<CommitMessage> = parse_all(&o.commitments)?;
+                let nonce = session.reveal(&commitments)?;
+                save(&o.session, &session)?;
+                println!("{}", serde_json::to_string(&nonce)?);
+            }
+            Command::Sign(o) => {
+                let mut session = load(&o.session)?;
+                let private = o.private.to_owned().resolve()?;
+                let nonces: Vec<NonceMessage> = parse_all(&o.nonces)?;
+                let partial = session.sign(&private, &nonces)?;
+                // Save before printing so the nonce can't be used again.
+                save(&o.session, &session)?;
+                println!("{}", serde_json::to_string(&partial)?);
+            }
+            Command::Aggregate(o) => {
+                let partials: Vec<PartialSignature> = parse_all(&o.partials)?;
+                let key =
+                    AggregatedKey::new(partials.iter().map(|p| p.public.to_owned()).collect())?;
+                let signature = key.aggregate(&o.hash, &partials)?;
+                eprintln!("Signed by {}", key.to_address());
+                println!("{}", signature);
+            }
+        };
+        Ok(())
+    }
+}
+
+#[derive(Clap)]
+enum Command {
+    /// The address of the account that the keys make together.
+    Address(AddressOpts),
+
+    /// Round 1: Start signing a block hash and output a nonce commitment for the others.
+    Commit(CommitOpts),
+
+    /// Round 2: Take every participant's commitment and output a nonce.
+    Reveal(RevealOpts),
+
+    /// Round 3: Take every participant's nonce and output a partial signature.
+    Sign(SignOpts),
+
+    /// Combine every participant's partial signature into the signature of the block hash.
+    Aggregate(AggregateOpts),
+}
+
+#[derive(Clap)]
+struct AddressOpts {
+    /// Public keys or addresses of every participant.
+    #[clap(required = true, parse(try_from_str = parse_key))]
+    keys: Vec<Public>,
+}
+
+#[derive(Clap)]
+struct CommitOpts {
+    /// Your private key, or `-` to read it from stdin.
+    #[clap(short, long)]
+    private: StringOrStdin<Private>,
+
+    /// The block hash to sign.
+    #[clap(long)]
+    hash: BlockHash,
+
+    /// Where to keep the state of this signature between rounds. It contains a secret nonce.
+    #[clap(short, long)]
+    session: PathBuf,
+
+    /// Public keys or addresses of every participant, including yours.
+    #[clap(required = true, parse(try_from_str = parse_key))]
+    keys: Vec<Public>,
+}
+
+#[derive(Clap)]
+struct RevealOpts {
+    #[clap(short, long)]
+    session: PathBuf,
+
+    /// The JSON commitment of every participant, including yours.
+    #[clap(required = true)]
+    commitments: Vec<String>,
+}
+
+#[derive(Clap)]
+struct SignOpts {
+    /// Your private key, or `-` to read it from stdin.
+    #[clap(short, long)]
+    private: StringOrStdin<Private>,
+
+    #[clap(short, long)]
+    session: PathBuf,
+
+    /// The JSON nonce of every participant, including yours.
+    #[clap(required = true)]
+    nonces: Vec<String>,
+}
+
+#[derive(Clap)]
+struct AggregateOpts {
+    /// The block hash that was signed.
+    #[clap(long)]
+    hash: BlockHash,
+
+    /// The JSON partial signature of every participant.
+    #[clap(required = true)]
+    partials: Vec<String>,
+}
+
+fn parse_key(s: &str) -> anyhow::Result<Public> {
+    if s.starts_with("nano_") || s.starts_with("xrb_") {
+        Ok(Address::from_str(s)?.to_public())
+    } else {
+        Ok(Public::from_str(s)?)
+    }
+}
+
+fn parse_all<T: DeserializeOwned>(messages: &[String]) -> anyhow::Result<Vec<T>> {
+    messages
+        .iter()
+        .map(|m| serde_json::from_str(m).with_context(|| format!("Parsing message {}", m)))
+        .collect()
+}
+
+fn load(path: &Path) -> anyhow::Result<Session> {
+    let json =
+        std::fs::read_to_string(path).with_context(|| format!("Reading session {:?}", path))?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// The session holds the secret nonce, so only the owner can read it. It's written to a temporary
+/// file first, so a crash can't leave half a session behind.
+fn save(path: &Path, session: &Session) -> anyhow::Result<()> {
+    let json = serde_json::to_string_pretty(session)?;
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    // A leftover file would keep its permissions.
+    let _ = std::fs::remove_file(&tmp);
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options
+        .open(&tmp)
+        .with_context(|| format!("Creating session {:?}", tmp))?;
+    file.write_all(json.as_bytes())
+        .and_then(|_| file.sync_all())
+        .with_context(|| format!("Writing session {:?}", tmp))?;
+
+    std::fs::rename(&tmp, path).with_context(|| format!("Saving session {:?}", path))
+}
@@ -1,9 +1,14 @@
+use crate::pcap::OutputFormat;
 use anyhow::Context;
 use clap::Clap;
-use std::net::Ipv4Addr;
+use std::net::IpAddr;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 /// Read a pcapng file containing Nano packets, and print some information about each payload.
+///
+/// IPv4 and IPv6 are supported, captured over Ethernet, Linux cooked capture (`any` interface) or
+/// raw IP.
 #[derive(Clap)]
 pub(crate) struct PcapDumpOpts {
     path: String,
@@ -24,23 +29,61 @@ pub(crate) struct PcapDumpOpts {
     /// Last packet to process.
     #[clap(long)]
     end: Option<usize>,
+
+    /// Look for Nano connections on every port, instead of only the default port. A connection
+    /// is recognised when the first data seen on it is a valid header.
+    #[clap(long)]
+    any_port: bool,
+
+    /// Also write a record of each decoded message: log, json or csv.
+    #[clap(short, long, default_value = "log")]
+    format: OutputFormat,
+
+    /// Where to write the records, instead of stdout.
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+
+    /// Write message counts, bytes, votes and blocks for each peer to this file as JSON, instead
+    /// of logging them at the end.
+    #[clap(long)]
+    summary: Option<PathBuf>,
+
+    /// Process captured blocks, votes and bootstrap streams into a ledger, then report blocks
+    /// accepted or rejected by reason, elections observed and the final frontiers.
+    #[clap(long)]
+    replay: bool,
+
+    /// Replay into a sled database at this path, instead of into memory.
+    #[clap(long, requires = "replay")]
+    sled: Option<PathBuf>,
+
+    /// Write the replay report to this file as JSON, instead of logging it.
+    #[clap(long, requires = "replay")]
+    report: Option<PathBuf>,
 }
 
 impl PcapDumpOpts {
     pub async fn handle(&self) -> anyhow::Result<()> {
         let subject = match &self.my_addr {
             Some(ip_addr) => crate::pcap::Subject::Specified(
-                Ipv4Addr::from_str(&ip_addr).context("Invalid IP address")?,
+                IpAddr::from_str(&ip_addr).context("Invalid IP address")?,
             ),
             None => crate::pcap::Subject::AutoFirstSource,
         };
         let mut p = crate::pcap::PcapDump::new(subject);
         p.start_at = self.start;
         p.end_at = self.end;
+        p.any_port = self.any_port;
+        p.format = self.format;
+        p.output = self.output.to_owned();
+        p.summary = self.summary.to_owned();
+        p.replay = self.replay;
+        p.sled = self.sled.to_owned();
+        p.report = self.report.to_owned();
         p.filter_addr = self
             .filter_addr
             .as_ref()
-            .map(|i| Ipv4Addr::from_str(i).context("Invalid IP address"))
+            .map(|i| IpAddr::from_str(i).context("Invalid IP address"))
             .transpose()?;
         p.dump(&self.path).await
     }
@@ -1,5 +1,5 @@
 use crate::cli::StringOrStdin;
-use crate::phrase::{Language, MnemonicType};
+use crate::phrase::{Derivation, Language, MnemonicType};
 use clap::Clap;
 use std::str::FromStr;
 
@@ -86,15 +86,20 @@ pub struct FromPhraseOpts {
     // I tried using default_value = "" but clap still complained about the field being required.
     #[clap(short, long)]
     passphrase: Option<String>,
+
+    /// How keys are derived: bip44 (or ledger), seed, or a custom path with `{account}`.
+    #[clap(long, default_value = "bip44")]
+    derivation: Derivation,
 }
 
 impl FromPhraseOpts {
     pub fn to_private(&self) -> anyhow::Result<crate::Private> {
         let words = self.words.to_owned().resolve().unwrap();
         let phrase = crate::Phrase::from_words(self.language.language.to_owned(), words.as_str())?;
-        let private = phrase.to_private(
+        let private = phrase.derive(
             self.account.to_owned(),
             self.passphrase.as_ref().unwrap_or(&"".to_string()).as_str(),
+            &self.derivation,
         )?;
         Ok(private)
     }
@@ -22,6 +22,15 @@ impl PrivateOpts {
                 let address = a.private.to_owned().resolve()?.to_public()?.to_address();
                 println!("{}", address);
             }
+            Command::Combine(c) => {
+                let private = c.private.to_owned().resolve()?;
+                let combined = c.offset.combine(&private)?;
+                if c.address {
+                    println!("{}", combined.to_address());
+                } else {
+                    println!("{}", combined);
+                }
+            }
         };
         Ok(())
     }
@@ -32,6 +41,9 @@ pub enum Command {
     New,
     ToPublic(Public),
     ToAddress(Address),
+    /// Add an offset from a split-key vanity search to a private key. The result is an expanded
+    /// private key.
+    Combine(Combine),
 }
 
 #[derive(Clap)]
@@ -43,3 +55,15 @@ pub struct Public {
 pub struct Address {
     private: StringOrStdin<crate::Private>,
 }
+
+#[derive(Clap)]
+pub struct Combine {
+    private: StringOrStdin<crate::Private>,
+
+    #[clap(short, long)]
+    offset: crate::vanity::Offset,
+
+    /// Print the address of the combined key instead of the key.
+    #[clap(short, long)]
+    address: bool,
+}
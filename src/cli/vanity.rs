@@ -1,7 +1,13 @@
 use crate::vanity;
-use crate::vanity::Secret;
+use anyhow::{anyhow, Context};
 use clap::Clap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
+use strum_macros::EnumString;
 use tokio::time::{timeout, Duration, Instant};
 
 #[derive(Clap)]
@@ -24,54 +30,138 @@ impl VanityOpts {
             VanitySecretType::Private(private) => {
                 (vanity::SecretType::Private, &private.common_opts)
             }
+            VanitySecretType::SplitKey(opts) => {
+                let public = match (&opts.public, &opts.address) {
+                    (Some(public), _) => public.to_owned(),
+                    (None, Some(address)) => address.to_public(),
+                    (None, None) => return Err(anyhow!("Specify --public or --address")),
+                };
+                let split_key = vanity::SplitKey::new(&public)
+                    .with_context(|| format!("{} is not a valid public key", public))?;
+                (vanity::SecretType::SplitKey(split_key), &opts.common_opts)
+            }
         };
 
-        let matches = if opts.start {
-            vanity::Match::start(&opts.matching)
-        } else if opts.end {
-            vanity::Match::end(&opts.matching)
-        } else if opts.regex {
-            vanity::Match::regex(&opts.matching)?
-        } else {
-            vanity::Match::start_or_end(&opts.matching)
+        let format = match (opts.format, &opts.output) {
+            (Some(OutputFormat::Plain), Some(_)) => {
+                return Err(anyhow!("Use the csv or json format when writing to a file"))
+            }
+            (Some(format), _) => format,
+            (None, Some(_)) => OutputFormat::Csv,
+            (None, None) => OutputFormat::Plain,
         };
 
-        let mut vanity = vanity::Vanity::new(secret_type, matches);
+        // Results already in the output file count towards the limit of each pattern.
+        let previous = match &opts.output {
+            Some(path) if path.exists() => count_rows(&std::fs::read_to_string(path)?, format)?,
+            _ => HashMap::new(),
+        };
+
+        let mut patterns = vec![];
+        let mut names = vec![];
+        for matching in &opts.matching {
+            let mut pattern = vanity::Pattern::new(opts.to_match(matching)?);
+            if let Some(limit) = opts.limit {
+                let done = previous.get(matching).copied().unwrap_or(0);
+                if done >= limit {
+                    eprintln!(
+                        "Skipping {}, already found {} in the output",
+                        matching, done
+                    );
+                    continue;
+                }
+                pattern.limit(limit - done);
+            }
+            patterns.push(pattern);
+            names.push(matching.to_owned());
+        }
+        if patterns.is_empty() {
+            return Ok(());
+        }
+        let mut found: usize = previous.values().sum();
+        if let Some(total) = opts.total {
+            if found >= total {
+                eprintln!("Already found {} in the output", found);
+                return Ok(());
+            }
+        }
+
+        let mut vanity = vanity::Vanity::with_patterns(secret_type, patterns);
         if let Some(tasks) = opts.tasks {
             vanity.tasks(tasks);
         }
         if opts.include_digit {
             vanity.include_first_digit(true);
         }
+        vanity.index(opts.index).scan(opts.scan);
+        vanity.validate()?;
+        let expected: Vec<Option<f64>> = (0..names.len())
+            .map(|p| vanity.expected_attempts(p))
+            .collect();
+        for (name, expected) in names.iter().zip(&expected) {
+            match expected {
+                Some(e) => eprintln!("{}: about {:.0} addresses per match", name, e),
+                None => eprintln!("{}: too rare to estimate addresses per match", name),
+            }
+        }
+
+        let mut output: Box<dyn Write> = match &opts.output {
+            Some(path) => {
+                let exists = path.exists();
+                let mut options = OpenOptions::new();
+                options.create(true).append(true);
+                // The file holds secrets, so only the owner can read it.
+                #[cfg(unix)]
+                std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+                let file = options.open(path)?;
+                let mut file = Box::new(file);
+                if format == OutputFormat::Csv && !exists {
+                    writeln!(file, "pattern,address,index,secret")?;
+                }
+                file
+            }
+            None => {
+                let mut stdout = Box::new(std::io::stdout());
+                if format == OutputFormat::Csv {
+                    writeln!(stdout, "pattern,address,index,secret")?;
+                }
+                stdout
+            }
+        };
 
         let (mut rx, attempts) = vanity.start().await?;
         let started = Instant::now();
         let mut last_log = Instant::now();
-        let mut found = 0;
         loop {
             match timeout(Duration::from_secs(1), rx.recv()).await {
                 Ok(Some(result)) => {
-                    let s = match result.secret {
-                        Secret::Phrase(p) => p.to_string(),
-                        Secret::Seed(s) => s.to_string(),
-                        Secret::Private(p) => p.to_string(),
+                    let row = Row {
+                        pattern: names[result.pattern].to_owned(),
+                        address: result.address.to_string(),
+                        index: result.index,
+                        secret: result.secret.to_string(),
                     };
-                    println!("{},{}", result.address.to_string(), s);
-                    last_log = log(started, last_log, attempts.clone()).await;
+                    match format {
+                        OutputFormat::Plain => writeln!(output, "{},{}", row.address, row.secret)?,
+                        OutputFormat::Csv => writeln!(output, "{}", row.to_csv())?,
+                        OutputFormat::Json => writeln!(output, "{}", serde_json::to_string(&row)?)?,
+                    }
+                    output.flush()?;
+                    last_log = log(started, last_log, attempts.clone(), &expected).await;
                     found += 1;
-                    if let Some(limit) = opts.limit {
-                        if limit == found {
+                    if let Some(limit) = opts.total {
+                        if found >= limit {
                             break;
                         }
                     }
                 }
-                // Channel closed.
+                // Channel closed, so all patterns reached their limit.
                 Ok(None) => {
                     break;
                 }
                 // Timeout
                 Err(_) => {
-                    last_log = log(started, last_log, attempts.clone()).await;
+                    last_log = log(started, last_log, attempts.clone(), &expected).await;
                 }
             }
         }
@@ -79,7 +169,84 @@ impl VanityOpts {
     }
 }
 
-async fn log(started: Instant, last_log: Instant, attempts: Arc<RwLock<usize>>) -> Instant {
+/// A found address, as written to the output.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Row {
+    pattern: String,
+    address: String,
+    index: u32,
+    secret: String,
+}
+
+impl Row {
+    fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{}",
+            csv_escape(&self.pattern),
+            self.address,
+            self.index,
+            self.secret
+        )
+    }
+}
+
+/// Quote a field if it has a comma or quote in it, e.g. a regex like `a{2,3}`.
+fn csv_escape(s: &str) -> String {
+    if s.contains(',') || s.contains('"') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.into()
+    }
+}
+
+/// The first field of a CSV line, unquoted.
+fn csv_first_field(line: &str) -> String {
+    if let Some(quoted) = line.strip_prefix('"') {
+        let mut field = String::new();
+        let mut chars = quoted.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, chars.peek()) {
+                ('"', Some('"')) => {
+                    field.push('"');
+                    chars.next();
+                }
+                ('"', _) => break,
+                (c, _) => field.push(c),
+            }
+        }
+        field
+    } else {
+        line.split(',').next().unwrap_or_default().into()
+    }
+}
+
+/// How many results each pattern has in a previous output.
+fn count_rows(contents: &str, format: OutputFormat) -> anyhow::Result<HashMap<String, usize>> {
+    let mut counts = HashMap::new();
+    for (line_no, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let pattern = match format {
+            OutputFormat::Csv if line_no == 0 => continue,
+            OutputFormat::Json => {
+                let row: Row = serde_json::from_str(line)
+                    .with_context(|| format!("Output line {}", line_no + 1))?;
+                row.pattern
+            }
+            _ => csv_first_field(line),
+        };
+        *counts.entry(pattern).or_insert(0) += 1;
+    }
+    Ok(counts)
+}
+
+async fn log(
+    started: Instant,
+    last_log: Instant,
+    attempts: Arc<RwLock<usize>>,
+    expected: &[Option<f64>],
+) -> Instant {
     let now = Instant::now();
     let since_last_log = now.duration_since(last_log);
     if since_last_log < Duration::from_secs(1) {
@@ -90,7 +257,19 @@ async fn log(started: Instant, last_log: Instant, attempts: Arc<RwLock<usize>>)
             .read()
             .expect("Could not unlock attempts for reading.");
         let rate = (attempts as f64) / total_taken.as_secs_f64();
-        eprintln!("Attempted: {}, Rate: {:?} attempts/s", attempts, rate);
+        let etas: Vec<String> = expected
+            .iter()
+            .map(|e| match e {
+                Some(e) if rate > 0f64 => format!("{:.0}s", e / rate),
+                _ => "?".into(),
+            })
+            .collect();
+        eprintln!(
+            "Attempted: {}, Rate: {:?} attempts/s, ETA per match: {}",
+            attempts,
+            rate,
+            etas.join(" ")
+        );
         now
     }
 }
@@ -103,6 +282,9 @@ enum VanitySecretType {
     Seed(SeedOpts),
     /// Generate private keys to find addresses.
     Private(PrivateOpts),
+    /// Find offsets for someone else's public key, without knowing their private key. They add the
+    /// offset to their private key with `feeless private combine`.
+    SplitKey(SplitKeyOpts),
 }
 
 #[derive(Clap)]
@@ -126,10 +308,25 @@ struct PrivateOpts {
     pub common_opts: CommonOpts,
 }
 
+#[derive(Clap)]
+struct SplitKeyOpts {
+    /// The public key to find offsets for.
+    #[clap(short, long, group = "key")]
+    public: Option<crate::Public>,
+
+    /// The address to find offsets for, instead of a public key.
+    #[clap(short, long, group = "key")]
+    address: Option<crate::Address>,
+
+    #[clap(flatten)]
+    pub common_opts: CommonOpts,
+}
+
 #[derive(Clap)]
 struct CommonOpts {
-    /// Match on this string. By default will match the start and end.
-    matching: String,
+    /// Match on these strings. By default will match the start and end.
+    #[clap(required = true)]
+    matching: Vec<String>,
 
     /// Match on start only. Default is start and end.
     #[clap(short, long, group = "match")]
@@ -151,7 +348,51 @@ struct CommonOpts {
     #[clap(short, long)]
     tasks: Option<usize>,
 
-    /// Stop after finding this many matches.
+    /// Stop looking for a pattern after finding this many matches for it.
     #[clap(short, long)]
     limit: Option<usize>,
+
+    /// Stop after finding this many matches in total, including any already in the output.
+    #[clap(long)]
+    total: Option<usize>,
+
+    /// The first account index to check for seeds and phrases.
+    #[clap(long, default_value = "0")]
+    index: u32,
+
+    /// Check this many account indexes of each seed or phrase.
+    #[clap(long, default_value = "1")]
+    scan: u32,
+
+    /// Append results to this file. Matches already in the file count towards `--limit`, so an
+    /// interrupted search can be resumed.
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+
+    /// How to write results: plain, csv or json (one object per line). Defaults to csv for files.
+    #[clap(short, long)]
+    format: Option<OutputFormat>,
+}
+
+impl CommonOpts {
+    fn to_match(&self, matching: &str) -> anyhow::Result<vanity::Match> {
+        Ok(if self.start {
+            vanity::Match::start(matching)
+        } else if self.end {
+            vanity::Match::end(matching)
+        } else if self.regex {
+            vanity::Match::regex(matching)?
+        } else {
+            vanity::Match::start_or_end(matching)
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString)]
+#[strum(serialize_all = "snake_case")]
+enum OutputFormat {
+    /// `address,secret`
+    Plain,
+    Csv,
+    Json,
 }
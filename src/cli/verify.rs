@@ -1,10 +1,9 @@
-use crate::keys::armor::Armor;
-use crate::{Address, Public, Signature};
+use crate::blocks::BlockHash;
+use crate::{Address, Armor, Public, Signature};
 use anyhow::anyhow;
 use clap::Clap;
 use std::io;
 use std::io::Read;
-use std::str::FromStr;
 
 #[derive(Clap)]
 pub struct VerifyOpts {
@@ -17,9 +16,14 @@ pub struct VerifyOpts {
     #[clap(short, long)]
     signature: Option<Signature>,
 
-    #[clap(short, long)]
+    #[clap(short, long, group = "signed")]
     message: Option<String>,
 
+    /// Verify the signature of a block hash, rather than of a message.
+    #[clap(long, group = "signed")]
+    hash: Option<BlockHash>,
+
+    /// Read a signed message from stdin, in the armor, JSON or compact format.
     #[clap(long)]
     armor: bool,
 }
@@ -39,18 +43,12 @@ impl VerifyOpts {
     fn handle_armor(&self) -> anyhow::Result<()> {
         let mut buffer = String::new();
         io::stdin().read_to_string(&mut buffer)?;
-        let armor = Armor::from_str(&buffer)?;
+        let armor = Armor::parse(&buffer)?;
         armor.verify()?;
         Ok(())
     }
 
     fn handle_args(&self) -> anyhow::Result<()> {
-        let message = if let Some(message) = &self.message {
-            message
-        } else {
-            return Err(anyhow!("Please specify a message."));
-        };
-
         let signature = if let Some(signature) = &self.signature {
             signature
         } else {
@@ -65,7 +63,11 @@ impl VerifyOpts {
             return Err(anyhow!("Please specify an address or public key."));
         };
 
-        public.verify(message.as_bytes(), signature)?;
+        match (&self.message, &self.hash) {
+            (Some(message), _) => public.verify_message(message.as_bytes(), signature)?,
+            (None, Some(hash)) => public.verify(hash.as_bytes(), signature)?,
+            (None, None) => return Err(anyhow!("Please specify a message.")),
+        }
         Ok(())
     }
 }
@@ -1,9 +1,28 @@
 use crate::cli::StringOrStdin;
-use crate::keys::armor::Armor;
 use crate::paths::PathsOpts;
-use crate::wallet::{Wallet, WalletId, WalletManager};
-use crate::Phrase;
+use crate::phrase::Derivation;
+#[cfg(feature = "rpc_client")]
+use crate::rpc::client::RPCClient;
+#[cfg(all(feature = "rpc_client", unix))]
+use crate::wallet::RemoteSigner;
+use crate::wallet::{
+    serve_signer, Backup, Kdf, NanoNodeKeys, NanoNodeWallet, Passphrase, PhraseWallet, Wallet,
+    WalletId, WalletManager,
+};
+#[cfg(feature = "rpc_client")]
+use crate::wallet::{AutoReceive, Journal, Signer, WorkCache};
+use crate::{encryption, Armor, ArmorFormat, EncryptedMessage, Phrase};
+#[cfg(feature = "rpc_client")]
+use crate::{Address, Raw};
+use anyhow::anyhow;
 use clap::Clap;
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+use std::io::{BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+#[cfg(feature = "rpc_client")]
+use std::time::Duration;
 
 #[derive(Clap)]
 pub struct WalletOpts {
@@ -17,13 +36,12 @@ impl WalletOpts {
             Command::New(c) => match &c.create_type {
                 CreateType::Phrase(o) => {
                     let (manager, wallet_id) = WalletOpts::create(&o.opts).await?;
-                    manager
-                        .add_random_phrase(
-                            wallet_id.to_owned(),
-                            o.phrase_opts.words.0.to_owned(),
-                            o.phrase_opts.language.language.to_owned(),
-                        )
-                        .await?;
+                    let phrase = Phrase::random(
+                        o.phrase_opts.words.0.to_owned(),
+                        o.phrase_opts.language.language.to_owned(),
+                    );
+                    let wallet = o.phrase_wallet_opts.wallet(phrase);
+                    manager.add(wallet_id.to_owned(), wallet).await?;
                     println!("{}", wallet_id);
                 }
                 CreateType::Seed(o) => {
@@ -44,7 +62,7 @@ impl WalletOpts {
                         o.language.language.to_owned(),
                         o.words.to_owned().resolve()?.as_str(),
                     )?;
-                    let wallet = Wallet::Phrase(phrase);
+                    let wallet = o.phrase_wallet_opts.wallet(phrase);
                     manager.add(wallet_id.to_owned(), wallet).await?;
                     println!("{}", wallet_id);
                 }
@@ -60,6 +78,72 @@ impl WalletOpts {
                     manager.add(wallet_id.to_owned(), wallet).await?;
                     println!("{}", wallet_id);
                 }
+                ImportType::Backup(o) => {
+                    let manager = WalletManager::new(&o.paths_opts.wallet_path()?);
+                    manager.ensure().await?;
+                    let backup = Backup::from_json(&read_input(&o.file)?, o.passphrase.as_deref())?;
+                    let ids: Vec<WalletId> = backup.wallets.iter().map(|w| w.id.clone()).collect();
+                    manager.import(backup.wallets).await?;
+                    for id in ids {
+                        println!("{}", id);
+                    }
+                }
+                ImportType::NanoNode(o) => {
+                    let (manager, wallet_id) = WalletOpts::create(&o.opts).await?;
+                    let kdf_work =
+                        NanoNodeWallet::kdf_work(o.opts.common_opts.paths_opts.network());
+                    let keys = NanoNodeWallet::from_json(&read_input(&o.file)?)?
+                        .decrypt(&o.password, kdf_work)?;
+                    let wallets = keys.to_backup_wallets(wallet_id);
+                    let ids: Vec<WalletId> = wallets.iter().map(|w| w.id.clone()).collect();
+                    manager.import(wallets).await?;
+                    for id in ids {
+                        println!("{}", id);
+                    }
+                }
+            },
+            Command::Export(o) => match &o.export_type {
+                ExportType::Backup(o) => {
+                    let manager = WalletManager::new(&o.paths_opts.wallet_path()?);
+                    let backup = Backup::new(manager.export().await?);
+                    println!(
+                        "{}",
+                        backup.to_json(o.passphrase.as_deref(), &Kdf::default())?
+                    );
+                }
+                ExportType::NanoNode(o) => {
+                    let manager = WalletManager::new(&o.opts.paths_opts.wallet_path()?);
+                    let wallet_id = o.opts.wallet_id()?;
+                    let backup = manager
+                        .export()
+                        .await?
+                        .into_iter()
+                        .find(|w| w.id == wallet_id)
+                        .ok_or_else(|| anyhow!("Wallet reference not found: {:?}", &wallet_id))?;
+                    let accounts = if o.accounts.is_empty() {
+                        backup.accounts
+                    } else {
+                        o.accounts.to_owned()
+                    };
+                    let keys = NanoNodeKeys::from_wallet(&backup.wallet, &accounts)?;
+                    let kdf_work = NanoNodeWallet::kdf_work(o.opts.paths_opts.network());
+                    let wallet = NanoNodeWallet::encrypt(&keys, &o.password, kdf_work)?;
+                    println!("{}", wallet.to_json());
+                }
+                ExportType::Qr(o) => {
+                    let wallet = WalletOpts::read(&o.opts).await?;
+                    let secret = wallet.to_qr_string();
+                    if o.render {
+                        let code = QrCode::new(secret.as_bytes())?;
+                        let image = code
+                            .render::<Dense1x2>()
+                            .dark_color(Dense1x2::Light)
+                            .light_color(Dense1x2::Dark)
+                            .build();
+                        println!("{}", image);
+                    }
+                    println!("{}", secret);
+                }
             },
             Command::Delete(o) => {
                 let (manager, wallet_id) = WalletOpts::delete(&o.opts).await?;
@@ -80,23 +164,125 @@ impl WalletOpts {
             }
             Command::Sign(o) => {
                 let wallet = WalletOpts::read(&o.opts).await?;
-                let string = o.message.to_owned().resolve()?;
-                let message = string.as_bytes();
-                let signed = wallet.private(o.address)?.sign(message)?;
-                if o.armor {
-                    println!("{}", Armor::new(string, wallet.address(o.address)?, signed));
+                let message = o.message.to_owned().resolve()?;
+                let armor = Armor::sign(message, &wallet.private(o.address)?)?;
+                let format = if o.armor {
+                    Some(ArmorFormat::Armor)
+                } else {
+                    o.format
+                };
+                match format {
+                    Some(format) => println!("{}", armor.format(format)?),
+                    None => println!("{}", armor.signature()),
+                }
+            }
+            Command::Encrypt(o) => {
+                let wallet = WalletOpts::read(&o.opts).await?;
+                let message = o.message.to_owned().resolve()?;
+                let encrypted = encryption::encrypt(
+                    &o.to.to_public(),
+                    &wallet.private(o.address)?,
+                    message.as_bytes(),
+                )?;
+                println!("{}", encrypted);
+            }
+            Command::Decrypt(o) => {
+                let wallet = WalletOpts::read(&o.opts).await?;
+                let encrypted: EncryptedMessage = o.message.to_owned().resolve()?.parse()?;
+                let decrypted = encryption::decrypt(&encrypted, &wallet.private(o.address)?)?;
+                println!("{}", String::from_utf8(decrypted)?);
+            }
+            Command::Signer(o) => match &o.socket {
+                None => {
+                    if o.opts.passphrase.is_none()
+                        && WalletOpts::load(&o.opts).await?.needs_passphrase()
+                    {
+                        return Err(anyhow!(
+                            "A passphrase is needed since stdin is used by the signer protocol"
+                        ));
+                    }
+                    let wallet = WalletOpts::read(&o.opts).await?;
+                    serve_signer(&wallet, tokio::io::stdin(), tokio::io::stdout()).await?;
+                }
+                #[cfg(unix)]
+                Some(path) => {
+                    let wallet = Arc::new(WalletOpts::read(&o.opts).await?);
+                    let listener = tokio::net::UnixListener::bind(path)?;
+                    eprintln!("Listening on {:?}", path);
+                    loop {
+                        let (stream, _) = listener.accept().await?;
+                        let wallet = wallet.clone();
+                        tokio::spawn(async move {
+                            let (reader, writer) = tokio::io::split(stream);
+                            if let Err(err) = serve_signer(&*wallet, reader, writer).await {
+                                tracing::warn!("Signer connection failed: {:?}", err);
+                            }
+                        });
+                    }
+                }
+                #[cfg(not(unix))]
+                Some(_) => return Err(anyhow!("Unix sockets are not supported here")),
+            },
+            #[cfg(feature = "rpc_client")]
+            Command::Autoreceive(o) => {
+                let signer: Arc<dyn Signer> = match &o.signer_socket {
+                    #[cfg(unix)]
+                    Some(path) => Arc::new(RemoteSigner::connect(path).await?),
+                    #[cfg(not(unix))]
+                    Some(_) => return Err(anyhow!("Unix sockets are not supported here")),
+                    None => Arc::new(WalletOpts::read(&o.opts).await?),
+                };
+                let journal_name = format!("autoreceive-{}.journal", o.opts.wallet_id()?);
+                let journal_path = o.opts.paths_opts.data_path(Path::new(&journal_name))?;
+                let journal = Journal::open(journal_path).await?;
+                let cache_name = format!("work-{}.json", o.opts.wallet_id()?);
+                let cache_path = o.opts.paths_opts.data_path(Path::new(&cache_name))?;
+                let work_cache = WorkCache::open(cache_path).await?;
+
+                let mut client = RPCClient::new(&o.url);
+                if let Some(auth) = &o.auth {
+                    client.authorization(auth);
+                }
+
+                let mut auto = AutoReceive::new(Arc::new(client), signer, journal);
+                auto.accounts(o.accounts)
+                    .threshold(o.threshold.to_owned())
+                    .count(o.count)
+                    .work_cache(work_cache);
+                if let Some(representative) = &o.representative {
+                    auto.representative(representative.to_public());
+                }
+
+                if o.once {
+                    for entry in auto.poll().await? {
+                        println!("{}", serde_json::to_string(&entry)?);
+                    }
                 } else {
-                    println!("{}", signed);
+                    auto.run(Duration::from_secs(o.interval)).await?;
                 }
             }
         };
         Ok(())
     }
 
+    /// Load a wallet, unlocking it with the passphrase option or a prompt if it needs one.
     async fn read(o: &CommonOpts) -> anyhow::Result<Wallet> {
+        let wallet = WalletOpts::load(o).await?;
+        if !wallet.needs_passphrase() {
+            return Ok(wallet);
+        }
+
+        let passphrase = match &o.passphrase {
+            Some(passphrase) => passphrase.to_owned(),
+            None => prompt_passphrase()?,
+        };
+        Ok(wallet.unlock(&passphrase)?)
+    }
+
+    /// Load a wallet without unlocking it.
+    async fn load(o: &CommonOpts) -> anyhow::Result<Wallet> {
         let manager = WalletManager::new(&o.paths_opts.wallet_path()?);
-        let wallet = manager.wallet(&o.wallet_id()?).await?;
-        Ok(wallet)
+        manager.wallet(&o.wallet_id()?).await
     }
 
     async fn create(o: &CommonOptsCreate) -> anyhow::Result<(WalletManager, WalletId)> {
@@ -122,6 +308,9 @@ enum Command {
     /// Import an existing wallet. If the wallet file doesn't exist, it will be created.
     Import(ImportOpts),
 
+    /// Export wallets to a backup, a nano_node wallet, or a QR code.
+    Export(ExportOpts),
+
     /// Output the private key of a wallet.
     Private(PrivateOpts),
 
@@ -134,8 +323,21 @@ enum Command {
     /// Sign a message using a key in this wallet.
     Sign(SignOpts),
 
+    /// Encrypt a message that only the owner of an address, or this wallet, can read.
+    Encrypt(EncryptOpts),
+
+    /// Decrypt a message sent to or from a key in this wallet.
+    Decrypt(DecryptOpts),
+
     /// Delete an existing wallet.
     Delete(DeleteOpts),
+
+    /// Sign blocks for other processes, over stdin/stdout or a Unix socket.
+    Signer(SignerOpts),
+
+    /// Keep receiving pending blocks for the accounts in a wallet via RPC.
+    #[cfg(feature = "rpc_client")]
+    Autoreceive(AutoreceiveOpts),
 }
 
 #[derive(Clap)]
@@ -146,6 +348,11 @@ struct CommonOpts {
     /// Wallet ID.
     #[clap(short, long, env = "FEELESS_WALLET_ID")]
     id: Option<WalletId>,
+
+    /// The passphrase of a phrase wallet that was created with `--prompt-passphrase`. If it isn't
+    /// given, it will be asked for.
+    #[clap(long, env = "FEELESS_PASSPHRASE")]
+    passphrase: Option<String>,
 }
 
 impl CommonOpts {
@@ -200,11 +407,48 @@ enum CreateType {
     Private(CreatePrivateOpts),
 }
 
+/// How a phrase wallet is unlocked and derives its keys.
+#[derive(Clap)]
+struct PhraseWalletOpts {
+    /// A BIP39 passphrase to store with the wallet.
+    #[clap(long, conflicts_with = "prompt-passphrase")]
+    store_passphrase: Option<String>,
+
+    /// Don't store the BIP39 passphrase. It will be needed every time the wallet is used.
+    #[clap(long)]
+    prompt_passphrase: bool,
+
+    /// How keys are derived: bip44 (or ledger) for `m/44'/165'/{account}'`, seed to use the phrase
+    /// as a Nano seed, or a custom path such as `m/44'/165'/0'/{account}'`.
+    #[clap(long, default_value = "bip44")]
+    derivation: Derivation,
+}
+
+impl PhraseWalletOpts {
+    fn wallet(&self, phrase: Phrase) -> Wallet {
+        let passphrase = if self.prompt_passphrase {
+            Passphrase::Prompt
+        } else if let Some(passphrase) = &self.store_passphrase {
+            Passphrase::Stored(passphrase.to_owned())
+        } else {
+            Passphrase::None
+        };
+        Wallet::Phrase(PhraseWallet {
+            phrase,
+            passphrase,
+            derivation: self.derivation.to_owned(),
+        })
+    }
+}
+
 #[derive(Clap)]
 struct CreatePhraseOpts {
     #[clap(flatten)]
     pub phrase_opts: super::phrase::New,
 
+    #[clap(flatten)]
+    phrase_wallet_opts: PhraseWalletOpts,
+
     #[clap(flatten)]
     pub opts: CommonOptsCreate,
 }
@@ -232,6 +476,12 @@ enum ImportType {
     Phrase(ImportPhraseOpts),
     Seed(ImportSeedOpts),
     Private(ImportPrivateOpts),
+
+    /// Import every wallet from a feeless backup.
+    Backup(ImportBackupOpts),
+
+    /// Import a wallet exported by nano_node's `wallet_export` RPC.
+    NanoNode(ImportNanoNodeOpts),
 }
 
 #[derive(Clap)]
@@ -241,6 +491,9 @@ struct ImportPhraseOpts {
     #[clap(flatten)]
     pub(crate) language: crate::cli::phrase::LanguageOpt,
 
+    #[clap(flatten)]
+    phrase_wallet_opts: PhraseWalletOpts,
+
     #[clap(flatten)]
     opts: CommonOptsCreate,
 }
@@ -288,6 +541,84 @@ struct AddressOpts {
     opts: CommonOpts,
 }
 
+#[derive(Clap)]
+struct ImportBackupOpts {
+    /// The backup file, or `-` for stdin.
+    file: PathBuf,
+
+    /// Passphrase for an encrypted backup.
+    #[clap(long, env = "FEELESS_BACKUP_PASSPHRASE")]
+    passphrase: Option<String>,
+
+    #[clap(flatten)]
+    paths_opts: PathsOpts,
+}
+
+#[derive(Clap)]
+struct ImportNanoNodeOpts {
+    /// The exported wallet JSON file, or `-` for stdin.
+    file: PathBuf,
+
+    /// The nano_node wallet password.
+    #[clap(long, default_value = "", env = "FEELESS_NANO_NODE_PASSWORD")]
+    password: String,
+
+    #[clap(flatten)]
+    opts: CommonOptsCreate,
+}
+
+#[derive(Clap)]
+struct ExportOpts {
+    #[clap(subcommand)]
+    export_type: ExportType,
+}
+
+#[derive(Clap)]
+enum ExportType {
+    /// Export every wallet into a feeless backup.
+    Backup(ExportBackupOpts),
+
+    /// Export a wallet in the format of nano_node's `wallet_export` RPC.
+    NanoNode(ExportNanoNodeOpts),
+
+    /// Output the secret of a wallet in a QR code friendly format.
+    Qr(ExportQrOpts),
+}
+
+#[derive(Clap)]
+struct ExportBackupOpts {
+    /// Encrypt the backup with this passphrase.
+    #[clap(long, env = "FEELESS_BACKUP_PASSPHRASE")]
+    passphrase: Option<String>,
+
+    #[clap(flatten)]
+    paths_opts: PathsOpts,
+}
+
+#[derive(Clap)]
+struct ExportNanoNodeOpts {
+    /// The password for the nano_node wallet.
+    #[clap(long, default_value = "", env = "FEELESS_NANO_NODE_PASSWORD")]
+    password: String,
+
+    /// Comma separated account indexes to include. Defaults to the indexes known to be in use.
+    #[clap(long, use_delimiter = true)]
+    accounts: Vec<u32>,
+
+    #[clap(flatten)]
+    opts: CommonOpts,
+}
+
+#[derive(Clap)]
+struct ExportQrOpts {
+    /// Also draw the QR code in the terminal.
+    #[clap(long)]
+    render: bool,
+
+    #[clap(flatten)]
+    opts: CommonOpts,
+}
+
 #[derive(Clap)]
 struct DeleteOpts {
     #[clap(flatten)]
@@ -299,12 +630,165 @@ struct SignOpts {
     message: StringOrStdin<String>,
 
     /// Use the feeless armor format which includes the address, message and signature.
-    #[clap(long)]
+    #[clap(long, group = "output")]
     armor: bool,
 
+    /// Output the address and message with the signature: armor, json or compact.
+    #[clap(short, long, group = "output")]
+    format: Option<ArmorFormat>,
+
+    #[clap(short, long, default_value = "0")]
+    address: u32,
+
+    #[clap(flatten)]
+    opts: CommonOpts,
+}
+
+#[derive(Clap)]
+struct EncryptOpts {
+    /// The address to encrypt the message for.
+    to: crate::Address,
+
+    message: StringOrStdin<String>,
+
+    #[clap(short, long, default_value = "0")]
+    address: u32,
+
+    #[clap(flatten)]
+    opts: CommonOpts,
+}
+
+#[derive(Clap)]
+struct DecryptOpts {
+    /// The armored encrypted message, or `-` to read it from stdin.
+    message: StringOrStdin<String>,
+
     #[clap(short, long, default_value = "0")]
     address: u32,
 
     #[clap(flatten)]
     opts: CommonOpts,
 }
+
+#[cfg(feature = "rpc_client")]
+#[derive(Clap)]
+struct AutoreceiveOpts {
+    /// The URL of the RPC server.
+    #[clap(
+        long,
+        short,
+        default_value = "http://localhost:7076",
+        env = "FEELESS_RPC_URL"
+    )]
+    url: String,
+
+    /// Send a string in the HTTP authorization header.
+    #[clap(long, env = "FEELESS_RPC_AUTH")]
+    auth: Option<String>,
+
+    /// How many accounts of the wallet to watch, starting from index 0.
+    #[clap(long, default_value = "1")]
+    accounts: u32,
+
+    /// Ignore pending blocks smaller than this amount in raw.
+    #[clap(long, default_value = "1")]
+    threshold: Raw,
+
+    /// The maximum pending blocks to receive per account per poll.
+    #[clap(long, default_value = "10")]
+    count: u64,
+
+    /// Representative for newly opened accounts. Defaults to the account itself.
+    #[clap(long)]
+    representative: Option<Address>,
+
+    /// Seconds to wait between polls.
+    #[clap(long, default_value = "10")]
+    interval: u64,
+
+    /// Poll once, print what was received as JSON lines, then exit.
+    #[clap(long)]
+    once: bool,
+
+    /// Sign blocks with a `feeless wallet signer` listening on this Unix socket, instead of
+    /// loading the wallet in this process.
+    #[clap(long)]
+    signer_socket: Option<PathBuf>,
+
+    #[clap(flatten)]
+    opts: CommonOpts,
+}
+
+#[derive(Clap)]
+struct SignerOpts {
+    /// Listen on a Unix socket instead of using stdin and stdout.
+    #[clap(long)]
+    socket: Option<PathBuf>,
+
+    #[clap(flatten)]
+    opts: CommonOpts,
+}
+
+/// Read a file, or stdin when the path is `-`.
+fn read_input(path: &Path) -> anyhow::Result<String> {
+    let mut buffer = String::new();
+    if path.as_os_str() == "-" {
+        std::io::stdin().read_to_string(&mut buffer)?;
+    } else {
+        buffer = std::fs::read_to_string(path)?;
+    }
+    Ok(buffer)
+}
+
+/// Ask for a passphrase on stderr so it doesn't end up in the output, and read it from stdin.
+///
+/// When stdin is a terminal the passphrase isn't shown as it's typed. This is only supported on
+/// unix.
+fn prompt_passphrase() -> anyhow::Result<String> {
+    eprint!("Passphrase: ");
+    std::io::stderr().flush()?;
+    let mut line = String::new();
+    if atty::is(atty::Stream::Stdin) {
+        let hidden = HiddenInput::new()?;
+        std::io::stdin().lock().read_line(&mut line)?;
+        drop(hidden);
+        // The newline typed after the passphrase wasn't echoed either.
+        eprintln!();
+    } else {
+        std::io::stdin().lock().read_line(&mut line)?;
+    }
+    Ok(line.trim_end_matches(&['\r', '\n'][..]).to_string())
+}
+
+/// Turns off echoing of the terminal on stdin, until it's dropped.
+struct HiddenInput;
+
+impl HiddenInput {
+    fn new() -> anyhow::Result<Self> {
+        #[cfg(unix)]
+        stty("-echo")?;
+        Ok(Self)
+    }
+}
+
+impl Drop for HiddenInput {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Err(err) = stty("echo") {
+            tracing::warn!("Could not turn the terminal echo back on: {:?}", err);
+        }
+    }
+}
+
+/// Change a setting of the terminal on stdin.
+#[cfg(unix)]
+fn stty(setting: &str) -> anyhow::Result<()> {
+    let status = std::process::Command::new("stty")
+        .arg(setting)
+        .stdin(std::process::Stdio::inherit())
+        .status()?;
+    if !status.success() {
+        return Err(anyhow!("stty {} failed: {}", setting, status));
+    }
+    Ok(())
+}
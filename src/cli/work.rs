@@ -1,7 +1,9 @@
 use crate::blocks::BlockHash;
-use crate::pow::{Subject, Work};
-use crate::Difficulty;
+use crate::pow::{Subject, WorkGenerator};
+use crate::{Difficulty, Multiplier};
 use clap::Clap;
+use serde::Serialize;
+use std::time::Duration;
 use tracing::info;
 
 #[derive(Clap)]
@@ -20,6 +22,37 @@ pub struct WorkOpts {
     /// The base difficulty in hex.
     #[clap(short, long, group = "base")]
     difficulty: Option<Difficulty>,
+
+    /// Multiply the base difficulty, e.g. `2` takes twice as long on average.
+    #[clap(short, long)]
+    multiplier: Option<Multiplier>,
+
+    /// Number of threads to use. Defaults to the number of CPUs.
+    #[clap(short, long)]
+    threads: Option<usize>,
+
+    /// Give up after this many seconds.
+    #[clap(long)]
+    timeout: Option<u64>,
+
+    /// Print the hash rate to stderr every second while generating.
+    #[clap(short, long)]
+    progress: bool,
+
+    /// Output as JSON.
+    #[clap(long)]
+    json: bool,
+}
+
+/// The output of the work command, similar to the `work_generate` RPC response.
+#[derive(Serialize)]
+struct WorkOutput {
+    hash: BlockHash,
+    work: crate::Work,
+    difficulty: Difficulty,
+    multiplier: String,
+    hashes: u64,
+    hash_rate: f64,
 }
 
 impl WorkOpts {
@@ -28,16 +61,54 @@ impl WorkOpts {
         // It really doesn't matter which it is, pow doesn't care, so we just pick one.
         let subject = Subject::Hash(self.hash.to_owned());
 
-        let difficulty = if let Some(d) = &self.difficulty {
+        let base = if let Some(d) = &self.difficulty {
             d.to_owned()
         } else if self.receive {
             Difficulty::receive()
         } else {
             Difficulty::normal()
         };
+        let difficulty = match &self.multiplier {
+            Some(multiplier) => Difficulty::from_multiplier(multiplier, &base),
+            None => base.to_owned(),
+        };
+
+        let mut generator = WorkGenerator::new();
+        if let Some(threads) = self.threads {
+            generator.threads(threads);
+        }
+        if let Some(timeout) = self.timeout {
+            generator.timeout(Duration::from_secs(timeout));
+        }
+        if self.progress {
+            generator.on_progress(Duration::from_secs(1), |p| {
+                eprintln!(
+                    "{:.2} MH/s, {} hashes in {:.1}s",
+                    p.hash_rate() / 1_000_000f64,
+                    p.hashes,
+                    p.elapsed.as_secs_f64()
+                )
+            });
+        }
+
         info!("Finding work for {:?} at {:?}", &subject, &difficulty);
-        let result = Work::generate(&subject, &difficulty)?;
-        dbg!(result);
+        let generated = generator.generate(&subject, &difficulty)?;
+        let output = WorkOutput {
+            hash: self.hash.to_owned(),
+            work: generated.work,
+            multiplier: format!("{:.2}", generated.difficulty.to_multiplier(&base)),
+            difficulty: generated.difficulty,
+            hashes: generated.progress.hashes,
+            hash_rate: generated.progress.hash_rate(),
+        };
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        } else {
+            println!("{}", output.work);
+            println!("Difficulty: {:?}", output.difficulty);
+            println!("Multiplier: {}", output.multiplier);
+        }
         Ok(())
     }
 }
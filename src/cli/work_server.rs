@@ -0,0 +1,50 @@
+use crate::rpc::client::RPCClient;
+use crate::rpc::work_server::WorkServer;
+use crate::Difficulty;
+use clap::Clap;
+use std::net::SocketAddr;
+
+#[derive(Clap)]
+pub struct WorkServerOpts {
+    /// The address to listen on. The port differs from the RPC server's so both can run on one
+    /// machine.
+    #[clap(short, long, default_value = "127.0.0.1:7000")]
+    address: SocketAddr,
+
+    /// CPU threads used for each job. Defaults to the number of CPUs.
+    #[clap(short, long)]
+    threads: Option<usize>,
+
+    /// How many jobs are worked on at the same time.
+    #[clap(short, long, default_value = "1")]
+    workers: usize,
+
+    /// Don't generate work on this machine, only use peers.
+    #[clap(long)]
+    no_local: bool,
+
+    /// URL of a work peer to race against. Can be given multiple times.
+    #[clap(short, long, multiple_occurrences = true, number_of_values = 1)]
+    peer: Vec<String>,
+
+    /// The difficulty used for requests without one, and the base for multipliers.
+    #[clap(short, long)]
+    difficulty: Option<Difficulty>,
+}
+
+impl WorkServerOpts {
+    pub async fn handle(&self) -> anyhow::Result<()> {
+        let mut server = WorkServer::new();
+        server.workers(self.workers).local(!self.no_local);
+        if let Some(threads) = self.threads {
+            server.threads(threads);
+        }
+        if let Some(difficulty) = &self.difficulty {
+            server.base_difficulty(difficulty.to_owned());
+        }
+        for peer in &self.peer {
+            server.peer(RPCClient::new(peer));
+        }
+        server.serve(self.address).await
+    }
+}
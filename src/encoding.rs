@@ -45,7 +45,7 @@ where
     <T as std::str::FromStr>::Err: std::fmt::Display,
 {
     let s: &str = Deserialize::deserialize(deserializer)?;
-    Ok(T::from_str(s).map_err(serde::de::Error::custom)?)
+    T::from_str(s).map_err(serde::de::Error::custom)
 }
 
 pub fn deserialize_from_string<'de, T, D>(
@@ -57,7 +57,7 @@ where
     <T as std::str::FromStr>::Err: std::fmt::Display,
 {
     let s: String = Deserialize::deserialize(deserializer)?;
-    Ok(T::from_str(s.as_str()).map_err(serde::de::Error::custom)?)
+    T::from_str(s.as_str()).map_err(serde::de::Error::custom)
 }
 
 pub fn blake2b(size: usize, data: &[u8]) -> Box<[u8]> {
@@ -212,7 +212,7 @@ macro_rules! hexify {
             {
                 use ::std::str::FromStr;
                 let s: String = serde::Deserialize::deserialize(deserializer)?;
-                Ok(Self::from_str(&s).map_err(serde::de::Error::custom)?)
+                Self::from_str(&s).map_err(serde::de::Error::custom)
             }
         }
     };
@@ -59,9 +59,30 @@ pub enum Error {
     #[error("Possible language codes are {0}")]
     LanguageError(String),
 
+    #[error("Invalid derivation: {0}")]
+    InvalidDerivation(String),
+
+    #[error("This wallet needs a passphrase to be unlocked")]
+    PassphraseRequired,
+
+    #[error("Work generation was cancelled")]
+    WorkCancelled,
+
+    #[error("Work generation timed out after {0:?}")]
+    WorkTimeout(std::time::Duration),
+
+    #[error("Invalid multiplier: {0}")]
+    InvalidMultiplier(String),
+
     #[error("Invalid armor content: {0}")]
     InvalidArmor(String),
 
+    #[error("Could not decrypt: {0}")]
+    DecryptionFailed(String),
+
+    #[error("Multisig error: {0}")]
+    Multisig(String),
+
     #[cfg(any(feature = "rpc_client", feature = "rpc_server"))]
     #[error("RPC request failed: {0}")]
     RPCRequestFailed(#[from] reqwest::Error),
@@ -74,4 +95,22 @@ pub enum Error {
 
     #[error("RPC error: {0}")]
     RPCError(String),
+
+    #[cfg(feature = "ws_client")]
+    #[error("WebSocket error: {0}")]
+    WebSocket(#[source] Box<tokio_tungstenite::tungstenite::Error>),
+
+    #[error("Bad WebSocket message: {err:?} message: {message}")]
+    BadWebSocketMessage {
+        err: serde_json::Error,
+        message: String,
+    },
+}
+
+// Boxed since it's much larger than the other variants, which would make every Result large.
+#[cfg(feature = "ws_client")]
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(err: tokio_tungstenite::tungstenite::Error) -> Self {
+        Error::WebSocket(Box::new(err))
+    }
 }
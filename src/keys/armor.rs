@@ -1,8 +1,46 @@
-use crate::{Address, Error, Result, Signature};
+//! Signed messages, e.g. proofs of owning an address.
+//!
+//! Messages aren't signed directly, otherwise a signed message could double as the signature of a
+//! block whose hash happens to be the message. Instead the signature is over the blake2b hash of
+//! [MESSAGE_PREFIX] followed by the message, which can't be a block hash.
+//!
+//! A signed message can be written in three ways, see [ArmorFormat].
+use crate::encoding::blake2b;
+use crate::{Address, Error, Private, Public, Result, Signature};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
 use std::fmt::{Display, Formatter};
-use std::str::{FromStr, Split};
+use std::str::FromStr;
+use strum_macros::EnumString;
 
-#[derive(Debug)]
+/// Prepended to every message before hashing and signing.
+pub const MESSAGE_PREFIX: &str = "Nano Signed Message:\n";
+
+/// The hash that is signed for a message.
+pub fn message_hash(message: &[u8]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(MESSAGE_PREFIX.len() + message.len());
+    data.extend_from_slice(MESSAGE_PREFIX.as_bytes());
+    data.extend_from_slice(message);
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&blake2b(32, &data));
+    hash
+}
+
+/// How a signed message is written.
+#[derive(Debug, Clone, Copy, PartialEq, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum ArmorFormat {
+    /// Human readable blocks for the message, address and signature.
+    Armor,
+
+    /// An object with `message`, `address` and `signature` fields.
+    Json,
+
+    /// One line of URL safe base64: the public key, signature, then the message.
+    Compact,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Armor {
     message: String,
     address: Address,
@@ -23,10 +61,76 @@ impl Armor {
         }
     }
 
+    /// Sign a message with the prefixed hash.
+    pub fn sign(message: String, private: &Private) -> Result<Self> {
+        let signature = private.sign_message(message.as_bytes())?;
+        let address = private.to_address()?;
+        Ok(Self::new(message, address, signature))
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
     pub fn verify(&self) -> Result<()> {
         self.address
             .to_public()
-            .verify(self.message.as_bytes(), &self.signature)
+            .verify_message(self.message.as_bytes(), &self.signature)
+    }
+
+    pub fn format(&self, format: ArmorFormat) -> Result<String> {
+        Ok(match format {
+            ArmorFormat::Armor => self.to_string(),
+            ArmorFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|e| Error::InvalidArmor(format!("Invalid JSON: {}", e)))?,
+            ArmorFormat::Compact => self.to_compact(),
+        })
+    }
+
+    /// Parse any of the formats, working out which one it is.
+    pub fn parse(s: &str) -> Result<Self> {
+        let trimmed = s.trim();
+        if trimmed.starts_with('{') {
+            serde_json::from_str(trimmed)
+                .map_err(|e| Error::InvalidArmor(format!("Invalid JSON: {}", e)))
+        } else if trimmed.starts_with(Self::BEGIN_MESSAGE) {
+            Self::from_str(s)
+        } else {
+            Self::from_compact(trimmed)
+        }
+    }
+
+    fn to_compact(&self) -> String {
+        let mut bytes = vec![];
+        bytes.extend_from_slice(self.address.to_public().as_bytes());
+        bytes.extend_from_slice(self.signature.as_bytes());
+        bytes.extend_from_slice(self.message.as_bytes());
+        base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD)
+    }
+
+    fn from_compact(s: &str) -> Result<Self> {
+        let bytes = base64::decode_config(s, base64::URL_SAFE_NO_PAD)
+            .map_err(|e| Error::InvalidArmor(format!("Invalid base64: {}", e)))?;
+        if bytes.len() < Public::LEN + Signature::LEN {
+            return Err(Error::InvalidArmor("Compact form is too short".into()));
+        }
+        let (public, rest) = bytes.split_at(Public::LEN);
+        let (signature, message) = rest.split_at(Signature::LEN);
+        let message = String::from_utf8(message.to_vec())
+            .map_err(|_| Error::InvalidArmor("Message is not UTF-8".into()))?;
+        Ok(Self::new(
+            message,
+            Public::try_from(public)?.to_address(),
+            Signature::try_from(signature)?,
+        ))
     }
 }
 
@@ -34,8 +138,15 @@ impl Display for Armor {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.write_str(Self::BEGIN_MESSAGE)?;
         f.write_str("\n")?;
-        f.write_str(&self.message.to_string())?;
-        f.write_str("\n")?;
+        for line in self.message.split('\n') {
+            // Dash escape lines like in OpenPGP, so a message line can't be read as a header.
+            // Headers are matched after trimming, so leading whitespace doesn't avoid escaping.
+            if line.trim_start().starts_with('-') {
+                f.write_str("- ")?;
+            }
+            f.write_str(line)?;
+            f.write_str("\n")?;
+        }
         f.write_str(Self::BEGIN_ADDRESS)?;
         f.write_str("\n")?;
         f.write_str(&self.address.to_string())?;
@@ -52,17 +163,26 @@ impl FromStr for Armor {
     type Err = Error;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let mut iter = s.split("\n");
+        let mut iter = s.trim_start().split('\n');
 
         decode_static(Self::BEGIN_MESSAGE, iter.next(), "begin message")?;
-        let message = decode_part(&mut iter, "Missing message")?;
+        let mut lines = vec![];
+        loop {
+            let line = iter
+                .next()
+                .ok_or_else(|| Error::InvalidArmor("Missing begin address".into()))?;
+            if line.trim() == Self::BEGIN_ADDRESS {
+                break;
+            }
+            lines.push(line.strip_prefix("- ").unwrap_or(line));
+        }
+        let message = lines.join("\n");
 
-        decode_static(Self::BEGIN_ADDRESS, iter.next(), "begin address")?;
-        let address_str = decode_part(&mut iter, "Missing address")?;
+        let address_str = decode_part(iter.next(), "Missing address")?;
         let address = Address::from_str(&address_str)?;
 
         decode_static(Self::BEGIN_SIGNATURE, iter.next(), "begin signature")?;
-        let signature_str = decode_part(&mut iter, "Missing signature")?;
+        let signature_str = decode_part(iter.next(), "Missing signature")?;
         let signature = Signature::from_str(&signature_str)?;
 
         decode_static(Self::END_SIGNATURE, iter.next(), "end signature")?;
@@ -87,10 +207,40 @@ fn decode_static(expected: &str, got: Option<&str>, what: &str) -> Result<()> {
     Ok(())
 }
 
-fn decode_part(iter: &mut Split<&str>, what: &str) -> Result<String> {
-    Ok(iter
-        .next()
+fn decode_part(got: Option<&str>, what: &str) -> Result<String> {
+    Ok(got
         .ok_or_else(|| Error::InvalidArmor(what.into()))?
         .trim()
         .to_owned())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Seed;
+
+    #[test]
+    fn round_trip() {
+        let private = Seed::random().derive(0);
+        let message =
+            "I own this address.\n\n-----BEGIN NANO ADDRESS-----\n- dashes\n -----BEGIN NANO ADDRESS-----\n";
+        let armor = Armor::sign(message.into(), &private).unwrap();
+        for format in &[ArmorFormat::Armor, ArmorFormat::Json, ArmorFormat::Compact] {
+            let parsed = Armor::parse(&armor.format(*format).unwrap()).unwrap();
+            assert_eq!(parsed, armor);
+            parsed.verify().unwrap();
+        }
+    }
+
+    #[test]
+    fn not_a_raw_signature() {
+        let private = Seed::random().derive(0);
+        let message = [1u8; 32];
+
+        // The signature is over the prefixed hash, so it isn't valid for a block with this hash.
+        let signature = private.sign_message(&message).unwrap();
+        let public = private.to_public().unwrap();
+        assert!(public.verify(&message, &signature).is_err());
+        assert!(public.verify(&message_hash(&message), &signature).is_ok());
+    }
+}
@@ -0,0 +1,262 @@
+//! Messages encrypted between two Nano accounts.
+//!
+//! Both ed25519 keys are converted to X25519 to agree on a shared secret, which only the sender
+//! and the recipient can compute. Keys are derived from it with Blake2b, the message is encrypted
+//! with AES-256-CTR, and a keyed Blake2b MAC detects tampering, like encrypted wallet backups.
+//!
+//! Since the secret is shared, the sender can decrypt their own messages too.
+use crate::{Address, Error, Private, Public, Result};
+use aes::cipher::{NewCipher, StreamCipher};
+use aes::Aes256Ctr;
+use blake2::digest::{Update, VariableOutput};
+use blake2::VarBlake2b;
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use curve25519_dalek::scalar::Scalar;
+use ed25519_dalek::ExpandedSecretKey;
+use rand::RngCore;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use subtle::ConstantTimeEq;
+
+const NONCE_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const MAC_LEN: usize = 32;
+
+/// Mixed into the key derivation so the shared secret isn't used for anything else as is.
+const KDF_CONTEXT: &[u8] = b"feeless encrypted message";
+
+/// Characters per line of the armored ciphertext.
+const LINE_LEN: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncryptedMessage {
+    from: Address,
+    to: Address,
+    nonce: [u8; NONCE_LEN],
+    ciphertext: Vec<u8>,
+    mac: [u8; MAC_LEN],
+}
+
+/// Encrypt `plaintext` so only `to` and the owner of `from` can read it.
+///
+/// ```
+/// use feeless::encryption::{decrypt, encrypt};
+/// use feeless::Seed;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let alice = Seed::random().derive(0);
+/// let bob = Seed::random().derive(0);
+/// let encrypted = encrypt(&bob.to_public()?, &alice, b"Hi Bob")?;
+/// assert_eq!(decrypt(&encrypted, &bob)?, b"Hi Bob");
+/// # Ok(())
+/// # }
+/// ```
+pub fn encrypt(to: &Public, from: &Private, plaintext: &[u8]) -> Result<EncryptedMessage> {
+    let from_public = from.to_public()?;
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let (key, mac_key) = derive_keys(&shared_secret(from, to)?, &from_public, to);
+    let mut ciphertext = plaintext.to_vec();
+    Aes256Ctr::new(key.as_ref().into(), nonce.as_ref().into()).apply_keystream(&mut ciphertext);
+    let mac = mac(&mac_key, &nonce, &ciphertext);
+
+    Ok(EncryptedMessage {
+        from: from_public.to_address(),
+        to: to.to_address(),
+        nonce,
+        ciphertext,
+        mac,
+    })
+}
+
+/// Decrypt a message with the private key of either the recipient or the sender.
+pub fn decrypt(message: &EncryptedMessage, private: &Private) -> Result<Vec<u8>> {
+    let public = private.to_public()?;
+    let (from, to) = (message.from.to_public(), message.to.to_public());
+    let other = if public == to {
+        &from
+    } else if public == from {
+        &to
+    } else {
+        return Err(Error::DecryptionFailed(format!(
+            "Message is between {} and {}, not {}",
+            message.from,
+            message.to,
+            public.to_address()
+        )));
+    };
+
+    let (key, mac_key) = derive_keys(&shared_secret(private, other)?, &from, &to);
+    let expected = mac(&mac_key, &message.nonce, &message.ciphertext);
+    if !bool::from(expected.ct_eq(&message.mac)) {
+        return Err(Error::DecryptionFailed(
+            "Message was tampered with or corrupted".into(),
+        ));
+    }
+    let mut plaintext = message.ciphertext.to_owned();
+    Aes256Ctr::new(key.as_ref().into(), message.nonce.as_ref().into())
+        .apply_keystream(&mut plaintext);
+    Ok(plaintext)
+}
+
+impl EncryptedMessage {
+    const BEGIN_MESSAGE: &'static str = "-----BEGIN NANO ENCRYPTED MESSAGE-----";
+    const BEGIN_FROM: &'static str = "-----BEGIN NANO SENDER-----";
+    const BEGIN_TO: &'static str = "-----BEGIN NANO RECIPIENT-----";
+    const END_MESSAGE: &'static str = "-----END NANO ENCRYPTED MESSAGE-----";
+
+    pub fn from(&self) -> &Address {
+        &self.from
+    }
+
+    pub fn to(&self) -> &Address {
+        &self.to
+    }
+}
+
+impl Display for EncryptedMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut bytes = self.nonce.to_vec();
+        bytes.extend_from_slice(&self.ciphertext);
+        bytes.extend_from_slice(&self.mac);
+        let encoded = base64::encode(&bytes);
+
+        f.write_str(Self::BEGIN_MESSAGE)?;
+        f.write_str("\n")?;
+        // The encoding is ASCII so splitting by bytes is fine.
+        for line in encoded.as_bytes().chunks(LINE_LEN) {
+            f.write_str(std::str::from_utf8(line).unwrap())?;
+            f.write_str("\n")?;
+        }
+        f.write_str(Self::BEGIN_FROM)?;
+        f.write_str("\n")?;
+        f.write_str(&self.from.to_string())?;
+        f.write_str("\n")?;
+        f.write_str(Self::BEGIN_TO)?;
+        f.write_str("\n")?;
+        f.write_str(&self.to.to_string())?;
+        f.write_str("\n")?;
+        f.write_str(Self::END_MESSAGE)
+    }
+}
+
+impl FromStr for EncryptedMessage {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = |what: &str| Error::InvalidArmor(what.into());
+        let mut iter = s.trim().split('\n').map(|l| l.trim());
+
+        if iter.next() != Some(Self::BEGIN_MESSAGE) {
+            return Err(invalid("Missing begin encrypted message"));
+        }
+        let mut encoded = String::new();
+        loop {
+            match iter.next() {
+                Some(Self::BEGIN_FROM) => break,
+                Some(line) => encoded.push_str(line),
+                None => return Err(invalid("Missing begin sender")),
+            }
+        }
+        let from = Address::from_str(iter.next().ok_or_else(|| invalid("Missing sender"))?)?;
+        if iter.next() != Some(Self::BEGIN_TO) {
+            return Err(invalid("Missing begin recipient"));
+        }
+        let to = Address::from_str(iter.next().ok_or_else(|| invalid("Missing recipient"))?)?;
+        if iter.next() != Some(Self::END_MESSAGE) {
+            return Err(invalid("Missing end encrypted message"));
+        }
+
+        let bytes = base64::decode(&encoded)
+            .map_err(|e| Error::InvalidArmor(format!("Invalid base64: {}", e)))?;
+        if bytes.len() < NONCE_LEN + MAC_LEN {
+            return Err(invalid("Encrypted message is too short"));
+        }
+        let (nonce_bytes, rest) = bytes.split_at(NONCE_LEN);
+        let (ciphertext, mac_bytes) = rest.split_at(rest.len() - MAC_LEN);
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(nonce_bytes);
+        let mut mac = [0u8; MAC_LEN];
+        mac.copy_from_slice(mac_bytes);
+
+        Ok(Self {
+            from,
+            to,
+            nonce,
+            ciphertext: ciphertext.to_vec(),
+            mac,
+        })
+    }
+}
+
+/// X25519 of our clamped secret scalar and the other public key converted to Montgomery form.
+fn shared_secret(private: &Private, other: &Public) -> Result<[u8; 32]> {
+    let expanded = ExpandedSecretKey::from(&private.to_ed25519_dalek()?).to_bytes();
+    let mut bits = [0u8; 32];
+    bits.copy_from_slice(&expanded[..32]);
+    let point: MontgomeryPoint = other.to_point()?.to_montgomery();
+    let shared = Scalar::from_bits(bits) * point;
+
+    // A small order public key would make the secret predictable.
+    if shared.as_bytes() == &[0u8; 32] {
+        return Err(Error::BadPublicKey);
+    }
+    Ok(shared.to_bytes())
+}
+
+/// Derive an encryption key and a MAC key, bound to both accounts.
+fn derive_keys(shared: &[u8], from: &Public, to: &Public) -> ([u8; KEY_LEN], [u8; KEY_LEN]) {
+    let mut blake = VarBlake2b::new(KEY_LEN * 2).expect("Output size was zero");
+    blake.update(KDF_CONTEXT);
+    blake.update(shared);
+    blake.update(from.as_bytes());
+    blake.update(to.as_bytes());
+    let mut key = [0u8; KEY_LEN];
+    let mut mac_key = [0u8; KEY_LEN];
+    blake.finalize_variable(|hash| {
+        key.copy_from_slice(&hash[..KEY_LEN]);
+        mac_key.copy_from_slice(&hash[KEY_LEN..]);
+    });
+    (key, mac_key)
+}
+
+fn mac(key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> [u8; MAC_LEN] {
+    let mut blake = VarBlake2b::new_keyed(key, MAC_LEN);
+    blake.update(nonce);
+    blake.update(ciphertext);
+    let mut mac = [0u8; MAC_LEN];
+    blake.finalize_variable(|hash| mac.copy_from_slice(hash));
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Seed;
+
+    #[test]
+    fn round_trip() {
+        let alice = Seed::random().derive(0);
+        let bob = Seed::random().derive(0);
+        let eve = Seed::random().derive(0);
+        let plaintext = "A memo\nover a few lines that is longer than one armor line.".as_bytes();
+
+        let encrypted = encrypt(&bob.to_public().unwrap(), &alice, plaintext).unwrap();
+        let parsed = EncryptedMessage::from_str(&encrypted.to_string()).unwrap();
+        assert_eq!(parsed, encrypted);
+
+        assert_eq!(decrypt(&parsed, &bob).unwrap(), plaintext);
+        assert_eq!(decrypt(&parsed, &alice).unwrap(), plaintext);
+        assert!(decrypt(&parsed, &eve).is_err());
+    }
+
+    #[test]
+    fn tampered() {
+        let alice = Seed::random().derive(0);
+        let bob = Seed::random().derive(0);
+        let mut encrypted = encrypt(&bob.to_public().unwrap(), &alice, b"Pay 1 Nano").unwrap();
+        encrypted.ciphertext[4] ^= 1;
+        assert!(decrypt(&encrypted, &bob).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use crate::keys::armor::message_hash;
+use crate::{hexify, Address, Error, Public, Signature};
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+use curve25519_dalek::scalar::Scalar;
+use ed25519_dalek::ed25519::signature::Signature as InternalSignature;
+use ed25519_dalek::ExpandedSecretKey;
+use std::convert::TryFrom;
+
+/// 512 bit expanded private key: the secret scalar followed by the nonce prefix used for signing.
+///
+/// A [Private](crate::Private) is hashed into one of these to sign. Keys that don't come from
+/// hashing a private key, like the result of combining a key with a split-key vanity
+/// [Offset](crate::vanity::Offset), only exist in this form and can't be imported into wallets
+/// that expect a 256 bit private key.
+#[derive(Clone)]
+pub struct ExpandedPrivate([u8; ExpandedPrivate::LEN]);
+
+hexify!(ExpandedPrivate, "expanded private key");
+
+impl ExpandedPrivate {
+    pub(crate) const LEN: usize = 64;
+
+    pub(crate) fn from_parts(scalar: Scalar, nonce: &[u8]) -> Self {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..32].copy_from_slice(scalar.as_bytes());
+        bytes[32..].copy_from_slice(nonce);
+        Self(bytes)
+    }
+
+    /// The secret scalar, reduced so it can be added to.
+    pub(crate) fn scalar(&self) -> Scalar {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&self.0[..32]);
+        Scalar::from_bytes_mod_order(bytes)
+    }
+
+    pub(crate) fn nonce(&self) -> &[u8] {
+        &self.0[32..]
+    }
+
+    pub fn to_public(&self) -> Public {
+        let point = &self.scalar() * &ED25519_BASEPOINT_TABLE;
+        Public::from(point.compress())
+    }
+
+    pub fn to_address(&self) -> Address {
+        self.to_public().to_address()
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Result<Signature, Error> {
+        let expanded =
+            ExpandedSecretKey::from_bytes(&self.0).map_err(|e| Error::SignatureError {
+                msg: String::from("Converting to ExpandedSecretKey"),
+                source: e,
+            })?;
+        let public =
+            ed25519_dalek::PublicKey::from_bytes(self.to_public().as_bytes()).map_err(|e| {
+                Error::SignatureError {
+                    msg: String::from("Converting to PublicKey"),
+                    source: e,
+                }
+            })?;
+        let internal_signed = expanded.sign(message, &public);
+        Signature::try_from(internal_signed.as_bytes())
+    }
+
+    /// Sign a message, rather than a block hash. See [Armor](crate::keys::armor::Armor).
+    pub fn sign_message(&self, message: &[u8]) -> Result<Signature, Error> {
+        self.sign(&message_hash(message))
+    }
+}
+
+impl From<ExpandedSecretKey> for ExpandedPrivate {
+    fn from(v: ExpandedSecretKey) -> Self {
+        let bytes = v.to_bytes();
+        let mut scalar = [0u8; 32];
+        scalar.copy_from_slice(&bytes[..32]);
+        Self::from_parts(Scalar::from_bytes_mod_order(scalar), &bytes[32..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Seed;
+
+    #[test]
+    fn same_as_private() {
+        let message = [1, 2, 3, 4, 5];
+        let private = Seed::random().derive(0);
+        let expanded = private.to_expanded().unwrap();
+        assert_eq!(expanded.to_public(), private.to_public().unwrap());
+
+        // Signatures are deterministic, and the reduced scalar signs the same way.
+        let signature = expanded.sign(&message).unwrap();
+        assert_eq!(signature, private.sign(&message).unwrap());
+    }
+}
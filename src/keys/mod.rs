@@ -1,5 +1,7 @@
 pub mod address;
 pub mod armor;
+pub mod encryption;
+pub mod expanded;
 pub mod phrase;
 pub mod private;
 pub mod public;
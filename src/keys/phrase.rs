@@ -1,7 +1,7 @@
 //! BIP39 and BIP44 mnemonic seed phrase.
 use crate::encoding::to_hex;
 use crate::Error;
-use crate::Private;
+use crate::{Private, Seed};
 use bip39::Mnemonic;
 pub use bip39::MnemonicType;
 use ed25519_dalek_bip32::{DerivationPath, ExtendedSecretKey};
@@ -131,20 +131,57 @@ impl Phrase {
         &self,
         account: u32,
         passphrase: &str,
+    ) -> Result<ExtendedSecretKey, Error> {
+        self.to_bip32_ext_key_path(&Derivation::bip44_path(account), passphrase)
+    }
+
+    /// Derive a key using a BIP32 path, e.g. `m/44'/165'/0'`.
+    pub fn to_bip32_ext_key_path(
+        &self,
+        path: &str,
+        passphrase: &str,
     ) -> Result<ExtendedSecretKey, Error> {
         let bip39_seed = self.to_bip39_seed(passphrase)?;
         let key = ExtendedSecretKey::from_seed(bip39_seed.as_bytes())?;
-        let path = format!("m/44'/165'/{}'", account);
-        let path: DerivationPath = path.parse().unwrap();
+        let path: DerivationPath = path
+            .parse()
+            .map_err(|e| Error::InvalidDerivation(format!("{}: {:?}", path, e)))?;
         let derived = key.derive(&path)?;
 
         Ok(derived)
     }
 
     pub fn to_private(&self, account: u32, passphrase: &str) -> Result<Private, Error> {
-        let ext_key = self.to_bip32_ext_key(account, passphrase)?;
+        self.derive(account, passphrase, &Derivation::Bip44)
+    }
+
+    /// Derive a private key for an account using a [Derivation] scheme.
+    pub fn derive(
+        &self,
+        account: u32,
+        passphrase: &str,
+        derivation: &Derivation,
+    ) -> Result<Private, Error> {
+        let path = match derivation {
+            Derivation::Bip44 => Derivation::bip44_path(account),
+            Derivation::Custom(template) => Derivation::custom_path(template, account),
+            Derivation::Seed => {
+                if !passphrase.is_empty() {
+                    return Err(Error::InvalidDerivation(
+                        "A passphrase can't be used when the phrase is a seed".into(),
+                    ));
+                }
+                return Ok(self.to_seed()?.derive(account));
+            }
+        };
+        let ext_key = self.to_bip32_ext_key_path(&path, passphrase)?;
         let bip39_seed = ext_key.secret_key.as_ref();
-        Ok(Private::try_from(bip39_seed)?)
+        Private::try_from(bip39_seed)
+    }
+
+    /// Use the entropy of the phrase as a Nano [Seed]. Only 24 word phrases contain a full seed.
+    pub fn to_seed(&self) -> Result<Seed, Error> {
+        Seed::try_from(self.entropy.0.as_slice())
     }
 
     pub fn from_words(language: Language, words: &str) -> Result<Self, Error> {
@@ -156,6 +193,79 @@ impl Phrase {
     }
 }
 
+/// How private keys are derived from a [Phrase].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Derivation {
+    /// BIP44 with the path `m/44'/165'/{account}'`. This is compatible with Ledger devices.
+    Bip44,
+
+    /// The phrase entropy is used directly as a Nano [Seed], as some wallets encode seeds as 24
+    /// word phrases.
+    Seed,
+
+    /// A BIP32 path where `{account}` is replaced with the account index, e.g.
+    /// `m/44'/165'/0'/{account}'`. Every level needs to be hardened.
+    Custom(String),
+}
+
+impl Default for Derivation {
+    fn default() -> Self {
+        Derivation::Bip44
+    }
+}
+
+impl Derivation {
+    fn bip44_path(account: u32) -> String {
+        format!("m/44'/165'/{}'", account)
+    }
+
+    fn custom_path(template: &str, account: u32) -> String {
+        template.replace("{account}", &account.to_string())
+    }
+
+    /// Make sure a custom path can be parsed.
+    pub fn validate(&self) -> Result<(), Error> {
+        if let Derivation::Custom(template) = self {
+            Derivation::custom_path(template, 0)
+                .parse::<DerivationPath>()
+                .map_err(|e| Error::InvalidDerivation(format!("{}: {:?}", template, e)))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Derivation {
+    type Err = Error;
+
+    /// `bip44` (or `ledger`), `seed`, or a custom path starting with `m/`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let derivation = match s {
+            "bip44" | "ledger" => Derivation::Bip44,
+            "seed" => Derivation::Seed,
+            s if s.starts_with("m/") => Derivation::Custom(s.to_string()),
+            s => {
+                return Err(Error::InvalidDerivation(format!(
+                    "{}: expected bip44, ledger, seed or a path starting with m/",
+                    s
+                )))
+            }
+        };
+        derivation.validate()?;
+        Ok(derivation)
+    }
+}
+
+impl Display for Derivation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Derivation::Bip44 => write!(f, "bip44"),
+            Derivation::Seed => write!(f, "seed"),
+            Derivation::Custom(path) => write!(f, "{}", path),
+        }
+    }
+}
+
 impl Display for Phrase {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         // TODO: remove unwrap
@@ -199,4 +309,38 @@ mod tests {
             "nano_1pu7p5n3ghq1i1p4rhmek41f5add1uh34xpb94nkbxe8g4a6x1p69emk8y1d"
         );
     }
+
+    #[test]
+    fn derivations() {
+        let phrase = Phrase::from_words(
+            Language::English,
+            "edge defense waste choose enrich upon flee junk siren film clown finish \
+            luggage leader kid quick brick print evidence swap drill paddle truly occur",
+        )
+        .unwrap();
+
+        let custom = Derivation::from_str("m/44'/165'/{account}'").unwrap();
+        for account in 0..3 {
+            assert_eq!(
+                phrase.derive(account, "pass", &custom).unwrap().as_bytes(),
+                phrase.to_private(account, "pass").unwrap().as_bytes()
+            );
+        }
+
+        let custom = Derivation::from_str("m/44'/165'/0'/{account}'").unwrap();
+        assert_ne!(
+            phrase.derive(0, "", &custom).unwrap().as_bytes(),
+            phrase.to_private(0, "").unwrap().as_bytes()
+        );
+
+        let seed = phrase.to_seed().unwrap();
+        assert_eq!(
+            phrase.derive(1, "", &Derivation::Seed).unwrap().as_bytes(),
+            seed.derive(1).as_bytes()
+        );
+        assert!(phrase.derive(1, "pass", &Derivation::Seed).is_err());
+
+        assert!(Derivation::from_str("m/44'/nope").is_err());
+        assert!(Derivation::from_str("nope").is_err());
+    }
 }
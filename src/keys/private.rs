@@ -1,4 +1,5 @@
-use crate::{hexify, Address, Error, Public, Signature};
+use crate::keys::armor::message_hash;
+use crate::{hexify, Address, Error, ExpandedPrivate, Public, Signature};
 use ed25519_dalek::ed25519::signature::Signature as InternalSignature;
 use ed25519_dalek::ExpandedSecretKey;
 use rand::RngCore;
@@ -53,12 +54,23 @@ impl Private {
         Signature::try_from(internal_signed.as_bytes())
     }
 
+    /// Sign a message, rather than a block hash. See [Armor](crate::keys::armor::Armor).
+    pub fn sign_message(&self, message: &[u8]) -> Result<Signature, Error> {
+        self.sign(&message_hash(message))
+    }
+
+    /// The expanded form of this key, which signs the same way.
+    pub fn to_expanded(&self) -> Result<ExpandedPrivate, Error> {
+        let dalek = self.to_ed25519_dalek()?;
+        Ok(ExpandedPrivate::from(ExpandedSecretKey::from(&dalek)))
+    }
+
     // Not public because we don't want users to accidentally generate this key.
     fn zero() -> Self {
         Self([0u8; 32])
     }
 
-    fn to_ed25519_dalek(&self) -> Result<ed25519_dalek::SecretKey, Error> {
+    pub(crate) fn to_ed25519_dalek(&self) -> Result<ed25519_dalek::SecretKey, Error> {
         Ok(
             ed25519_dalek::SecretKey::from_bytes(&self.0).map_err(|e| Error::SignatureError {
                 msg: String::from("Converting to SecretKey"),
@@ -5,9 +5,11 @@ use crate::node::Wire;
 use crate::node::Header;
 
 use crate::hexify;
+use crate::keys::armor::message_hash;
 use crate::Error;
 use crate::{encoding, Address, Signature};
 use bitvec::prelude::*;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
 use ed25519_dalek::Verifier;
 use serde::{Deserialize, Deserializer, Serializer};
 use std::iter::FromIterator;
@@ -32,6 +34,13 @@ impl Public {
         )
     }
 
+    /// The curve point of this key, or an error if it isn't a valid point.
+    pub(crate) fn to_point(&self) -> Result<EdwardsPoint, Error> {
+        CompressedEdwardsY(self.0)
+            .decompress()
+            .ok_or(Error::BadPublicKey)
+    }
+
     pub fn to_address(&self) -> Address {
         Address::from(self)
     }
@@ -63,6 +72,11 @@ impl Public {
             _ => Err(Error::BadPublicKey),
         }
     }
+
+    /// Verify a signature from [Private::sign_message](crate::Private::sign_message).
+    pub fn verify_message(&self, message: &[u8], signature: &Signature) -> Result<(), Error> {
+        self.verify(&message_hash(message), signature)
+    }
 }
 
 impl From<ed25519_dalek::PublicKey> for Public {
@@ -71,6 +85,12 @@ impl From<ed25519_dalek::PublicKey> for Public {
     }
 }
 
+impl From<CompressedEdwardsY> for Public {
+    fn from(v: CompressedEdwardsY) -> Self {
+        Self(v.to_bytes())
+    }
+}
+
 #[cfg(feature = "node")]
 impl Wire for Public {
     fn serialize(&self) -> Vec<u8> {
@@ -54,6 +54,7 @@ mod bytes;
 mod encoding;
 mod errors;
 mod keys;
+pub mod multisig;
 mod network;
 mod paths;
 mod pow;
@@ -65,6 +66,10 @@ pub mod wallet;
 
 pub use errors::{Error, Result};
 pub use keys::address::Address;
+pub use keys::armor::{Armor, ArmorFormat};
+pub use keys::encryption;
+pub use keys::encryption::EncryptedMessage;
+pub use keys::expanded::ExpandedPrivate;
 pub use keys::phrase;
 pub use keys::phrase::Phrase;
 pub use keys::private::Private;
@@ -72,6 +77,14 @@ pub use keys::public::Public;
 pub use keys::seed::Seed;
 pub use keys::signature::Signature;
 pub use network::{Network, DEFAULT_PORT};
-pub use pow::{Difficulty, Subject, Work};
+#[cfg(feature = "node")]
+pub use node::{
+    dissect, payload_fields, DissectStatus, DissectedField, DissectedMessage, Header, MessageType,
+    Node,
+};
+pub use pow::{
+    CancelToken, Difficulty, Epoch, GeneratedWork, Multiplier, Subject, Work, WorkGenerator,
+    WorkProgress, WorkThresholds,
+};
 pub use units::raw::Raw;
 pub use version::Version;
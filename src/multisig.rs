@@ -0,0 +1,490 @@
+//! n-of-n aggregated signatures (MuSig) for Nano accounts.
+//!
+//! Several keys are aggregated into one [Public] key, which is a normal Nano account. Blocks of
+//! that account can only be signed by every participant together, and the result is a normal
+//! [Signature] that nodes verify like any other.
+//!
+//! Each public key is weighted by a coefficient hashed from the full set of keys, so nobody can
+//! pick their key to cancel out the others. Signing takes three rounds of messages, each of
+//! which needs the messages of every participant from the round before:
+//!
+//! 1. [Session::commit]: Each participant picks a secret nonce and shares a hash of its point.
+//! 2. [Session::reveal]: Once all commitments are in, each participant shares their nonce point.
+//!    Committing first stops anyone from choosing their nonce based on the others.
+//! 3. [Session::sign]: Each participant checks the nonces against their commitments and shares
+//!    a partial signature.
+//!
+//! Anyone can then combine the partial signatures with [AggregatedKey::aggregate].
+//!
+//! A nonce must never be used for two signatures, so a [Session] is for one block hash only and
+//! forgets its nonce once it has signed.
+use crate::blocks::BlockHash;
+use crate::encoding::blake2b;
+use crate::{hexify, Address, Error, Private, Public, Result, Signature};
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// Domain separation for the hashes only used by this scheme.
+const KEY_LIST_CONTEXT: &[u8] = b"feeless multisig key list";
+const COEFFICIENT_CONTEXT: &[u8] = b"feeless multisig coefficient";
+
+/// A hash of a participant's nonce point, shared before the point itself.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Commitment([u8; Commitment::LEN]);
+
+hexify!(Commitment, "commitment");
+
+impl Commitment {
+    pub const LEN: usize = 32;
+}
+
+/// The public part of a participant's nonce.
+#[derive(Clone, PartialEq, Eq)]
+pub struct NoncePoint([u8; NoncePoint::LEN]);
+
+hexify!(NoncePoint, "nonce point");
+
+impl NoncePoint {
+    pub const LEN: usize = 32;
+}
+
+/// The secret part of a participant's nonce. Only kept until the partial signature is made.
+#[derive(Clone)]
+pub struct NonceSecret([u8; NonceSecret::LEN]);
+
+hexify!(NonceSecret, "nonce secret");
+
+impl NonceSecret {
+    pub const LEN: usize = 32;
+}
+
+/// A participant's share of the `s` half of the signature.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Share([u8; Share::LEN]);
+
+hexify!(Share, "signature share");
+
+impl Share {
+    pub const LEN: usize = 32;
+}
+
+/// Round 1 message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommitMessage {
+    pub public: Public,
+    pub commitment: Commitment,
+}
+
+/// Round 2 message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NonceMessage {
+    pub public: Public,
+    pub nonce: NoncePoint,
+}
+
+/// Round 3 message, combined with the others into a [Signature].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartialSignature {
+    pub public: Public,
+    pub nonce: NoncePoint,
+    pub share: Share,
+}
+
+/// The public keys of every participant, and the key they make together.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregatedKey {
+    publics: Vec<Public>,
+    public: Public,
+}
+
+impl AggregatedKey {
+    /// Aggregate keys. The order they're given in doesn't matter.
+    pub fn new(mut publics: Vec<Public>) -> Result<Self> {
+        if publics.is_empty() {
+            return Err(Error::Multisig("No public keys to aggregate".into()));
+        }
+        publics.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+        let before = publics.len();
+        publics.dedup();
+        if publics.len() != before {
+            return Err(Error::Multisig(
+                "A public key is given more than once".into(),
+            ));
+        }
+
+        let list_hash = key_list_hash(&publics);
+        let mut point = EdwardsPoint::default();
+        for public in &publics {
+            point += coefficient(&list_hash, public) * public.to_point()?;
+        }
+        Ok(Self {
+            publics,
+            public: Public::from(point.compress()),
+        })
+    }
+
+    pub fn public(&self) -> &Public {
+        &self.public
+    }
+
+    pub fn to_address(&self) -> Address {
+        self.public.to_address()
+    }
+
+    /// Every participant, sorted.
+    pub fn publics(&self) -> &[Public] {
+        &self.publics
+    }
+
+    fn coefficient(&self, public: &Public) -> Result<Scalar> {
+        if !self.publics.contains(public) {
+            return Err(Error::Multisig(format!(
+                "{} is not a participant",
+                public.to_address()
+            )));
+        }
+        Ok(coefficient(&key_list_hash(&self.publics), public))
+    }
+
+    /// Combine the partial signatures of every participant, checking each one.
+    pub fn aggregate(&self, hash: &BlockHash, partials: &[PartialSignature]) -> Result<Signature> {
+        let partials = self.one_each(partials, |p| &p.public)?;
+        let nonce = total_nonce(partials.iter().map(|p| &p.nonce))?;
+        let challenge = challenge(&nonce, &self.public, hash);
+
+        let mut s = Scalar::zero();
+        for partial in partials {
+            let share = Scalar::from_canonical_bytes(partial.share.0)
+                .ok_or_else(|| Error::Multisig("Share is not a canonical scalar".into()))?;
+            let expected = to_point(&partial.nonce)?
+                + challenge * self.coefficient(&partial.public)? * partial.public.to_point()?;
+            if &share * &ED25519_BASEPOINT_TABLE != expected {
+                return Err(Error::Multisig(format!(
+                    "Invalid partial signature from {}",
+                    partial.public.to_address()
+                )));
+            }
+            s += share;
+        }
+
+        let mut bytes = [0u8; Signature::LEN];
+        bytes[..32].copy_from_slice(nonce.as_bytes());
+        bytes[32..].copy_from_slice(s.as_bytes());
+        let signature = Signature::try_from(bytes.as_ref())?;
+        self.public.verify(hash.as_bytes(), &signature)?;
+        Ok(signature)
+    }
+
+    /// Check there's exactly one message from each participant, returning them in key order.
+    fn one_each<'a, T, F>(&self, messages: &'a [T], public: F) -> Result<Vec<&'a T>>
+    where
+        F: Fn(&T) -> &Public,
+    {
+        let mut sorted = vec![];
+        for participant in &self.publics {
+            let mut from = messages.iter().filter(|m| public(m) == participant);
+            match (from.next(), from.next()) {
+                (Some(message), None) => sorted.push(message),
+                (None, _) => {
+                    return Err(Error::Multisig(format!(
+                        "Missing message from {}",
+                        participant.to_address()
+                    )))
+                }
+                (Some(_), Some(_)) => {
+                    return Err(Error::Multisig(format!(
+                        "More than one message from {}",
+                        participant.to_address()
+                    )))
+                }
+            }
+        }
+        if sorted.len() != messages.len() {
+            return Err(Error::Multisig(
+                "Message from someone who isn't a participant".into(),
+            ));
+        }
+        Ok(sorted)
+    }
+}
+
+/// One participant's side of signing a block hash.
+///
+/// It can be serialized to be kept between rounds. It contains the secret nonce, so treat it
+/// like a private key until it has signed.
+///
+/// ```
+/// use feeless::blocks::BlockHash;
+/// use feeless::multisig::{AggregatedKey, Session};
+/// use feeless::Seed;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let alice = Seed::random().derive(0);
+/// let bob = Seed::random().derive(0);
+/// let key = AggregatedKey::new(vec![alice.to_public()?, bob.to_public()?])?;
+/// let hash = BlockHash::zero();
+///
+/// let mut a = Session::new(key.to_owned(), &alice, hash.to_owned())?;
+/// let mut b = Session::new(key.to_owned(), &bob, hash.to_owned())?;
+/// let commitments = vec![a.commit(), b.commit()];
+/// let nonces = vec![a.reveal(&commitments)?, b.reveal(&commitments)?];
+/// let partials = vec![a.sign(&alice, &nonces)?, b.sign(&bob, &nonces)?];
+///
+/// let signature = key.aggregate(&hash, &partials)?;
+/// key.public().verify(hash.as_bytes(), &signature)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Session {
+    key: AggregatedKey,
+    hash: BlockHash,
+    public: Public,
+    nonce: NoncePoint,
+
+    /// Removed once used.
+    nonce_secret: Option<NonceSecret>,
+
+    /// Everyone's commitments, once received.
+    commitments: Option<Vec<CommitMessage>>,
+}
+
+impl Session {
+    /// Start signing `hash` as the owner of `private`, picking a new random nonce.
+    pub fn new(key: AggregatedKey, private: &Private, hash: BlockHash) -> Result<Self> {
+        let public = private.to_public()?;
+        key.coefficient(&public)?;
+
+        let mut wide = [0u8; 64];
+        rand::thread_rng().fill_bytes(&mut wide);
+        let secret = Scalar::from_bytes_mod_order_wide(&wide);
+        let nonce = NoncePoint((&secret * &ED25519_BASEPOINT_TABLE).compress().to_bytes());
+
+        Ok(Self {
+            key,
+            hash,
+            public,
+            nonce,
+            nonce_secret: Some(NonceSecret(secret.to_bytes())),
+            commitments: None,
+        })
+    }
+
+    pub fn key(&self) -> &AggregatedKey {
+        &self.key
+    }
+
+    pub fn hash(&self) -> &BlockHash {
+        &self.hash
+    }
+
+    /// Round 1: The commitment to share with the other participants.
+    pub fn commit(&self) -> CommitMessage {
+        CommitMessage {
+            public: self.public.to_owned(),
+            commitment: commitment(&self.nonce),
+        }
+    }
+
+    /// Round 2: Take everyone's commitments, including this one, and reveal the nonce point.
+    pub fn reveal(&mut self, commitments: &[CommitMessage]) -> Result<NonceMessage> {
+        // Otherwise the others could pick their commitments after seeing our nonce point.
+        if self.commitments.is_some() {
+            return Err(Error::Multisig(
+                "This session has already revealed its nonce".into(),
+            ));
+        }
+        let sorted = self.key.one_each(commitments, |c| &c.public)?;
+        if !sorted.contains(&&self.commit()) {
+            return Err(Error::Multisig(
+                "Our own commitment is not in the list".into(),
+            ));
+        }
+        self.commitments = Some(sorted.into_iter().cloned().collect());
+        Ok(NonceMessage {
+            public: self.public.to_owned(),
+            nonce: self.nonce.to_owned(),
+        })
+    }
+
+    /// Round 3: Take everyone's nonce points, check they match their commitments, and sign.
+    pub fn sign(&mut self, private: &Private, nonces: &[NonceMessage]) -> Result<PartialSignature> {
+        if private.to_public()? != self.public {
+            return Err(Error::Multisig(
+                "Private key doesn't belong to this session".into(),
+            ));
+        }
+        let commitments = self
+            .commitments
+            .as_ref()
+            .ok_or_else(|| Error::Multisig("Commitments have not been revealed yet".into()))?;
+        let nonces = self.key.one_each(nonces, |n| &n.public)?;
+        for (nonce, commit) in nonces.iter().zip(commitments) {
+            if commitment(&nonce.nonce) != commit.commitment {
+                return Err(Error::Multisig(format!(
+                    "Nonce from {} doesn't match its commitment",
+                    nonce.public.to_address()
+                )));
+            }
+        }
+
+        let secret = self
+            .nonce_secret
+            .take()
+            .ok_or_else(|| Error::Multisig("This session has already signed".into()))?;
+        let total = total_nonce(nonces.iter().map(|n| &n.nonce))?;
+        let challenge = challenge(&total, self.key.public(), &self.hash);
+        let coefficient = self.key.coefficient(&self.public)?;
+        let x = private.to_expanded()?.scalar();
+        let share = Scalar::from_bytes_mod_order(secret.0) + challenge * coefficient * x;
+
+        Ok(PartialSignature {
+            public: self.public.to_owned(),
+            nonce: self.nonce.to_owned(),
+            share: Share(share.to_bytes()),
+        })
+    }
+}
+
+fn key_list_hash(publics: &[Public]) -> Box<[u8]> {
+    let mut data = KEY_LIST_CONTEXT.to_vec();
+    for public in publics {
+        data.extend_from_slice(public.as_bytes());
+    }
+    blake2b(32, &data)
+}
+
+fn coefficient(list_hash: &[u8], public: &Public) -> Scalar {
+    let mut data = COEFFICIENT_CONTEXT.to_vec();
+    data.extend_from_slice(list_hash);
+    data.extend_from_slice(public.as_bytes());
+    wide_scalar(&blake2b(64, &data))
+}
+
+fn commitment(nonce: &NoncePoint) -> Commitment {
+    let mut bytes = [0u8; Commitment::LEN];
+    bytes.copy_from_slice(&blake2b(Commitment::LEN, &nonce.0));
+    Commitment(bytes)
+}
+
+fn to_point(nonce: &NoncePoint) -> Result<EdwardsPoint> {
+    CompressedEdwardsY(nonce.0)
+        .decompress()
+        .ok_or_else(|| Error::Multisig("Nonce is not a valid point".into()))
+}
+
+fn total_nonce<'a, I>(nonces: I) -> Result<CompressedEdwardsY>
+where
+    I: Iterator<Item = &'a NoncePoint>,
+{
+    let mut total = EdwardsPoint::default();
+    for nonce in nonces {
+        total += to_point(nonce)?;
+    }
+    Ok(total.compress())
+}
+
+/// The hash ed25519 signs with, as in ed25519-dalek but with blake2b.
+fn challenge(nonce: &CompressedEdwardsY, public: &Public, hash: &BlockHash) -> Scalar {
+    let mut data = nonce.as_bytes().to_vec();
+    data.extend_from_slice(public.as_bytes());
+    data.extend_from_slice(hash.as_bytes());
+    wide_scalar(&blake2b(64, &data))
+}
+
+fn wide_scalar(hash: &[u8]) -> Scalar {
+    let mut wide = [0u8; 64];
+    wide.copy_from_slice(hash);
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Seed;
+
+    fn setup(n: usize) -> (Vec<Private>, AggregatedKey) {
+        let privates: Vec<Private> = (0..n).map(|_| Seed::random().derive(0)).collect();
+        let publics = privates.iter().map(|p| p.to_public().unwrap()).collect();
+        (privates, AggregatedKey::new(publics).unwrap())
+    }
+
+    #[test]
+    fn sign() {
+        let (privates, key) = setup(3);
+        let hash = BlockHash::try_from([7u8; 32].as_ref()).unwrap();
+        let mut sessions: Vec<Session> = privates
+            .iter()
+            .map(|p| Session::new(key.to_owned(), p, hash.to_owned()).unwrap())
+            .collect();
+
+        let commitments: Vec<CommitMessage> = sessions.iter().map(|s| s.commit()).collect();
+        let nonces: Vec<NonceMessage> = sessions
+            .iter_mut()
+            .map(|s| s.reveal(&commitments).unwrap())
+            .collect();
+        let partials: Vec<PartialSignature> = sessions
+            .iter_mut()
+            .zip(&privates)
+            .map(|(s, p)| s.sign(p, &nonces).unwrap())
+            .collect();
+
+        let signature = key.aggregate(&hash, &partials).unwrap();
+        assert!(key.public().verify(hash.as_bytes(), &signature).is_ok());
+
+        // The nonce is gone after signing.
+        assert!(sessions[0].sign(&privates[0], &nonces).is_err());
+
+        // Every participant is needed.
+        assert!(key.aggregate(&hash, &partials[1..]).is_err());
+    }
+
+    #[test]
+    fn order_does_not_matter() {
+        let (privates, key) = setup(3);
+        let mut publics: Vec<Public> = privates.iter().map(|p| p.to_public().unwrap()).collect();
+        publics.reverse();
+        assert_eq!(AggregatedKey::new(publics.to_owned()).unwrap(), key);
+
+        publics.push(publics[0].to_owned());
+        assert!(AggregatedKey::new(publics).is_err());
+    }
+
+    #[test]
+    fn nonce_must_match_commitment() {
+        let (privates, key) = setup(2);
+        let hash = BlockHash::zero();
+        let mut a = Session::new(key.to_owned(), &privates[0], hash.to_owned()).unwrap();
+        let mut b = Session::new(key.to_owned(), &privates[1], hash.to_owned()).unwrap();
+        let commitments = vec![a.commit(), b.commit()];
+        let mut nonces = vec![
+            a.reveal(&commitments).unwrap(),
+            b.reveal(&commitments).unwrap(),
+        ];
+
+        // b swaps its nonce after seeing a's.
+        let other = Session::new(key, &privates[1], hash).unwrap();
+        nonces[1].nonce = other.nonce;
+        assert!(a.sign(&privates[0], &nonces).is_err());
+    }
+
+    #[test]
+    fn reveal_only_once() {
+        let (privates, key) = setup(2);
+        let hash = BlockHash::zero();
+        let mut a = Session::new(key.to_owned(), &privates[0], hash.to_owned()).unwrap();
+        let b = Session::new(key.to_owned(), &privates[1], hash.to_owned()).unwrap();
+        let commitments = vec![a.commit(), b.commit()];
+        a.reveal(&commitments).unwrap();
+
+        // b can't swap its commitment after seeing a's nonce.
+        let other = Session::new(key, &privates[1], hash).unwrap();
+        let swapped = vec![a.commit(), other.commit()];
+        assert!(a.reveal(&swapped).is_err());
+        assert!(a.reveal(&commitments).is_err());
+    }
+}
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::net::SocketAddr;
+
+/// Which way a captured packet went, relative to the subject of a pcap dump.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Send,
+    Recv,
+}
+
+/// Where a captured packet came from, so decoded messages can be traced back to it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Annotation {
+    /// 1 based packet number because wireshark uses it, or 0 for live traffic.
+    pub packet: usize,
+    pub timestamp: DateTime<Utc>,
+    pub direction: Direction,
+    pub source: SocketAddr,
+    pub destination: SocketAddr,
+
+    /// Bytes of TCP data in the packet.
+    pub size: usize,
+}
+
+impl Annotation {
+    /// One direction of a TCP connection.
+    pub fn stream(&self) -> String {
+        format!("{}->{}", self.source, self.destination)
+    }
+
+    /// The end of the connection that isn't the subject.
+    pub fn remote(&self) -> SocketAddr {
+        match self.direction {
+            Direction::Send => self.destination,
+            Direction::Recv => self.source,
+        }
+    }
+}
+
+impl Display for Annotation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let arrows = match self.direction {
+            Direction::Send => ">>>",
+            Direction::Recv => "<<<",
+        };
+        write!(
+            f,
+            "Packet: #{} {} {} {} size: {}",
+            self.packet,
+            self.timestamp.format("%+"),
+            arrows,
+            self.remote(),
+            self.size
+        )
+    }
+}
@@ -0,0 +1,460 @@
+//! Stateless decoding of Nano protocol bytes into messages and the byte ranges of their fields,
+//! similar to a Wireshark dissector.
+//!
+//! Unlike [Peer](crate::node::Peer), nothing is kept between calls, so this is useful for looking
+//! at handcrafted messages or captured payloads without a connection.
+
+use crate::blocks::{BlockHash, BlockType};
+use crate::encoding::to_hex;
+use crate::node::cookie::Cookie;
+use crate::node::header::{Header, MessageType};
+use crate::node::messages::bulk_pull::BulkPull;
+use crate::node::messages::confirm_ack::ConfirmAck;
+use crate::node::messages::confirm_req::ConfirmReq;
+use crate::node::messages::frontier_req::FrontierReq;
+use crate::node::messages::handshake::Handshake;
+use crate::node::messages::keepalive::Keepalive;
+use crate::node::messages::publish::Publish;
+use crate::node::messages::telemetry_ack::TelemetryAck;
+use crate::node::messages::telemetry_req::TelemetryReq;
+use crate::node::peer_info::PeerInfo;
+use crate::node::timestamp::Timestamp;
+use crate::node::wire::Wire;
+use crate::{Public, Raw, Signature, Work};
+use anyhow::anyhow;
+use serde::Serialize;
+
+/// A named range of bytes in a message.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DissectedField {
+    pub name: String,
+
+    /// From the start of the data given to [dissect].
+    pub offset: usize,
+    pub len: usize,
+
+    /// The bytes of the field.
+    pub hex: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "status")]
+pub enum DissectStatus {
+    Complete,
+
+    /// The data ended part way through the message.
+    Partial {
+        missing: usize,
+    },
+
+    /// The message couldn't be decoded, e.g. a bad magic number or an unsupported block type.
+    Invalid {
+        error: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DissectedMessage {
+    /// Where the header starts in the data given to [dissect].
+    pub offset: usize,
+
+    /// The header and payload, or what's left of the data when the message isn't complete.
+    pub len: usize,
+
+    /// `None` when the header couldn't be decoded.
+    pub message_type: Option<MessageType>,
+    pub status: DissectStatus,
+
+    /// The header fields, then the payload fields, as far as the data goes.
+    pub fields: Vec<DissectedField>,
+
+    /// The decoded payload, when the message is complete.
+    pub payload: Option<serde_json::Value>,
+}
+
+/// Decode every message in `data`, which should start with a header.
+///
+/// Decoding stops at a trailing partial message, or when the length of a message can't be
+/// worked out. A message that can't be decoded but has a known length is skipped over.
+///
+/// ```
+/// use feeless::{dissect, DissectStatus, MessageType};
+///
+/// // A telemetry request on the live network, which is only a header.
+/// let data = [0x52, 0x43, 0x12, 0x12, 0x12, 0x0c, 0x00, 0x00];
+/// let messages = dissect(&data);
+///
+/// assert_eq!(messages.len(), 1);
+/// assert_eq!(messages[0].message_type, Some(MessageType::TelemetryReq));
+/// assert_eq!(messages[0].status, DissectStatus::Complete);
+/// assert_eq!(messages[0].fields[0].name, "magic_number");
+/// ```
+pub fn dissect(data: &[u8]) -> Vec<DissectedMessage> {
+    let mut messages = vec![];
+    let mut offset = 0;
+    while offset < data.len() {
+        let (message, next) = dissect_message(data, offset);
+        messages.push(message);
+        match next {
+            Some(next) => offset = next,
+            None => break,
+        }
+    }
+    messages
+}
+
+/// The message at `offset`, and where the next one starts if it can be known.
+fn dissect_message(data: &[u8], offset: usize) -> (DissectedMessage, Option<usize>) {
+    let rest = &data[offset..];
+    let mut message = DissectedMessage {
+        offset,
+        len: rest.len(),
+        message_type: None,
+        status: DissectStatus::Complete,
+        fields: fields(HEADER_FIELDS, data, offset),
+        payload: None,
+    };
+
+    if rest.len() < Header::LEN {
+        message.status = DissectStatus::Partial {
+            missing: Header::LEN - rest.len(),
+        };
+        return (message, None);
+    }
+    let header = match Header::deserialize(None, &rest[..Header::LEN]) {
+        Ok(header) => header,
+        Err(err) => {
+            message.status = invalid(err);
+            return (message, None);
+        }
+    };
+    message.message_type = Some(header.message_type());
+
+    let layout = match payload_fields(&header) {
+        Ok(layout) => layout,
+        Err(err) => {
+            message.status = invalid(err);
+            return (message, None);
+        }
+    };
+    let payload_len: usize = layout.iter().map(|(_, len)| len).sum();
+    message
+        .fields
+        .extend(fields(&layout, data, offset + Header::LEN));
+
+    let len = Header::LEN + payload_len;
+    if rest.len() < len {
+        message.status = DissectStatus::Partial {
+            missing: len - rest.len(),
+        };
+        return (message, None);
+    }
+    message.len = len;
+
+    match decode(&header, &rest[Header::LEN..len]) {
+        Ok(payload) => message.payload = Some(payload),
+        Err(err) => message.status = invalid(err),
+    }
+    (message, Some(offset + len))
+}
+
+fn invalid(err: anyhow::Error) -> DissectStatus {
+    DissectStatus::Invalid {
+        error: format!("{:#}", err),
+    }
+}
+
+/// Lay out fields from `start`, leaving off any that run past the end of `data`.
+fn fields<S: AsRef<str>>(layout: &[(S, usize)], data: &[u8], start: usize) -> Vec<DissectedField> {
+    let mut fields = vec![];
+    let mut offset = start;
+    for (name, len) in layout {
+        if offset + len > data.len() {
+            break;
+        }
+        fields.push(DissectedField {
+            name: name.as_ref().to_owned(),
+            offset,
+            len: *len,
+            hex: to_hex(&data[offset..offset + len]),
+        });
+        offset += len;
+    }
+    fields
+}
+
+const HEADER_FIELDS: &[(&str, usize)] = &[
+    ("magic_number", 1),
+    ("network", 1),
+    ("version_max", 1),
+    ("version_using", 1),
+    ("version_min", 1),
+    ("message_type", 1),
+    ("extensions", 2),
+];
+
+const STATE_BLOCK_FIELDS: &[(&str, usize)] = &[
+    ("account", Public::LEN),
+    ("previous", BlockHash::LEN),
+    ("representative", Public::LEN),
+    ("balance", Raw::LEN),
+    ("link", BlockHash::LEN),
+    ("signature", Signature::LEN),
+    ("work", Work::LEN),
+];
+
+const SEND_BLOCK_FIELDS: &[(&str, usize)] = &[
+    ("previous", BlockHash::LEN),
+    ("destination", Public::LEN),
+    ("balance", Raw::LEN),
+    ("signature", Signature::LEN),
+    ("work", Work::LEN),
+];
+
+const FRONTIER_REQ_FIELDS: &[(&str, usize)] = &[("start", Public::LEN), ("age", 4), ("count", 4)];
+
+const TELEMETRY_ACK_FIELDS: &[(&str, usize)] = &[
+    ("signature", Signature::LEN),
+    ("node_id", Public::LEN),
+    ("block_count", 8),
+    ("cemented_count", 8),
+    ("unchecked_count", 8),
+    ("account_count", 8),
+    ("bandwidth_cap", 8),
+    ("uptime", 8),
+    ("peer_count", 4),
+    ("protocol_version", 1),
+    ("genesis_block", BlockHash::LEN),
+    ("major_version", 1),
+    ("minor_version", 1),
+    ("patch_version", 1),
+    ("prerelease_version", 1),
+    ("maker", 1),
+    ("timestamp", 8),
+    ("active_difficulty", 8),
+];
+
+/// The names and lengths of the payload fields of a message with this header.
+pub fn payload_fields(header: &Header) -> anyhow::Result<Vec<(String, usize)>> {
+    let owned = |layout: &[(&str, usize)]| {
+        layout
+            .iter()
+            .map(|(name, len)| (name.to_string(), *len))
+            .collect::<Vec<_>>()
+    };
+    let numbered = |name: &str, len: usize, count: usize| {
+        (0..count)
+            .map(|i| (format!("{}_{}", name, i), len))
+            .collect::<Vec<_>>()
+    };
+    let block = || -> anyhow::Result<Vec<(String, usize)>> {
+        match header.ext().block_type()? {
+            BlockType::State => Ok(owned(STATE_BLOCK_FIELDS)),
+            BlockType::Send => Ok(owned(SEND_BLOCK_FIELDS)),
+            block_type => Err(anyhow!("Unsupported {:?} block", block_type)),
+        }
+    };
+    let by_hash =
+        || -> anyhow::Result<bool> { Ok(header.ext().block_type()? == BlockType::NotABlock) };
+    let count = header.ext().item_count();
+
+    let layout = match header.message_type() {
+        MessageType::Keepalive => numbered("peer", PeerInfo::LEN, Keepalive::PEERS),
+        MessageType::Publish => block()?,
+        MessageType::ConfirmReq => {
+            if by_hash()? {
+                (0..count)
+                    .flat_map(|i| {
+                        vec![
+                            (format!("hash_{}", i), BlockHash::LEN),
+                            (format!("root_{}", i), BlockHash::LEN),
+                        ]
+                    })
+                    .collect()
+            } else {
+                block()?
+            }
+        }
+        MessageType::ConfirmAck => {
+            let mut layout = owned(&[
+                ("account", Public::LEN),
+                ("signature", Signature::LEN),
+                ("timestamp", Timestamp::LEN),
+            ]);
+            if by_hash()? {
+                layout.extend(numbered("hash", BlockHash::LEN, count));
+            } else {
+                layout.extend(block()?);
+            }
+            layout
+        }
+        MessageType::BulkPull => {
+            let mut layout = owned(&[("start", BlockHash::LEN), ("end", BlockHash::LEN)]);
+            if header.ext().is_count_present() {
+                layout.extend(owned(&[("extended", 1), ("count", 4), ("reserved", 3)]));
+            }
+            layout
+        }
+        MessageType::FrontierReq => owned(FRONTIER_REQ_FIELDS),
+        MessageType::Handshake => {
+            let mut layout = vec![];
+            if header.ext().is_query() {
+                layout.push(("cookie".to_owned(), Cookie::LEN));
+            }
+            if header.ext().is_response() {
+                layout.extend(owned(&[
+                    ("node_id", Public::LEN),
+                    ("signature", Signature::LEN),
+                ]));
+            }
+            layout
+        }
+        MessageType::TelemetryReq => vec![],
+        MessageType::TelemetryAck => owned(TELEMETRY_ACK_FIELDS),
+        message_type => return Err(anyhow!("Unsupported {:?} message", message_type)),
+    };
+    Ok(layout)
+}
+
+/// Decode a payload into the same form peers log messages in.
+fn decode(header: &Header, data: &[u8]) -> anyhow::Result<serde_json::Value> {
+    match header.message_type() {
+        MessageType::Keepalive => to_json::<Keepalive>(header, data),
+        MessageType::Publish => to_json::<Publish>(header, data),
+        MessageType::ConfirmReq => to_json::<ConfirmReq>(header, data),
+        MessageType::ConfirmAck => to_json::<ConfirmAck>(header, data),
+        MessageType::BulkPull => to_json::<BulkPull>(header, data),
+        MessageType::FrontierReq => to_json::<FrontierReq>(header, data),
+        MessageType::Handshake => to_json::<Handshake>(header, data),
+        MessageType::TelemetryReq => to_json::<TelemetryReq>(header, data),
+        MessageType::TelemetryAck => to_json::<TelemetryAck>(header, data),
+        message_type => Err(anyhow!("Unsupported {:?} message", message_type)),
+    }
+}
+
+fn to_json<T: Wire + Serialize>(header: &Header, data: &[u8]) -> anyhow::Result<serde_json::Value> {
+    let payload = T::deserialize(Some(header), data)?;
+    Ok(serde_json::to_value(&payload)?)
+}
+
+/// Only used to check [payload_fields] against the lengths messages expect.
+#[cfg(test)]
+fn payload_len(header: &Header) -> anyhow::Result<usize> {
+    match header.message_type() {
+        MessageType::Keepalive => Keepalive::len(Some(header)),
+        MessageType::Publish => Publish::len(Some(header)),
+        MessageType::ConfirmReq => ConfirmReq::len(Some(header)),
+        MessageType::ConfirmAck => ConfirmAck::len(Some(header)),
+        MessageType::BulkPull => BulkPull::len(Some(header)),
+        MessageType::FrontierReq => FrontierReq::len(Some(header)),
+        MessageType::Handshake => Handshake::len(Some(header)),
+        MessageType::TelemetryReq => TelemetryReq::len(Some(header)),
+        MessageType::TelemetryAck => TelemetryAck::len(Some(header)),
+        message_type => Err(anyhow!("Unsupported {:?} message", message_type)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::Network;
+    use crate::node::header::Extensions;
+
+    fn header(message_type: MessageType, ext: Extensions) -> Header {
+        Header::new(Network::Live, message_type, ext)
+    }
+
+    #[test]
+    fn keepalive_then_partial() {
+        let mut data = header(MessageType::Keepalive, Extensions::new()).serialize();
+        data.extend_from_slice(&[0u8; PeerInfo::LEN * Keepalive::PEERS]);
+        let first_len = data.len();
+        data.extend(header(MessageType::TelemetryReq, Extensions::new()).serialize());
+        data.extend(header(MessageType::FrontierReq, Extensions::new()).serialize());
+        data.extend_from_slice(&[0u8; 10]);
+
+        let messages = dissect(&data);
+        assert_eq!(messages.len(), 3);
+
+        assert_eq!(messages[0].status, DissectStatus::Complete);
+        assert_eq!(messages[0].len, first_len);
+        assert_eq!(
+            messages[0].fields.len(),
+            HEADER_FIELDS.len() + Keepalive::PEERS
+        );
+        let last = messages[0].fields.last().unwrap();
+        assert_eq!(last.name, "peer_7");
+        assert_eq!(last.offset + last.len, first_len);
+
+        assert_eq!(messages[1].message_type, Some(MessageType::TelemetryReq));
+        assert_eq!(messages[1].offset, first_len);
+        assert_eq!(messages[1].status, DissectStatus::Complete);
+
+        // None of the frontier request fields fit.
+        assert_eq!(
+            messages[2].status,
+            DissectStatus::Partial {
+                missing: FrontierReq::LEN - 10
+            }
+        );
+        assert_eq!(messages[2].fields.len(), HEADER_FIELDS.len());
+        assert!(messages[2].payload.is_none());
+    }
+
+    #[test]
+    fn invalid_header() {
+        let messages = dissect(&[0x52, 0x58, 1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(messages.len(), 1);
+        assert!(matches!(messages[0].status, DissectStatus::Invalid { .. }));
+        assert_eq!(messages[0].len, 10);
+        assert_eq!(messages[0].message_type, None);
+    }
+
+    #[test]
+    fn layouts_match_lengths() {
+        let mut by_hash = Extensions::new();
+        by_hash
+            .set_block_type(&BlockType::NotABlock)
+            .set_item_count(3);
+        let mut state = Extensions::new();
+        state.set_block_type(&BlockType::State);
+        let mut send = Extensions::new();
+        send.set_block_type(&BlockType::Send);
+        let mut count = Extensions::new();
+        count.count_present();
+        let mut handshake = Extensions::new();
+        handshake.query().response();
+
+        let headers = vec![
+            header(MessageType::Keepalive, Extensions::new()),
+            header(MessageType::Publish, state),
+            header(MessageType::Publish, send),
+            header(MessageType::ConfirmReq, by_hash),
+            header(MessageType::ConfirmReq, state),
+            header(MessageType::ConfirmAck, by_hash),
+            header(MessageType::ConfirmAck, state),
+            header(MessageType::BulkPull, Extensions::new()),
+            header(MessageType::BulkPull, count),
+            header(MessageType::FrontierReq, Extensions::new()),
+            header(MessageType::Handshake, handshake),
+            header(MessageType::TelemetryReq, Extensions::new()),
+            header(MessageType::TelemetryAck, Extensions::new()),
+        ];
+        for header in headers {
+            let layout = payload_fields(&header).unwrap();
+            let len: usize = layout.iter().map(|(_, len)| len).sum();
+            assert_eq!(len, payload_len(&header).unwrap(), "{:?}", header);
+        }
+    }
+
+    #[test]
+    fn unsupported_block_type() {
+        let mut ext = Extensions::new();
+        ext.set_block_type(&BlockType::Open);
+        let data = header(MessageType::Publish, ext).serialize();
+        let messages = dissect(&data);
+        assert_eq!(messages.len(), 1);
+        assert!(matches!(messages[0].status, DissectStatus::Invalid { .. }));
+        assert_eq!(messages[0].fields.len(), HEADER_FIELDS.len());
+    }
+}
@@ -0,0 +1,228 @@
+use crate::blocks::BlockHash;
+use crate::node::ledger_event::{BlockOutcome, LedgerEvent};
+use crate::rpc::websocket::{
+    ElectionEvent, Event, TelemetryEvent, VoteEvent, VoteType, WebSocketServer,
+};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// How long an election waits for a confirmation before it's stopped, the same as nano_node.
+const ELECTION_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Follows elections from what peers do to the ledger, publishing them to WebSocket clients,
+/// along with the telemetry of peers.
+///
+/// An election starts when a new block is accepted, and votes for it are tallied. Elections can't
+/// be confirmed until representative weights are tracked, so each one is stopped after
+/// [ELECTION_TIMEOUT], like an election nano_node couldn't confirm.
+pub(crate) struct Elections {
+    websocket: WebSocketServer,
+
+    /// When each election started.
+    active: HashMap<BlockHash, Instant>,
+}
+
+impl Elections {
+    pub fn new(websocket: WebSocketServer) -> Self {
+        Self {
+            websocket,
+            active: HashMap::new(),
+        }
+    }
+
+    /// Handle events until every peer has disconnected.
+    pub async fn run(mut self, mut ledger_log: mpsc::UnboundedReceiver<LedgerEvent>) {
+        while let Some(event) = ledger_log.recv().await {
+            self.handle(event, Instant::now());
+        }
+    }
+
+    fn handle(&mut self, event: LedgerEvent, now: Instant) {
+        self.stop_expired(now);
+
+        match event {
+            LedgerEvent::Block {
+                hash: Some(hash),
+                outcome: BlockOutcome::Accepted,
+                ..
+            } => {
+                self.active.insert(hash.clone(), now);
+                self.websocket
+                    .publish(Event::StartedElection(ElectionEvent { hash }));
+            }
+            LedgerEvent::Block { .. } => {}
+            LedgerEvent::Vote {
+                hash,
+                representative,
+                signature,
+                timestamp,
+            } => {
+                let vote_type = if self.active.contains_key(&hash) {
+                    VoteType::Vote
+                } else {
+                    VoteType::Indeterminate
+                };
+                self.websocket.publish(Event::Vote(VoteEvent {
+                    account: representative.to_address(),
+                    signature,
+                    sequence: None,
+                    timestamp: Some(timestamp.to_u64().to_string()),
+                    blocks: vec![hash],
+                    vote_type,
+                }));
+            }
+            LedgerEvent::Telemetry {
+                peer_addr,
+                telemetry,
+            } => {
+                self.websocket.publish(Event::Telemetry(TelemetryEvent {
+                    address: peer_addr.ip().to_string(),
+                    port: peer_addr.port(),
+                    telemetry,
+                }));
+            }
+        }
+    }
+
+    fn stop_expired(&mut self, now: Instant) {
+        let expired: Vec<BlockHash> = self
+            .active
+            .iter()
+            .filter(|(_, started)| now.duration_since(**started) >= ELECTION_TIMEOUT)
+            .map(|(hash, _)| hash.clone())
+            .collect();
+        for hash in expired {
+            self.active.remove(&hash);
+            self.websocket
+                .publish(Event::StoppedElection(ElectionEvent { hash }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::timestamp::Timestamp;
+    use crate::rpc::calls::TelemetryResponse;
+    use crate::rpc::websocket::Notification;
+    use crate::{Difficulty, Seed, Signature};
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    fn next(rx: &mut tokio::sync::broadcast::Receiver<Arc<Notification>>) -> Option<Event> {
+        rx.try_recv().ok().map(|n| {
+            Arc::try_unwrap(n)
+                .expect("Only this receiver has the notification")
+                .event
+        })
+    }
+
+    #[test]
+    fn elections() {
+        let websocket = WebSocketServer::new();
+        let mut rx = websocket.subscribe();
+        let mut elections = Elections::new(websocket);
+
+        let hash =
+            BlockHash::from_str("87434F8041869A01C8F6F263B87972D7BA443A72E0A97D7A3FD0CCC2358FD6F9")
+                .unwrap();
+        let other =
+            BlockHash::from_str("CE898C131AAEE25E05362F247760F8A3ACF34A9796A5AE0D9204E86B0637965E")
+                .unwrap();
+        let representative = Seed::zero().derive(0).to_public().unwrap();
+        let vote = |hash: &BlockHash| LedgerEvent::Vote {
+            hash: hash.clone(),
+            representative: representative.clone(),
+            signature: Signature::zero(),
+            timestamp: Timestamp::from_u64(1614200740266),
+        };
+        let start = Instant::now();
+
+        elections.handle(
+            LedgerEvent::Block {
+                hash: Some(hash.clone()),
+                account: None,
+                outcome: BlockOutcome::Accepted,
+            },
+            start,
+        );
+        assert_eq!(
+            next(&mut rx),
+            Some(Event::StartedElection(ElectionEvent { hash: hash.clone() }))
+        );
+
+        elections.handle(
+            LedgerEvent::Block {
+                hash: Some(other.clone()),
+                account: None,
+                outcome: BlockOutcome::AlreadyExists,
+            },
+            start,
+        );
+        assert_eq!(next(&mut rx), None);
+
+        elections.handle(vote(&hash), start);
+        match next(&mut rx) {
+            Some(Event::Vote(v)) => {
+                assert_eq!(v.vote_type, VoteType::Vote);
+                assert_eq!(v.account, representative.to_address());
+                assert_eq!(v.timestamp, Some("1614200740266".into()));
+                assert_eq!(v.blocks, vec![hash.clone()]);
+            }
+            event => panic!("Unexpected event: {:?}", event),
+        }
+
+        elections.handle(vote(&hash), start + ELECTION_TIMEOUT);
+        assert_eq!(
+            next(&mut rx),
+            Some(Event::StoppedElection(ElectionEvent { hash }))
+        );
+        match next(&mut rx) {
+            Some(Event::Vote(v)) => assert_eq!(v.vote_type, VoteType::Indeterminate),
+            event => panic!("Unexpected event: {:?}", event),
+        }
+        assert_eq!(next(&mut rx), None);
+    }
+
+    #[test]
+    fn telemetry() {
+        let websocket = WebSocketServer::new();
+        let mut rx = websocket.subscribe();
+        let mut elections = Elections::new(websocket);
+
+        let telemetry = || TelemetryResponse {
+            block_count: 5777903,
+            cemented_count: 688819,
+            unchecked_count: 443468,
+            account_count: 620750,
+            bandwidth_cap: 1572864,
+            peer_count: 32,
+            protocol_version: 18,
+            uptime: 556896,
+            genesis_block: BlockHash::zero(),
+            major_version: 21,
+            minor_version: 0,
+            patch_version: 0,
+            pre_release_version: 0,
+            maker: 0,
+            timestamp: 1587055945990,
+            active_difficulty: Difficulty::new(0xffffffc000000000),
+        };
+        elections.handle(
+            LedgerEvent::Telemetry {
+                peer_addr: "[::ffff:1.2.3.4]:7075".parse().unwrap(),
+                telemetry: telemetry(),
+            },
+            Instant::now(),
+        );
+        assert_eq!(
+            next(&mut rx),
+            Some(Event::Telemetry(TelemetryEvent {
+                address: "::ffff:1.2.3.4".into(),
+                port: 7075,
+                telemetry: telemetry(),
+            }))
+        );
+    }
+}
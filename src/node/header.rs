@@ -51,6 +51,23 @@ impl Header {
     pub fn to_short_string(&self) -> String {
         format!("{:?} {:?}", self.message_type, self.ext)
     }
+
+    /// The offset of the first bytes in `data` that look like a header for `network`, used to
+    /// find the next message after losing track of a stream.
+    ///
+    /// The magic number and network alone are common in other data, so the versions and
+    /// message type have to be sensible too.
+    pub fn find(network: &Network, data: &[u8]) -> Option<usize> {
+        data.windows(Self::LEN).position(|window| {
+            let versions_ok = window[Self::VERSION_MIN] <= window[Self::VERSION_USING]
+                && window[Self::VERSION_USING] <= window[Self::VERSION_MAX]
+                && window[Self::VERSION_MAX] <= Self::MAX_PLAUSIBLE_VERSION;
+            window[Self::MAGIC_NUMBER] == MagicNumber::MAGIC
+                && window[Self::NETWORK] == *network as u8
+                && versions_ok
+                && MessageType::try_from(window[Self::MESSAGE_TYPE]).is_ok()
+        })
+    }
 }
 
 impl Header {
@@ -65,6 +82,9 @@ impl Header {
     const MESSAGE_TYPE: usize = 5;
     const EXTENSIONS: usize = 6;
 
+    /// Protocol versions above this are assumed to be random data when searching for a header.
+    const MAX_PLAUSIBLE_VERSION: u8 = 64;
+
     pub fn new(network: Network, message_type: MessageType, ext: Extensions) -> Self {
         Self {
             magic_number: MagicNumber::new(),
@@ -156,7 +176,7 @@ impl TryFrom<u8> for MagicNumber {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
 #[repr(u8)]
 pub enum MessageType {
     Keepalive = 2,
@@ -207,6 +227,8 @@ impl Extensions {
     // Bit offsets and lengths
     const QUERY: usize = 0;
     const RESPONSE: usize = 1;
+    /// Bulk pulls use the first bit for whether a count follows.
+    const COUNT_PRESENT: usize = 0;
     const ITEM_COUNT: usize = 12;
     const ITEM_COUNT_BITS: usize = 4;
     const BLOCK_TYPE: usize = 8;
@@ -234,16 +256,36 @@ impl Extensions {
         self.bits()[Self::RESPONSE]
     }
 
+    pub fn count_present(&mut self) -> &mut Self {
+        self.mut_bits().set(Self::COUNT_PRESENT, true);
+        self
+    }
+
+    pub fn is_count_present(&self) -> bool {
+        self.bits()[Self::COUNT_PRESENT]
+    }
+
     pub fn item_count(&self) -> usize {
         self.bits()[Self::ITEM_COUNT..Self::ITEM_COUNT + Self::ITEM_COUNT_BITS].load_be()
     }
 
+    pub fn set_item_count(&mut self, count: usize) -> &mut Self {
+        self.mut_bits()[Self::ITEM_COUNT..Self::ITEM_COUNT + Self::ITEM_COUNT_BITS].store_be(count);
+        self
+    }
+
     pub fn block_type(&self) -> anyhow::Result<BlockType> {
         self.bits()[Self::BLOCK_TYPE..Self::BLOCK_TYPE + Self::BLOCK_TYPE_BITS]
             .load_be::<u8>()
             .try_into()
     }
 
+    pub fn set_block_type(&mut self, block_type: &BlockType) -> &mut Self {
+        self.mut_bits()[Self::BLOCK_TYPE..Self::BLOCK_TYPE + Self::BLOCK_TYPE_BITS]
+            .store_be(block_type.as_u8());
+        self
+    }
+
     fn bits(&self) -> &BitSlice<Lsb0, u8> {
         self.0.view_bits()
     }
@@ -318,6 +360,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn find() {
+        let network = Network::Live;
+        let header = Header::new(network, MessageType::Keepalive, Extensions::new()).serialize();
+        let mut data = vec![0x52, 0x43, 0xff, 0, 0, 2, 0, 0, 0x52];
+        data.extend_from_slice(&header);
+        assert_eq!(Header::find(&network, &data), Some(9));
+        assert_eq!(Header::find(&Network::Beta, &data), None);
+        assert_eq!(Header::find(&network, &header[..7]), None);
+    }
+
     #[test]
     fn bad_length() {
         let s = vec![];
@@ -353,6 +406,14 @@ mod tests {
         assert_eq!(h.message_type, MessageType::Publish);
     }
 
+    #[test]
+    fn set_block_type() {
+        let mut ext = Extensions::new();
+        ext.query().set_block_type(&BlockType::State);
+        assert_eq!(ext.block_type().unwrap(), BlockType::State);
+        assert!(ext.is_query());
+    }
+
     #[test]
     fn item_count() {
         let fixtures: &[(u8, u8, u8)] = &[
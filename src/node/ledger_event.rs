@@ -0,0 +1,53 @@
+use crate::blocks::BlockHash;
+use crate::node::timestamp::Timestamp;
+use crate::rpc::calls::TelemetryResponse;
+use crate::{Public, Signature};
+use serde::Serialize;
+use std::net::SocketAddr;
+
+/// What happened to a block received from the network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockOutcome {
+    /// Stored in the state.
+    Accepted,
+    AlreadyExists,
+    BadSignature,
+    InsufficientWork,
+
+    /// The previous block isn't known, e.g. it was published before a capture started.
+    GapPrevious,
+
+    /// The previous block isn't the latest of its account, or isn't a state block. Also used for an
+    /// open block of an account that's already open.
+    BadPrevious,
+
+    /// The block received isn't known, e.g. it was published before a capture started.
+    GapSource,
+
+    /// Processing this kind of block isn't implemented yet.
+    Unsupported,
+}
+
+/// A change a peer made, or tried to make, to the state, sent to `Peer::ledger_log`.
+///
+/// Telemetry isn't a change to the state, but is sent along with them so the node can publish it.
+#[derive(Debug, Clone)]
+pub enum LedgerEvent {
+    Block {
+        /// Only known for state blocks.
+        hash: Option<BlockHash>,
+        account: Option<Public>,
+        outcome: BlockOutcome,
+    },
+    Vote {
+        hash: BlockHash,
+        representative: Public,
+        signature: Signature,
+        timestamp: Timestamp,
+    },
+    Telemetry {
+        peer_addr: SocketAddr,
+        telemetry: TelemetryResponse,
+    },
+}
@@ -0,0 +1,88 @@
+use crate::blocks::BlockHash;
+use crate::bytes::Bytes;
+use crate::node::header::Header;
+use crate::node::wire::Wire;
+use serde::Serialize;
+use std::convert::TryFrom;
+
+/// Requests the blocks of an account, which are sent back without headers until a
+/// [BlockType::NotABlock](crate::blocks::BlockType::NotABlock).
+#[derive(Debug, Serialize)]
+pub struct BulkPull {
+    /// An account or the block hash to start from.
+    start: BlockHash,
+
+    /// The block hash to stop at, or zero for all of them.
+    end: BlockHash,
+
+    /// The most blocks to send, when the count extension is set.
+    count: Option<u32>,
+}
+
+impl BulkPull {
+    pub const LEN: usize = BlockHash::LEN * 2;
+
+    /// A zero byte, the count, then three reserved bytes.
+    pub const COUNT_LEN: usize = 8;
+}
+
+impl Wire for BulkPull {
+    fn serialize(&self) -> Vec<u8> {
+        unimplemented!()
+    }
+
+    fn deserialize(header: Option<&Header>, data: &[u8]) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut bytes = Bytes::new(data);
+        let start = BlockHash::try_from(bytes.slice(BlockHash::LEN)?)?;
+        let end = BlockHash::try_from(bytes.slice(BlockHash::LEN)?)?;
+
+        let count = if matches!(header, Some(h) if h.ext().is_count_present()) {
+            let extended = bytes.slice(Self::COUNT_LEN)?;
+            let mut count = [0u8; 4];
+            count.copy_from_slice(&extended[1..5]);
+            Some(u32::from_le_bytes(count))
+        } else {
+            None
+        };
+
+        Ok(Self { start, end, count })
+    }
+
+    fn len(header: Option<&Header>) -> anyhow::Result<usize> {
+        if matches!(header, Some(h) if h.ext().is_count_present()) {
+            Ok(Self::LEN + Self::COUNT_LEN)
+        } else {
+            Ok(Self::LEN)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::Network;
+    use crate::node::header::{Extensions, MessageType};
+
+    #[test]
+    fn count() {
+        let mut data = vec![1u8; BulkPull::LEN];
+        data.extend_from_slice(&[0, 5, 0, 0, 0, 0, 0, 0]);
+
+        let header = Header::new(Network::Live, MessageType::BulkPull, Extensions::new());
+        assert_eq!(BulkPull::len(Some(&header)).unwrap(), BulkPull::LEN);
+        let pull = BulkPull::deserialize(Some(&header), &data).unwrap();
+        assert_eq!(pull.count, None);
+
+        let header = Header::new(
+            Network::Live,
+            MessageType::BulkPull,
+            *Extensions::new().count_present(),
+        );
+        assert_eq!(BulkPull::len(Some(&header)).unwrap(), data.len());
+        let pull = BulkPull::deserialize(Some(&header), &data).unwrap();
+        assert_eq!(pull.count, Some(5));
+    }
+}
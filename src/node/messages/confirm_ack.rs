@@ -1,16 +1,19 @@
-use crate::blocks::{Block, BlockHash, BlockType};
+use crate::blocks::{Block, BlockHash, BlockHolder, BlockType};
 use crate::bytes::Bytes;
 use crate::encoding::blake2b;
+use crate::keys::public::to_address;
 use crate::node::header::Header;
 use crate::node::timestamp::Timestamp;
 use crate::node::wire::Wire;
 use crate::{Public, Signature};
-use anyhow::Context;
+use anyhow::{anyhow, Context};
+use serde::Serialize;
 use std::convert::TryFrom;
 
 /// This is a vote on the network by a representative for one or more block hashes.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct ConfirmAck {
+    #[serde(serialize_with = "to_address")]
     pub account: Public,
     pub signature: Signature,
 
@@ -18,7 +21,8 @@ pub struct ConfirmAck {
     pub confirm: Confirm,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Confirm {
     VoteByHash(Vec<BlockHash>),
 
@@ -45,27 +49,30 @@ impl ConfirmAck {
 
     pub fn verify_signature(&self) -> anyhow::Result<()> {
         self.account
-            .verify(&self.inner_hash(), &self.signature)
+            .verify(&self.inner_hash()?, &self.signature)
             .context("Verify signature on ConfirmAck")
     }
 
     // nano::block_hash nano::vote::hash () const
-    pub fn inner_hash(&self) -> Vec<u8> {
+    pub fn inner_hash(&self) -> anyhow::Result<Vec<u8>> {
         let mut v = Vec::new();
 
-        // TODO: Only add this prefix if there's data. See nano::vote::hash()
-        v.extend_from_slice("vote ".as_bytes());
-
-        if let Confirm::VoteByHash(hashes) = &self.confirm {
-            for hash in hashes {
-                v.extend_from_slice(hash.as_bytes())
+        let hashes = match &self.confirm {
+            Confirm::VoteByHash(hashes) => {
+                // The prefix is left out of votes for a single full block.
+                if !hashes.is_empty() {
+                    v.extend_from_slice("vote ".as_bytes());
+                }
+                hashes.to_owned()
             }
-            v.extend_from_slice(&self.timestamp.to_bytes())
-        } else {
-            todo!("handle block hash");
+            Confirm::Block(block) => vec![block.hash()?.to_owned()],
+        };
+        for hash in hashes {
+            v.extend_from_slice(hash.as_bytes())
         }
+        v.extend_from_slice(&self.timestamp.to_bytes());
 
-        blake2b(BlockHash::LEN, &v).to_vec()
+        Ok(blake2b(BlockHash::LEN, &v).to_vec())
     }
 }
 
@@ -94,9 +101,20 @@ impl Wire for ConfirmAck {
             }
             Confirm::VoteByHash(block_hashes)
         } else {
-            // let block = Block;
-            dbg!("block!!!!!!!", header.ext().block_type().unwrap());
-            todo!()
+            match BlockHolder::deserialize(
+                Some(header),
+                data.slice(BlockHolder::len(Some(header))?)?,
+            )? {
+                BlockHolder::State(state_block) => {
+                    Confirm::Block(Block::from_state_block(&state_block))
+                }
+                _ => {
+                    return Err(anyhow!(
+                        "Unsupported {:?} block in vote",
+                        header.ext().block_type()?
+                    ))
+                }
+            }
         };
 
         Ok(Self::new(account, signature, timestamp, confirm))
@@ -109,8 +127,7 @@ impl Wire for ConfirmAck {
         if header.ext().block_type()? == BlockType::NotABlock {
             Ok(Self::VOTE_COMMON_LEN + header.ext().item_count() * BlockHash::LEN)
         } else {
-            todo!("got a block in confirm ack {:#?}", header);
-            // Ok(Self::VOTE_COMMON_LEN + FullBlock::LEN)
+            Ok(Self::VOTE_COMMON_LEN + BlockHolder::len(Some(header))?)
         }
     }
 }
@@ -118,6 +135,7 @@ impl Wire for ConfirmAck {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Network, Seed};
     use std::str::FromStr;
 
     #[test]
@@ -141,4 +159,31 @@ mod tests {
         );
         assert!(confirm_ack.verify_signature().is_ok());
     }
+
+    #[test]
+    fn verify_block_sig() {
+        let private = Seed::zero().derive(0);
+        let block = Network::Live.genesis_block();
+        let timestamp = Timestamp::from_u64(1614200740266);
+        let mut confirm_ack = ConfirmAck::new(
+            private.to_public().unwrap(),
+            Signature::zero(),
+            timestamp,
+            Confirm::Block(block.to_owned()),
+        );
+        confirm_ack.signature = private.sign(&confirm_ack.inner_hash().unwrap()).unwrap();
+        assert!(confirm_ack.verify_signature().is_ok());
+
+        // Unlike a vote by hash for the same block, there's no prefix.
+        let by_hash = ConfirmAck::new(
+            confirm_ack.account.to_owned(),
+            confirm_ack.signature.to_owned(),
+            Timestamp::from_u64(1614200740266),
+            Confirm::VoteByHash(vec![block.hash().unwrap().to_owned()]),
+        );
+        assert!(by_hash.verify_signature().is_err());
+
+        confirm_ack.timestamp = Timestamp::from_u64(1614200740267);
+        assert!(confirm_ack.verify_signature().is_err());
+    }
 }
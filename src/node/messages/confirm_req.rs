@@ -4,6 +4,7 @@ use crate::encoding::expect_len;
 use crate::node::header::Header;
 use crate::node::wire::Wire;
 use anyhow::Context;
+use serde::Serialize;
 use std::convert::TryFrom;
 use tracing::info;
 
@@ -16,7 +17,8 @@ use tracing::info;
 //  - id: block
 //    if: _root.header.block_type != enum_blocktype::not_a_block
 //    type: block_selector(_root.header.block_type_int)
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ConfirmReq {
     ConfirmReqByHash(Vec<RootHashPair>),
     BlockSelector(BlockHolder),
@@ -90,7 +92,7 @@ impl Wire for ConfirmReq {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct RootHashPair {
     pub hash: BlockHash,
     pub root: BlockHash,
@@ -2,9 +2,10 @@ use crate::bytes::Bytes;
 use crate::node::header::Header;
 use crate::node::wire::Wire;
 use crate::Public;
+use serde::Serialize;
 use std::convert::TryFrom;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct FrontierReq {
     start: Public,
     age: u32,
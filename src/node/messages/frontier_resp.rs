@@ -14,6 +14,12 @@ pub struct FrontierResp {
 
 impl FrontierResp {
     pub const LEN: usize = Public::LEN + BlockHash::LEN;
+
+    /// A frontier response of zeros marks the end of the stream.
+    pub fn is_end(&self) -> bool {
+        self.account.as_bytes() == [0u8; Public::LEN]
+            && self.frontier_hash.as_bytes() == [0u8; BlockHash::LEN]
+    }
 }
 
 impl Wire for FrontierResp {
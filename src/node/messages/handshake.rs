@@ -3,9 +3,10 @@ use crate::node::cookie::Cookie;
 use crate::node::header::Header;
 use crate::node::wire::Wire;
 use crate::{Public, Signature};
+use serde::Serialize;
 use std::convert::TryFrom;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Handshake {
     pub query: Option<HandshakeQuery>,
     pub response: Option<HandshakeResponse>,
@@ -57,7 +58,7 @@ impl Wire for Handshake {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct HandshakeQuery(pub Cookie);
 
 impl<'a> HandshakeQuery {
@@ -74,7 +75,7 @@ impl<'a> HandshakeQuery {
 
 impl Wire for HandshakeQuery {
     fn serialize(&self) -> Vec<u8> {
-        self.0.serialize()
+        Wire::serialize(&self.0)
     }
 
     fn deserialize(header: Option<&Header>, data: &[u8]) -> anyhow::Result<Self>
@@ -90,7 +91,7 @@ impl Wire for HandshakeQuery {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct HandshakeResponse {
     pub public: Public,
     pub signature: Signature,
@@ -2,8 +2,9 @@ use crate::bytes::Bytes;
 use crate::node::header::Header;
 use crate::node::peer_info::PeerInfo;
 use crate::node::wire::Wire;
+use serde::Serialize;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Keepalive(Vec<PeerInfo>);
 
 impl Keepalive {
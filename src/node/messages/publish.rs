@@ -1,8 +1,9 @@
 use crate::blocks::BlockHolder;
 use crate::node::header::Header;
 use crate::node::wire::Wire;
+use serde::Serialize;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Publish(pub(crate) BlockHolder);
 
 impl Wire for Publish {
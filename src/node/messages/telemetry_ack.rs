@@ -2,12 +2,13 @@ use crate::blocks::BlockHash;
 use crate::bytes::Bytes;
 use crate::node::header::Header;
 use crate::node::wire::Wire;
-use crate::{Public, Signature};
+use crate::rpc::calls::TelemetryResponse;
+use crate::{Difficulty, Public, Signature};
 use anyhow::Context;
+use serde::Serialize;
 use std::convert::TryFrom;
-use tracing::warn;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct TelemetryAck {
     signature: Signature,
     node_id: Public,
@@ -31,6 +32,28 @@ pub struct TelemetryAck {
 
 impl TelemetryAck {
     pub const LEN: usize = 202;
+
+    /// The same fields as the `telemetry` RPC returns for a peer.
+    pub fn to_response(&self) -> TelemetryResponse {
+        TelemetryResponse {
+            block_count: self.block_count,
+            cemented_count: self.cemented_count,
+            unchecked_count: self.unchecked_count,
+            account_count: self.account_count,
+            bandwidth_cap: self.bandwidth_cap,
+            peer_count: self.peer_count,
+            protocol_version: self.protocol_version,
+            uptime: self.uptime,
+            genesis_block: self.genesis_block.clone(),
+            major_version: self.major_version,
+            minor_version: self.minor_version,
+            patch_version: self.patch_version,
+            pre_release_version: self.prerelease_version,
+            maker: self.maker,
+            timestamp: u64::from_be_bytes(self.timestamp),
+            active_difficulty: Difficulty::new(u64::from_be_bytes(self.active_difficulty)),
+        }
+    }
 }
 
 impl Wire for TelemetryAck {
@@ -95,8 +118,8 @@ impl Wire for TelemetryAck {
         s.prerelease_version = bytes.u8()?;
         s.maker = bytes.u8()?;
 
-        warn!("TODO: telemetry ack timestamp");
-        warn!("TODO: telemetry ack active_difficulty");
+        s.timestamp.copy_from_slice(bytes.slice(8)?);
+        s.active_difficulty.copy_from_slice(bytes.slice(8)?);
 
         Ok(s)
     }
@@ -1,7 +1,8 @@
 use crate::node::header::Header;
 use crate::node::wire::Wire;
+use serde::Serialize;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct TelemetryReq;
 
 impl Wire for TelemetryReq {
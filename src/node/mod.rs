@@ -1,27 +1,39 @@
+mod annotation;
 mod command;
 mod cookie;
+mod dissect;
+mod elections;
 mod header;
+pub(crate) mod ledger_event;
 mod messages;
-mod peer;
+pub(crate) mod peer;
 mod peer_info;
+mod recorder;
 mod state;
-mod timestamp;
+pub(crate) mod timestamp;
 mod wire;
 
+use crate::node::elections::Elections;
+use crate::node::ledger_event::LedgerEvent;
 use crate::rpc::server::RPCServer;
+use crate::rpc::websocket::WebSocketServer;
 use crate::Network;
 pub use crate::Version;
+pub use annotation::{Annotation, Direction};
 use anyhow::Context;
+use chrono::Utc;
 pub use command::{NodeCommand, NodeCommandReceiver, NodeCommandSender};
-pub use header::Header;
+pub use dissect::{dissect, payload_fields, DissectStatus, DissectedField, DissectedMessage};
+pub use header::{Header, MessageType};
 pub use peer::{Packet, Peer};
+pub use recorder::{Recorder, RecorderSender};
 pub use state::{ArcState, MemoryState, SledDiskState};
 use std::net::SocketAddr;
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex};
 use tokio::task::JoinHandle;
 use tracing::{debug, error, info, instrument};
 pub use wire::Wire;
@@ -29,11 +41,23 @@ pub use wire::Wire;
 pub struct Node {
     network: Network,
     state: ArcState,
+
+    /// Where to send a copy of all traffic, when recording to pcapng.
+    recorder: Option<RecorderSender>,
+
+    /// Publishes elections, votes and telemetry to WebSocket clients.
+    websocket: WebSocketServer,
 }
 
 impl Node {
-    pub async fn start(override_peers: Option<Vec<String>>) -> anyhow::Result<()> {
+    pub async fn start(
+        override_peers: Option<Vec<String>>,
+        recorder: Option<Recorder>,
+    ) -> anyhow::Result<()> {
         let mut node = Node::new(Network::Live);
+        if let Some(recorder) = recorder {
+            node.record(recorder);
+        }
         let rpc_rx = node.start_rpc_server().await?;
         if let Some(str_addrs) = override_peers {
             let mut socket_addrs = vec![];
@@ -54,21 +78,41 @@ impl Node {
         // let state = SledDiskState::new(Network::Live);
         let state = MemoryState::new(network);
         let state = Arc::new(Mutex::new(state));
-        Self { state, network }
+        Self {
+            state,
+            network,
+            recorder: None,
+            websocket: WebSocketServer::new(),
+        }
+    }
+
+    /// Record the data sent and received on every connection from now on.
+    pub fn record(&mut self, recorder: Recorder) -> &mut Self {
+        self.recorder = Some(recorder.spawn());
+        self
+    }
+
+    pub(crate) fn state(&self) -> &ArcState {
+        &self.state
     }
 
     pub async fn start_rpc_server(&self) -> anyhow::Result<NodeCommandReceiver> {
-        let (rpc_server, rx) = RPCServer::new_with_channel(self.state.clone());
+        let (rpc_server, rx) =
+            RPCServer::new_with_channel(self.state.clone(), self.websocket.clone());
         tokio::spawn(rpc_server.run());
         Ok(rx)
     }
 
     pub async fn run(self, mut node_rx: NodeCommandReceiver) -> anyhow::Result<()> {
+        let (ledger_tx, ledger_rx) = mpsc::unbounded_channel();
+        tokio::spawn(Elections::new(self.websocket.clone()).run(ledger_rx));
+
         let initial_peers = self.state.lock().await.peers().await?;
         for address in initial_peers {
             let state = self.state.clone();
             let network = self.network.clone();
-            Self::connection(network, state, address).await?;
+            let recorder = self.recorder.clone();
+            Self::connection(network, state, address, recorder, ledger_tx.clone()).await?;
         }
 
         while let Some(node_command) = node_rx.recv().await {
@@ -82,11 +126,13 @@ impl Node {
         Ok(())
     }
 
-    #[instrument(skip(network, state))]
+    #[instrument(skip(network, state, recorder, ledger_log))]
     pub async fn connection(
         network: Network,
         state: ArcState,
         address: SocketAddr,
+        recorder: Option<RecorderSender>,
+        ledger_log: mpsc::UnboundedSender<LedgerEvent>,
     ) -> anyhow::Result<()> {
         info!("Connecting.");
         let stream = match TcpStream::connect(address).await {
@@ -97,7 +143,10 @@ impl Node {
             }
         };
 
-        let (peer, tx, mut rx) = Peer::new_with_channels(network, state.clone(), address);
+        let local_addr = stream.local_addr().context("Local address of connection")?;
+        let (mut peer, tx, mut rx) = Peer::new_with_channels(network, state.clone(), address);
+        peer.ledger_log = Some(ledger_log);
+        let writer_recorder = recorder.clone();
 
         // Task for the Peer handler.
         let peer_task = tokio::spawn(peer.run());
@@ -112,8 +161,10 @@ impl Node {
                     .read(&mut buffer)
                     .await
                     .with_context(|| format!("Could not read from socket at {}", address))?;
+                let data = &buffer[0..bytes];
+                Self::copy_to_recorder(&recorder, Direction::Recv, address, local_addr, data);
 
-                let result = tx.send(Packet::new(Vec::from(&buffer[0..bytes]))).await;
+                let result = tx.send(Packet::new(data.to_vec())).await;
                 if result.is_err() {
                     // When the channel disconnects from Peer, we rely on Peer to report the error.
                     break;
@@ -137,6 +188,13 @@ impl Node {
                     .write_all(&to_send.data)
                     .await
                     .with_context(|| format!("Could not send to socket at {}", address))?;
+                Self::copy_to_recorder(
+                    &writer_recorder,
+                    Direction::Send,
+                    local_addr,
+                    address,
+                    &to_send.data,
+                );
             }
             Ok(())
         });
@@ -154,6 +212,30 @@ impl Node {
         Ok(())
     }
 
+    /// Send a copy of data that went over a connection to the recorder, if there is one.
+    fn copy_to_recorder(
+        recorder: &Option<RecorderSender>,
+        direction: Direction,
+        source: SocketAddr,
+        destination: SocketAddr,
+        data: &[u8],
+    ) {
+        let recorder = match recorder {
+            Some(r) => r,
+            None => return,
+        };
+        let annotation = Annotation {
+            packet: 0,
+            timestamp: Utc::now(),
+            direction,
+            source,
+            destination,
+            size: data.len(),
+        };
+        // The recorder stops on errors, which it has already logged.
+        let _ = recorder.send(Packet::new_with_annotation(data.to_vec(), annotation));
+    }
+
     pub async fn add_peers(&mut self, socket_addrs: &[SocketAddr]) -> anyhow::Result<()> {
         debug!("Adding peers to state: {:?}", socket_addrs);
         self.state.lock().await.add_peers(socket_addrs).await?;
@@ -1,8 +1,10 @@
-use super::Peer;
+use super::{BootstrapStream, Peer, RecvState};
 use crate::blocks::{Block, BlockHash, BlockHolder, BlockType, Link, Previous, StateBlock};
 use crate::node::cookie::Cookie;
 use crate::node::header::{Extensions, Header, MessageType};
-use crate::node::messages::confirm_ack::ConfirmAck;
+use crate::node::ledger_event::{BlockOutcome, LedgerEvent};
+use crate::node::messages::bulk_pull::BulkPull;
+use crate::node::messages::confirm_ack::{Confirm, ConfirmAck};
 use crate::node::messages::confirm_req::ConfirmReq;
 use crate::node::messages::frontier_req::FrontierReq;
 use crate::node::messages::frontier_resp::FrontierResp;
@@ -11,7 +13,8 @@ use crate::node::messages::keepalive::Keepalive;
 use crate::node::messages::publish::Publish;
 use crate::node::messages::telemetry_ack::TelemetryAck;
 use crate::node::messages::telemetry_req::TelemetryReq;
-use crate::{Difficulty, Public, Seed, Signature};
+use crate::node::wire::Wire;
+use crate::{Difficulty, Public, Raw, Seed, Signature};
 use anyhow::anyhow;
 use anyhow::Context;
 use std::convert::TryFrom;
@@ -133,9 +136,12 @@ impl Peer {
     pub async fn handle_telemetry_ack(
         &mut self,
         _header: &Header,
-        _telemetry_ack: TelemetryAck,
+        telemetry_ack: TelemetryAck,
     ) -> anyhow::Result<()> {
-        // dbg!(telemetry_ack);
+        self.log_ledger_event(LedgerEvent::Telemetry {
+            peer_addr: self.peer_addr,
+            telemetry: telemetry_ack.to_response(),
+        });
         Ok(())
     }
 
@@ -144,42 +150,56 @@ impl Peer {
         _header: &Header,
         publish: Publish,
     ) -> anyhow::Result<()> {
-        let _block = match publish.0 {
-            BlockHolder::Send(_) => {
-                todo!("Received a send block")
-            }
-            BlockHolder::Receive(_) => {
-                todo!("Received a receive block")
-            }
-            BlockHolder::Open(_) => {
-                todo!("Received an open block")
-            }
-            BlockHolder::Change(_) => {
-                todo!("Received a change block")
-            }
-            BlockHolder::State(state_block) => {
-                self.state_block_handler(state_block).await?;
-            }
-        };
-
-        Ok(())
+        self.process_block_holder(publish.0).await
     }
 
     pub async fn handle_confirm_req(
         &mut self,
         _header: &Header,
-        _confirm_req: ConfirmReq,
+        confirm_req: ConfirmReq,
     ) -> anyhow::Result<()> {
-        // dbg!(confirm_req);
+        // A block up for election might not have been published to us.
+        if let ConfirmReq::BlockSelector(holder) = confirm_req {
+            self.process_block_holder(holder).await?;
+        }
         Ok(())
     }
 
     pub async fn handle_confirm_ack(
         &mut self,
         _header: &Header,
-        _confirm_ack: ConfirmAck,
+        confirm_ack: ConfirmAck,
     ) -> anyhow::Result<()> {
-        // dbg!(confirm_ack);
+        if let Err(err) = confirm_ack.verify_signature() {
+            warn!("Ignoring vote: {:?}", err);
+            return Ok(());
+        }
+
+        let hashes = match confirm_ack.confirm {
+            Confirm::VoteByHash(hashes) => hashes,
+            Confirm::Block(block) => {
+                let hash = block.hash()?.to_owned();
+                if *block.block_type() == BlockType::State {
+                    let state_block = StateBlock::try_from(block)?;
+                    self.process_block_holder(BlockHolder::State(state_block))
+                        .await?;
+                }
+                vec![hash]
+            }
+        };
+        for hash in hashes {
+            self.state
+                .lock()
+                .await
+                .add_vote(&hash, &confirm_ack.account)
+                .await?;
+            self.log_ledger_event(LedgerEvent::Vote {
+                hash,
+                representative: confirm_ack.account.to_owned(),
+                signature: confirm_ack.signature.to_owned(),
+                timestamp: confirm_ack.timestamp.to_owned(),
+            });
+        }
         Ok(())
     }
 
@@ -188,22 +208,95 @@ impl Peer {
         _header: &Header,
         _frontier_req: FrontierReq,
     ) -> anyhow::Result<()> {
-        // The rest of this connection will be a bunch of frontiers without any headers.
-        self.frontier_stream = true;
+        // The response will be a bunch of frontiers without any headers.
+        self.requested_stream = Some(BootstrapStream::Frontiers);
 
         Ok(())
     }
 
     pub async fn handle_frontier_resp(
         &mut self,
-        _frontier_resp: FrontierResp,
+        frontier_resp: FrontierResp,
+    ) -> anyhow::Result<()> {
+        debug!("{:?}", frontier_resp);
+        Ok(())
+    }
+
+    pub async fn handle_bulk_pull(
+        &mut self,
+        _header: &Header,
+        _bulk_pull: BulkPull,
     ) -> anyhow::Result<()> {
-        // dbg!(frontier_resp);
-        // dbg!("----------------------------------------------------------------------");
+        // The response will be blocks without any headers.
+        self.requested_stream = Some(BootstrapStream::Blocks);
 
         Ok(())
     }
 
+    /// Receive the next block of a bulk pull response, where each block is preceded by its type
+    /// and the last is followed by [BlockType::NotABlock].
+    pub(super) async fn recv_pulled_block(&mut self) -> anyhow::Result<(RecvState, bool)> {
+        let waiting = (RecvState::Response(BootstrapStream::Blocks), false);
+        let block_type = match self.incoming_buffer.first() {
+            Some(b) => BlockType::try_from(*b)?,
+            None => return Ok(waiting),
+        };
+        if block_type == BlockType::NotABlock {
+            self.recv_immediate(1)?;
+            return Ok((RecvState::Header, true));
+        }
+
+        // Blocks are decoded using the type in a header.
+        let header = Header::new(
+            self.network,
+            MessageType::BulkPull,
+            *Extensions::new().set_block_type(&block_type),
+        );
+        if self.incoming_buffer.len() < 1 + BlockHolder::len(Some(&header))? {
+            return Ok(waiting);
+        }
+        self.recv_immediate(1)?;
+        let holder = self
+            .recv::<BlockHolder>(Some(&header))?
+            .context("Receiving pulled block")?;
+        self.process_block_holder(holder).await?;
+        Ok((RecvState::Response(BootstrapStream::Blocks), true))
+    }
+
+    /// Validate and store a block, reporting what happened to it.
+    async fn process_block_holder(&self, holder: BlockHolder) -> anyhow::Result<()> {
+        let event = match holder {
+            BlockHolder::State(state_block) => {
+                let hash = Some(state_block.hash.to_owned());
+                let account = Some(state_block.account.to_owned());
+                let outcome = self.state_block_handler(state_block).await?;
+                LedgerEvent::Block {
+                    hash,
+                    account,
+                    outcome,
+                }
+            }
+            // TODO: Legacy blocks
+            holder => {
+                info!("Skipping legacy block {:?}", holder);
+                LedgerEvent::Block {
+                    hash: None,
+                    account: None,
+                    outcome: BlockOutcome::Unsupported,
+                }
+            }
+        };
+        self.log_ledger_event(event);
+        Ok(())
+    }
+
+    fn log_ledger_event(&self, event: LedgerEvent) {
+        if let Some(log) = &self.ledger_log {
+            // Nothing to do if whoever was listening has gone.
+            let _ = log.send(event);
+        }
+    }
+
     /// Returns the previous block if is a head block AND is a state_block
     /// Note: the returned block won't have Work, Amount or Signature
     async fn previous_as_account_info(
@@ -245,45 +338,70 @@ impl Peer {
 
     /// Actions to be performed to validate and store a state block
     /// TODO: this assumes we will never get a live epoch block
-    async fn state_block_handler(&self, state_block: StateBlock) -> anyhow::Result<()> {
+    async fn state_block_handler(&self, state_block: StateBlock) -> anyhow::Result<BlockOutcome> {
         // TODO: here there should be a check for epoch blocks
         if self.block_existed(&state_block.hash).await? {
-            info!("Block {} already exists!", state_block)
+            info!("Block {} already exists!", state_block);
+            Ok(BlockOutcome::AlreadyExists)
         } else if state_block.verify_self_signature().is_err() {
-            info!("Block {} has invalid signature!", state_block)
+            info!("Block {} has invalid signature!", state_block);
+            Ok(BlockOutcome::BadSignature)
         } else {
-            self.process_valid_existing_state_block(state_block).await?
+            self.process_valid_existing_state_block(state_block).await
         }
-        Ok(())
     }
 
     async fn process_valid_existing_state_block(
         &self,
         state_block: StateBlock,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<BlockOutcome> {
         match &state_block.previous {
             Previous::Block(previous_hash) => {
                 // Either wants to send, receive or change
-                let maybe_previous_block = self.previous_as_account_info(previous_hash).await?;
-                if let Some(previous_state_block) = maybe_previous_block {
-                    self.process_block_with_previous(state_block, previous_state_block)
-                        .await?
-                } else {
-                    info!("Block before {} not found!", state_block)
+                match self.previous_as_account_info(previous_hash).await {
+                    Ok(Some(previous_state_block)) => {
+                        self.process_block_with_previous(state_block, previous_state_block)
+                            .await
+                    }
+                    Ok(None) => {
+                        info!("Block before {} not found!", state_block);
+                        Ok(BlockOutcome::GapPrevious)
+                    }
+                    Err(err) => {
+                        info!("Block before {} can't be used: {:?}", state_block, err);
+                        Ok(BlockOutcome::BadPrevious)
+                    }
                 }
             }
-            Previous::Open => {
-                todo!("Received an open sub-block")
-            }
+            Previous::Open => self.process_open_sub_block(state_block).await,
         }
-        Ok(())
+    }
+
+    async fn process_open_sub_block(
+        &self,
+        mut open_block: StateBlock,
+    ) -> anyhow::Result<BlockOutcome> {
+        let balance = open_block.balance.to_owned();
+        open_block
+            .set_link_type(false, balance)
+            .context("Could not decide link type!")?;
+        if open_block.balance == Raw::zero() || open_block.link == Link::Nothing {
+            // TODO: Epoch open blocks
+            info!("Skipping open sub-block without an amount {}", open_block);
+            return Ok(BlockOutcome::Unsupported);
+        }
+        if self.get_latest_block(&open_block.account).await?.is_some() {
+            info!("Account of open sub-block {} is already open!", open_block);
+            return Ok(BlockOutcome::BadPrevious);
+        }
+        self.process_good_receive_sub_block(open_block).await
     }
 
     async fn process_block_with_previous(
         &self,
         mut state_block: StateBlock,
         previous_state_block: StateBlock,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<BlockOutcome> {
         let is_send = state_block.balance < previous_state_block.balance;
         let amount = if is_send {
             previous_state_block
@@ -295,18 +413,18 @@ impl Peer {
                 .checked_sub(&previous_state_block.balance)
         };
         let amount = amount.ok_or(anyhow!("Could not calculate amount!"))?;
+        let has_amount = amount != Raw::zero();
         state_block
             .set_link_type(is_send, amount)
             .context("Could not decide link type!")?;
         match state_block.link {
-            Link::Nothing => {
-                let _live_epoch_2_change_threshold = 0xfffffff800000000u64;
-                todo!("Received a change sub-block")
-            }
-            Link::Source(_) => {
-                let _live_epoch_2_receive_threshold = 0xfffffe0000000000u64;
-                todo!("Received a receive sub-block")
+            Link::Nothing => self.process_good_change_sub_block(state_block).await,
+            Link::Source(_) if !has_amount => {
+                // TODO: Epoch blocks
+                info!("Skipping sub-block without an amount {}", state_block);
+                Ok(BlockOutcome::Unsupported)
             }
+            Link::Source(_) => self.process_good_receive_sub_block(state_block).await,
             Link::DestinationAccount(_) => self.process_good_send_sub_block(state_block).await,
             Link::Unsure(_) => {
                 panic!("Unexpected error! Was `decide_link_type` called on this block?")
@@ -314,28 +432,74 @@ impl Peer {
         }
     }
 
-    async fn process_good_send_sub_block(&self, send_block: StateBlock) -> anyhow::Result<()> {
+    async fn process_good_send_sub_block(
+        &self,
+        send_block: StateBlock,
+    ) -> anyhow::Result<BlockOutcome> {
         let live_epoch_2_send_threshold = 0xfffffff800000000u64;
-        let block_difficulty = send_block
+        if !Peer::has_enough_work(&send_block, live_epoch_2_send_threshold)? {
+            return Ok(BlockOutcome::InsufficientWork);
+        }
+        self.store_block(&Block::from_state_block(&send_block))
+            .await?;
+        // TODO: Update rep weight cache
+        // TODO: Add to pending transactions
+        Ok(BlockOutcome::Accepted)
+    }
+
+    async fn process_good_change_sub_block(
+        &self,
+        change_block: StateBlock,
+    ) -> anyhow::Result<BlockOutcome> {
+        let live_epoch_2_change_threshold = 0xfffffff800000000u64;
+        if !Peer::has_enough_work(&change_block, live_epoch_2_change_threshold)? {
+            return Ok(BlockOutcome::InsufficientWork);
+        }
+        self.store_block(&Block::from_state_block(&change_block))
+            .await?;
+        // TODO: Update rep weight cache
+        Ok(BlockOutcome::Accepted)
+    }
+
+    /// Receive and open sub-blocks.
+    async fn process_good_receive_sub_block(
+        &self,
+        receive_block: StateBlock,
+    ) -> anyhow::Result<BlockOutcome> {
+        let live_epoch_2_receive_threshold = 0xfffffe0000000000u64;
+        if let Link::Source(source) = &receive_block.link {
+            if !self.block_exists(source).await? {
+                info!("Source of {} not found!", receive_block);
+                return Ok(BlockOutcome::GapSource);
+            }
+        }
+        if !Peer::has_enough_work(&receive_block, live_epoch_2_receive_threshold)? {
+            return Ok(BlockOutcome::InsufficientWork);
+        }
+        self.store_block(&Block::from_state_block(&receive_block))
+            .await?;
+        // TODO: Check the source is a send to this account for this amount, and isn't received
+        //       already, once pending transactions are tracked.
+        // TODO: Update rep weight cache
+        Ok(BlockOutcome::Accepted)
+    }
+
+    fn has_enough_work(block: &StateBlock, threshold: u64) -> anyhow::Result<bool> {
+        let block_difficulty = block
             .work
             .as_ref()
-            .ok_or(anyhow!("Send sub-block {} has no work!", &send_block))?
-            .difficulty_block_hash(&send_block.hash)?;
-        let work_ok = block_difficulty >= Difficulty::new(live_epoch_2_send_threshold);
+            .ok_or(anyhow!("Sub-block {} has no work!", &block))?
+            .difficulty_block_hash(&block.hash)?;
+        let work_ok = block_difficulty >= Difficulty::new(threshold);
         if !work_ok {
-            info!("Send sub-block {} has insufficient difficulty!", send_block);
+            info!("Sub-block {} has insufficient difficulty!", block);
             debug!(
-                "Send sub-block {} had difficulty {}",
-                send_block,
+                "Sub-block {} had difficulty {}",
+                block,
                 block_difficulty.as_u64()
             );
-        } else {
-            self.store_block(&Block::from_state_block(&send_block))
-                .await?
-            // TODO: Update rep weight cache
-            // TODO: Add to pending transactions
         }
-        Ok(())
+        Ok(work_ok)
     }
 
     async fn store_block(&self, block: &Block) -> anyhow::Result<()> {
@@ -533,4 +697,124 @@ mod tests {
         let block_was_stored = Peer::block_exists(&peer, &frontier.hash).await.unwrap();
         assert_eq!(block_was_stored, false)
     }
+
+    #[tokio::test]
+    async fn should_check_source_of_open_and_receive() {
+        let (mut open, _) = root_block();
+        open.work = Some(Work::zero());
+        let source = StateBlock::new(
+            open.representative.to_owned(),
+            Previous::Block(BlockHash::zero()),
+            open.representative.to_owned(),
+            Raw(0),
+            Link::DestinationAccount(open.account.to_owned()),
+        );
+        let source_block = Block::from_state_block(&source);
+
+        let peer = test_peer_with_blocks(&[]).await;
+        assert_eq!(
+            Peer::process_valid_existing_state_block(&peer, open.clone())
+                .await
+                .unwrap(),
+            BlockOutcome::GapSource
+        );
+
+        // The source is known, so it gets as far as checking work.
+        let mut source_open = open.clone();
+        source_open.link = Link::Source(source.hash.to_owned());
+        let peer = test_peer_with_blocks(&[&source_block]).await;
+        assert_eq!(
+            Peer::process_valid_existing_state_block(&peer, source_open.clone())
+                .await
+                .unwrap(),
+            BlockOutcome::InsufficientWork
+        );
+
+        let (root, root_block) = root_block();
+        let mut receive = StateBlock::new(
+            root.account.to_owned(),
+            Previous::Block(root.hash.to_owned()),
+            root.representative.to_owned(),
+            Raw(600),
+            Link::Source(BlockHash::zero()),
+        );
+        receive.work = Some(Work::zero());
+        let peer = test_peer_with_blocks(&[&root_block]).await;
+        assert_eq!(
+            Peer::process_block_with_previous(&peer, receive, root.clone())
+                .await
+                .unwrap(),
+            BlockOutcome::GapSource
+        );
+
+        // The account is already open.
+        assert_eq!(
+            Peer::process_valid_existing_state_block(&peer, source_open)
+                .await
+                .unwrap(),
+            BlockOutcome::BadPrevious
+        );
+    }
+
+    #[tokio::test]
+    async fn should_check_work_of_change() {
+        let (root, root_block) = root_block();
+        let mut change = StateBlock::new(
+            root.account.to_owned(),
+            Previous::Block(root.hash.to_owned()),
+            root.account.to_owned(),
+            root.balance.to_owned(),
+            Link::Nothing,
+        );
+        change.work = Some(Work::zero());
+        let peer = test_peer_with_blocks(&[&root_block]).await;
+        assert_eq!(
+            Peer::process_block_with_previous(&peer, change.clone(), root)
+                .await
+                .unwrap(),
+            BlockOutcome::InsufficientWork
+        );
+        assert!(!Peer::block_exists(&peer, &change.hash).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn should_report_block_outcomes() {
+        let (root, root_block) = root_block();
+        let (frontier, _) = frontier_block();
+        let peer = test_peer_with_blocks(&[&root_block]).await;
+        assert_eq!(
+            Peer::process_block_with_previous(&peer, frontier.clone(), root)
+                .await
+                .unwrap(),
+            BlockOutcome::Accepted
+        );
+        assert_eq!(
+            Peer::process_good_send_sub_block(&peer, bad_send_block())
+                .await
+                .unwrap(),
+            BlockOutcome::InsufficientWork
+        );
+
+        let mut peer = test_peer_with_blocks(&[]).await;
+        assert_eq!(
+            Peer::process_valid_existing_state_block(&peer, frontier.clone())
+                .await
+                .unwrap(),
+            BlockOutcome::GapPrevious
+        );
+
+        // The test blocks aren't signed.
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        peer.ledger_log = Some(tx);
+        Peer::process_block_holder(&peer, BlockHolder::State(frontier.clone()))
+            .await
+            .unwrap();
+        match rx.recv().await.unwrap() {
+            LedgerEvent::Block { hash, outcome, .. } => {
+                assert_eq!(hash, Some(frontier.hash));
+                assert_eq!(outcome, BlockOutcome::BadSignature);
+            }
+            event => panic!("Unexpected {:?}", event),
+        }
+    }
 }
@@ -5,7 +5,10 @@ mod messages;
 use crate::blocks::Block;
 use crate::encoding::to_hex;
 use crate::network::Network;
+use crate::node::annotation::Annotation;
 use crate::node::header::{Extensions, Header, MessageType};
+use crate::node::ledger_event::LedgerEvent;
+use crate::node::messages::frontier_resp::FrontierResp;
 use crate::node::state::ArcState;
 use crate::node::wire::Wire;
 use crate::{Public, Raw};
@@ -13,16 +16,20 @@ use anyhow::{anyhow, Context};
 use std::fmt::Debug;
 use std::net::SocketAddr;
 use tokio::sync::mpsc;
-use tracing::{debug, info, instrument, trace};
+use tracing::{debug, info, instrument, trace, warn};
 
 /// A message sent between channels that contains a peer's network data.
 #[derive(Debug)]
 pub struct Packet {
     /// Used by pcap to annotate direction and packet number, etc.
-    pub annotation: Option<String>,
+    pub annotation: Option<Annotation>,
 
     /// The data sent to/from a peer.
     pub data: Vec<u8>,
+
+    /// Data is missing before this packet, e.g. a TCP segment that wasn't captured, so the peer
+    /// has to look for the next header.
+    pub gap: bool,
 }
 
 impl Packet {
@@ -30,15 +37,47 @@ impl Packet {
         Self {
             data,
             annotation: None,
+            gap: false,
         }
     }
 
-    pub fn new_with_annotation(data: Vec<u8>, annotation: String) -> Self {
+    pub fn new_with_annotation(data: Vec<u8>, annotation: Annotation) -> Self {
         Self {
             data,
             annotation: Some(annotation),
+            gap: false,
         }
     }
+
+    /// Mark that data is missing before this packet.
+    pub fn gap(&mut self) -> &mut Self {
+        self.gap = true;
+        self
+    }
+}
+
+/// A message decoded by a peer, sent to [Peer::message_log].
+#[derive(Debug, Clone)]
+pub struct LoggedMessage {
+    /// The annotation of the packet that completed the message.
+    pub annotation: Option<Annotation>,
+    pub message_type: MessageType,
+
+    /// Bytes of the header and payload.
+    pub size: usize,
+
+    /// The decoded payload.
+    pub payload: serde_json::Value,
+}
+
+/// A bootstrap response, which is sent without headers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BootstrapStream {
+    /// Frontier responses, from a frontier request.
+    Frontiers,
+
+    /// Blocks each preceded by their type, from a bulk pull.
+    Blocks,
 }
 
 enum RecvState {
@@ -47,6 +86,12 @@ enum RecvState {
 
     /// Waiting for the payload.
     Payload(Header),
+
+    /// Lost track of the stream, so skipping data until something looks like a header.
+    Resync,
+
+    /// Receiving a bootstrap response until it ends.
+    Response(BootstrapStream),
 }
 
 /// Handles the logic of one peer. It handles and emits messages, as well as time
@@ -55,13 +100,23 @@ pub struct Peer {
     /// Disable when used for pcap dump, where might have our own different cookie.
     pub validate_handshakes: bool,
 
+    /// Skip messages that fail to decode or handle instead of disconnecting, looking for the next
+    /// header. Used for pcap dumps, where a broken message shouldn't stop the rest of the stream.
+    pub resync_on_error: bool,
+
+    /// Where to send every decoded message, e.g. for a structured pcap export.
+    pub message_log: Option<mpsc::UnboundedSender<LoggedMessage>>,
+
+    /// Where to send what happened to each block and vote, e.g. for a pcap replay.
+    pub ledger_log: Option<mpsc::UnboundedSender<LedgerEvent>>,
+
     network: Network,
     state: ArcState,
     peer_addr: SocketAddr,
     recv_state: RecvState,
 
-    /// Are we doing a frontier req stream? (Bootstrap?)
-    frontier_stream: bool,
+    /// A bootstrap request was received, so the response goes the other way.
+    requested_stream: Option<BootstrapStream>,
 
     /// Internal buffer for incoming data.
     incoming_buffer: Vec<u8>,
@@ -72,7 +127,7 @@ pub struct Peer {
     /// Data to be sent to the other peer.
     peer_tx: mpsc::Sender<Packet>,
 
-    last_annotation: Option<String>,
+    last_annotation: Option<Annotation>,
 }
 
 impl Peer {
@@ -88,11 +143,14 @@ impl Peer {
 
         let s = Self {
             validate_handshakes: true,
+            resync_on_error: false,
+            message_log: None,
+            ledger_log: None,
             network,
             state,
             peer_addr,
             recv_state: RecvState::Header,
-            frontier_stream: false,
+            requested_stream: None,
             incoming_buffer: Vec::with_capacity(10_000),
             peer_rx: incoming_rx,
             peer_tx: outgoing_tx,
@@ -121,18 +179,65 @@ impl Peer {
         Ok(())
     }
 
+    /// Handle data from the remote host, when driving the peer directly instead of with [Peer::run].
     #[instrument(skip(self, packet))]
-    async fn handle_packet(&mut self, packet: Packet) -> anyhow::Result<()> {
+    pub async fn handle_packet(&mut self, packet: Packet) -> anyhow::Result<()> {
         trace!("handle_packet");
 
+        if let Some(annotation) = packet.annotation {
+            self.last_annotation = Some(annotation);
+        }
+        if packet.gap {
+            if !self.incoming_buffer.is_empty() {
+                debug!(
+                    "Dropping {} bytes of an incomplete message",
+                    self.incoming_buffer.len()
+                );
+            }
+            self.incoming_buffer.clear();
+            self.recv_state = RecvState::Resync;
+        }
+        self.incoming_buffer.extend(packet.data);
+
+        loop {
+            let result = self.process_buffer().await;
+            match result {
+                Ok(true) => continue,
+                Ok(false) => break,
+                Err(err) if self.resync_on_error => {
+                    // The bad header or payload has already been taken off the buffer.
+                    warn!("Skipping malformed message: {:?}", err);
+                    self.recv_state = RecvState::Resync;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handle the next header or message in the buffer. Returns false when more data is needed.
+    async fn process_buffer(&mut self) -> anyhow::Result<bool> {
         macro_rules! handle {
             ($self: ident, $fun:ident, $header:expr) => {{
                 let sh = Some(&$header);
+                let available = self.incoming_buffer.len();
                 let payload = self
                     .recv(sh)
                     .with_context(|| format!("Receiving payload for {:?}", $header))?;
 
                 if let Some(payload) = payload {
+                    if let Some(log) = &self.message_log {
+                        let message = LoggedMessage {
+                            annotation: self.last_annotation.clone(),
+                            message_type: $header.message_type(),
+                            size: Header::LEN + available - self.incoming_buffer.len(),
+                            payload: serde_json::to_value(&payload).context("Logging payload")?,
+                        };
+                        // Nothing to do if whoever was listening has gone.
+                        let _ = log.send(message);
+                    }
+
                     match &self.last_annotation {
                         Some(a) => info!("{} {:?}", a, &payload),
                         None => debug!("{:?}", &payload),
@@ -143,60 +248,82 @@ impl Peer {
                         .await
                         .with_context(|| format!("Handling payload for {:?}", $header))?;
                 } else {
+                    // Wait for the rest of the payload.
+                    return Ok(false);
                 }
             };};
         }
 
-        if let Some(annotation) = packet.annotation {
-            self.last_annotation = Some(annotation);
-        }
-        self.incoming_buffer.extend(packet.data);
-
-        // TODO: Handle frontier stream
-        // if self.frontier_stream {
-        //     let payload = self.recv::<FrontierResp>(None).await?;
-        //     self.handle_frontier_resp(payload).await?;
-        // } else {
-
-        loop {
-            let (new_state, process) = match self.recv_state {
-                RecvState::Header => {
-                    if let Some(header) = self.recv::<Header>(None)? {
-                        header.validate(&self.network)?;
-                        (RecvState::Payload(header), true)
-                    } else {
-                        (RecvState::Header, false)
+        let (new_state, process) = match self.recv_state {
+            RecvState::Resync => match Header::find(&self.network, &self.incoming_buffer) {
+                Some(offset) => {
+                    debug!("Found a header after skipping {} bytes", offset);
+                    self.incoming_buffer.drain(..offset);
+                    (RecvState::Header, true)
+                }
+                None => {
+                    // Keep the end in case a header is split over packets.
+                    let keep = self.incoming_buffer.len().min(Header::LEN - 1);
+                    let skip = self.incoming_buffer.len() - keep;
+                    self.incoming_buffer.drain(..skip);
+                    (RecvState::Resync, false)
+                }
+            },
+            RecvState::Response(BootstrapStream::Frontiers) => {
+                match self.recv::<FrontierResp>(None)? {
+                    Some(frontier) if frontier.is_end() => (RecvState::Header, true),
+                    Some(frontier) => {
+                        self.handle_frontier_resp(frontier).await?;
+                        (RecvState::Response(BootstrapStream::Frontiers), true)
                     }
+                    None => (RecvState::Response(BootstrapStream::Frontiers), false),
                 }
-                RecvState::Payload(header) => {
-                    trace!(
-                        "Attempt to handle message of type: {:?}",
-                        header.message_type()
-                    );
-                    match header.message_type() {
-                        MessageType::Keepalive => handle!(self, handle_keepalive, header),
-                        MessageType::Publish => handle!(self, handle_publish, header),
-                        MessageType::ConfirmReq => handle!(self, handle_confirm_req, header),
-                        MessageType::ConfirmAck => handle!(self, handle_confirm_ack, header),
-                        MessageType::FrontierReq => handle!(self, handle_frontier_req, header),
-                        MessageType::Handshake => handle!(self, handle_handshake, header),
-                        MessageType::TelemetryReq => handle!(self, handle_telemetry_req, header),
-                        MessageType::TelemetryAck => handle!(self, handle_telemetry_ack, header),
-                        // MessageType::BulkPull => {}
-                        // MessageType::BulkPush => {}
-                        // MessageType::BulkPullAccount => {}
-                        _ => return Err(anyhow!("Unhandled message: {:?}", header)),
-                    };
+            }
+            RecvState::Response(BootstrapStream::Blocks) => self.recv_pulled_block().await?,
+            RecvState::Header => {
+                if let Some(header) = self.recv::<Header>(None)? {
+                    header.validate(&self.network)?;
+                    (RecvState::Payload(header), true)
+                } else {
                     (RecvState::Header, false)
                 }
-            };
-            self.recv_state = new_state;
-            if !process {
-                break;
             }
-        }
+            RecvState::Payload(header) => {
+                trace!(
+                    "Attempt to handle message of type: {:?}",
+                    header.message_type()
+                );
+                match header.message_type() {
+                    MessageType::Keepalive => handle!(self, handle_keepalive, header),
+                    MessageType::Publish => handle!(self, handle_publish, header),
+                    MessageType::ConfirmReq => handle!(self, handle_confirm_req, header),
+                    MessageType::ConfirmAck => handle!(self, handle_confirm_ack, header),
+                    MessageType::FrontierReq => handle!(self, handle_frontier_req, header),
+                    MessageType::Handshake => handle!(self, handle_handshake, header),
+                    MessageType::TelemetryReq => handle!(self, handle_telemetry_req, header),
+                    MessageType::TelemetryAck => handle!(self, handle_telemetry_ack, header),
+                    MessageType::BulkPull => handle!(self, handle_bulk_pull, header),
+                    // MessageType::BulkPush => {}
+                    // MessageType::BulkPullAccount => {}
+                    _ => return Err(anyhow!("Unhandled message: {:?}", header)),
+                };
+                (RecvState::Header, true)
+            }
+        };
+        self.recv_state = new_state;
+        Ok(process)
+    }
 
-        Ok(())
+    /// Take the bootstrap request this peer received, if any. The response to it comes back on
+    /// the other direction of the connection.
+    pub fn take_requested_stream(&mut self) -> Option<BootstrapStream> {
+        self.requested_stream.take()
+    }
+
+    /// Expect the next data to be a bootstrap response, e.g. when this peer only sees one
+    /// direction of a captured connection.
+    pub fn expect_response_stream(&mut self, stream: BootstrapStream) {
+        self.recv_state = RecvState::Response(stream);
     }
 
     /// Receive from the incoming buffer for type `T`. Will return None if there aren't enough
@@ -290,6 +417,8 @@ mod tests {
     use super::*;
     use crate::blocks::{Block, BlockHash, OpenBlock, Previous, SendBlock};
     use crate::network::DEFAULT_PORT;
+    use crate::node::messages::keepalive::Keepalive;
+    use crate::node::peer_info::PeerInfo;
     use crate::node::state::MemoryState;
     use crate::Address;
     use std::net::{Ipv4Addr, SocketAddrV4};
@@ -308,6 +437,80 @@ mod tests {
         peer
     }
 
+    fn keepalive(network: Network) -> Vec<u8> {
+        let mut data = Header::new(network, MessageType::Keepalive, Extensions::new()).serialize();
+        data.extend_from_slice(&[0u8; PeerInfo::LEN * Keepalive::PEERS]);
+        data
+    }
+
+    #[tokio::test]
+    async fn resync_after_gap() {
+        let network = Network::Live;
+        let mut peer = empty_lattice(network).await;
+
+        // The end of a message that started in a segment missing from the capture.
+        let mut data = vec![0x52, 0x43, 1, 2, 3];
+        data.extend(keepalive(network));
+        data.extend(keepalive(network));
+        let mut packet = Packet::new(data);
+        packet.gap();
+        peer.handle_packet(packet).await.unwrap();
+        assert!(peer.incoming_buffer.is_empty());
+        assert!(matches!(peer.recv_state, RecvState::Header));
+
+        // A message split over two packets.
+        let data = keepalive(network);
+        peer.handle_packet(Packet::new(data[..20].to_vec()))
+            .await
+            .unwrap();
+        assert!(matches!(peer.recv_state, RecvState::Payload(_)));
+        peer.handle_packet(Packet::new(data[20..].to_vec()))
+            .await
+            .unwrap();
+        assert!(peer.incoming_buffer.is_empty());
+    }
+
+    #[tokio::test]
+    async fn skip_malformed() {
+        let network = Network::Live;
+        let mut peer = empty_lattice(network).await;
+
+        let mut data =
+            Header::new(Network::Beta, MessageType::Keepalive, Extensions::new()).serialize();
+        data.extend(keepalive(network));
+        assert!(peer.handle_packet(Packet::new(data.clone())).await.is_err());
+
+        let mut peer = empty_lattice(network).await;
+        peer.resync_on_error = true;
+        peer.handle_packet(Packet::new(data)).await.unwrap();
+        assert!(peer.incoming_buffer.is_empty());
+        assert!(matches!(peer.recv_state, RecvState::Header));
+    }
+
+    #[tokio::test]
+    async fn frontier_stream() {
+        let network = Network::Live;
+        let mut peer = empty_lattice(network).await;
+        peer.expect_response_stream(BootstrapStream::Frontiers);
+
+        // One frontier, the end marker, then back to regular messages.
+        let mut data = vec![1u8; FrontierResp::LEN];
+        data.extend_from_slice(&[0u8; FrontierResp::LEN]);
+        data.extend(keepalive(network));
+        peer.handle_packet(Packet::new(data[..50].to_vec()))
+            .await
+            .unwrap();
+        assert!(matches!(
+            peer.recv_state,
+            RecvState::Response(BootstrapStream::Frontiers)
+        ));
+        peer.handle_packet(Packet::new(data[50..].to_vec()))
+            .await
+            .unwrap();
+        assert!(peer.incoming_buffer.is_empty());
+        assert!(matches!(peer.recv_state, RecvState::Header));
+    }
+
     #[tokio::test]
     async fn genesis() {
         let network = Network::Live;
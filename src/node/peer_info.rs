@@ -4,6 +4,7 @@ use crate::node::wire::Wire;
 use std::net::{Ipv6Addr, SocketAddrV6};
 use std::str::FromStr;
 
+#[derive(serde::Serialize)]
 pub struct PeerInfo(SocketAddrV6);
 
 impl PeerInfo {
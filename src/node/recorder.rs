@@ -0,0 +1,420 @@
+//! Recording of a node's own traffic to pcapng files, which `feeless pcap` and Wireshark can read.
+//!
+//! The node only sees TCP data, so an IP and TCP header is made up for each chunk of data sent or
+//! received, with sequence numbers that follow the bytes of each direction.
+
+use crate::node::{Annotation, Packet};
+use anyhow::Context;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+const SECTION_HEADER_BLOCK: u32 = 0x0A0D_0D0A;
+const INTERFACE_DESCRIPTION_BLOCK: u32 = 0x0000_0001;
+const ENHANCED_PACKET_BLOCK: u32 = 0x0000_0006;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+/// Packets start with an IPv4 or IPv6 header, without a link layer.
+const LINKTYPE_RAW: u16 = 101;
+
+const IPV4_HEADER_LEN: usize = 20;
+const IPV6_HEADER_LEN: usize = 40;
+const TCP_HEADER_LEN: usize = 20;
+const PROTOCOL_TCP: u8 = 6;
+
+const TCP_SYN: u8 = 0x02;
+const TCP_PSH: u8 = 0x08;
+const TCP_ACK: u8 = 0x10;
+
+/// Keep made up segments within the length an IPv4 header can describe.
+const MAX_SEGMENT: usize = 65_000;
+
+/// Where to send packets to be recorded. Each needs an annotation, for its addresses and time.
+pub type RecorderSender = mpsc::UnboundedSender<Packet>;
+
+pub struct Recorder {
+    path: PathBuf,
+
+    /// Start a new file once the current one reaches this size.
+    max_bytes: u64,
+
+    file: Option<BufWriter<File>>,
+    written: u64,
+
+    /// How many times the file has been rotated, used to name the next one.
+    rotation: usize,
+
+    /// The next sequence number of each direction of a connection in the current file.
+    seqs: HashMap<(SocketAddr, SocketAddr), u32>,
+}
+
+impl Recorder {
+    pub fn new(path: PathBuf, max_bytes: u64) -> Self {
+        Self {
+            path,
+            max_bytes,
+            file: None,
+            written: 0,
+            rotation: 0,
+            seqs: HashMap::new(),
+        }
+    }
+
+    /// Record packets sent to the returned channel until every sender is dropped.
+    pub fn spawn(mut self) -> RecorderSender {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Packet>();
+        tokio::task::spawn_blocking(move || {
+            while let Some(packet) = rx.blocking_recv() {
+                let annotation = match &packet.annotation {
+                    Some(a) => a,
+                    None => continue,
+                };
+                if let Err(err) = self.record(annotation, &packet.data) {
+                    error!("Stopped recording: {:?}", err);
+                    return;
+                }
+            }
+        });
+        tx
+    }
+
+    /// Write data that went from `annotation.source` to `annotation.destination`.
+    pub fn record(&mut self, annotation: &Annotation, data: &[u8]) -> anyhow::Result<()> {
+        if self.file.is_none() {
+            self.open()?;
+        }
+
+        let key = (annotation.source, annotation.destination);
+        let reverse = (annotation.destination, annotation.source);
+        let ack = self.seqs.get(&reverse).copied();
+
+        // Each direction starts with a SYN in every file, so readers know where the data starts.
+        if !self.seqs.contains_key(&key) {
+            let flags = if ack.is_some() {
+                TCP_SYN | TCP_ACK
+            } else {
+                TCP_SYN
+            };
+            self.write_segment(annotation, 0, ack.unwrap_or(0), flags, &[])?;
+            self.seqs.insert(key, 1);
+        }
+
+        for segment in data.chunks(MAX_SEGMENT) {
+            let seq = self.seqs[&key];
+            self.write_segment(
+                annotation,
+                seq,
+                ack.unwrap_or(0),
+                TCP_ACK | TCP_PSH,
+                segment,
+            )?;
+            self.seqs
+                .insert(key, seq.wrapping_add(segment.len() as u32));
+        }
+
+        let file = self.file.as_mut().expect("file to be open");
+        file.flush().context("Flushing recording")?;
+        if self.written >= self.max_bytes {
+            self.file = None;
+            self.rotation += 1;
+        }
+        Ok(())
+    }
+
+    /// Start the next file, with the headers pcapng needs before any packets.
+    fn open(&mut self) -> anyhow::Result<()> {
+        let path = rotated_path(&self.path, self.rotation);
+        info!("Recording traffic to {:?}", path);
+        let file = File::create(&path).with_context(|| format!("Creating {:?}", path))?;
+        self.file = Some(BufWriter::new(file));
+        self.written = 0;
+        self.seqs.clear();
+
+        let mut section = vec![];
+        section.extend_from_slice(&BYTE_ORDER_MAGIC.to_le_bytes());
+        section.extend_from_slice(&1u16.to_le_bytes()); // Major version.
+        section.extend_from_slice(&0u16.to_le_bytes()); // Minor version.
+        section.extend_from_slice(&(-1i64).to_le_bytes()); // Section length isn't known.
+        self.write_block(SECTION_HEADER_BLOCK, &section)?;
+
+        let mut interface = vec![];
+        interface.extend_from_slice(&LINKTYPE_RAW.to_le_bytes());
+        interface.extend_from_slice(&0u16.to_le_bytes()); // Reserved.
+        interface.extend_from_slice(&0u32.to_le_bytes()); // No snapshot length limit.
+        self.write_block(INTERFACE_DESCRIPTION_BLOCK, &interface)
+    }
+
+    fn write_segment(
+        &mut self,
+        annotation: &Annotation,
+        seq: u32,
+        ack: u32,
+        flags: u8,
+        data: &[u8],
+    ) -> anyhow::Result<()> {
+        let packet = ip_packet(
+            &annotation.source,
+            &annotation.destination,
+            seq,
+            ack,
+            flags,
+            data,
+        );
+
+        // Timestamps are in microseconds, the default resolution of an interface.
+        let timestamp = annotation.timestamp.timestamp() as u64 * 1_000_000
+            + annotation.timestamp.timestamp_subsec_micros() as u64;
+
+        let mut block = vec![];
+        block.extend_from_slice(&0u32.to_le_bytes()); // Interface id.
+        block.extend_from_slice(&((timestamp >> 32) as u32).to_le_bytes());
+        block.extend_from_slice(&(timestamp as u32).to_le_bytes());
+        block.extend_from_slice(&(packet.len() as u32).to_le_bytes()); // Captured length.
+        block.extend_from_slice(&(packet.len() as u32).to_le_bytes()); // Original length.
+        block.extend_from_slice(&packet);
+        block.resize(block.len() + padding(packet.len()), 0);
+        self.write_block(ENHANCED_PACKET_BLOCK, &block)
+    }
+
+    /// A block is its type and total length, the body, then the total length again.
+    fn write_block(&mut self, block_type: u32, body: &[u8]) -> anyhow::Result<()> {
+        let len = (body.len() + 12) as u32;
+        let file = self.file.as_mut().expect("file to be open");
+        file.write_all(&block_type.to_le_bytes())?;
+        file.write_all(&len.to_le_bytes())?;
+        file.write_all(body)?;
+        file.write_all(&len.to_le_bytes())
+            .context("Writing pcapng block")?;
+        self.written += len as u64;
+        Ok(())
+    }
+}
+
+/// The path of a rotated file, e.g. `traffic.pcapng`, then `traffic.1.pcapng`, `traffic.2.pcapng`.
+fn rotated_path(path: &Path, rotation: usize) -> PathBuf {
+    if rotation == 0 {
+        return path.to_owned();
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{}.{}.{}", stem, rotation, ext.to_string_lossy()),
+        None => format!("{}.{}", stem, rotation),
+    };
+    path.with_file_name(name)
+}
+
+/// Blocks are padded to 32 bits.
+fn padding(len: usize) -> usize {
+    (4 - len % 4) % 4
+}
+
+/// Make up an IP packet carrying a TCP segment.
+fn ip_packet(
+    source: &SocketAddr,
+    destination: &SocketAddr,
+    seq: u32,
+    ack: u32,
+    flags: u8,
+    data: &[u8],
+) -> Vec<u8> {
+    let (source_ip, destination_ip) = same_family(source.ip(), destination.ip());
+    let tcp_len = TCP_HEADER_LEN + data.len();
+
+    let mut tcp = Vec::with_capacity(tcp_len);
+    tcp.extend_from_slice(&source.port().to_be_bytes());
+    tcp.extend_from_slice(&destination.port().to_be_bytes());
+    tcp.extend_from_slice(&seq.to_be_bytes());
+    tcp.extend_from_slice(&ack.to_be_bytes());
+    tcp.push((TCP_HEADER_LEN as u8 / 4) << 4);
+    tcp.push(flags);
+    tcp.extend_from_slice(&u16::MAX.to_be_bytes()); // Window.
+    tcp.extend_from_slice(&[0, 0]); // Checksum, filled in below.
+    tcp.extend_from_slice(&[0, 0]); // Urgent pointer.
+    tcp.extend_from_slice(data);
+
+    let mut packet = vec![];
+    let mut pseudo_header = vec![];
+    match (source_ip, destination_ip) {
+        (IpAddr::V4(s), IpAddr::V4(d)) => {
+            packet.extend_from_slice(&[0x45, 0]);
+            packet.extend_from_slice(&((IPV4_HEADER_LEN + tcp_len) as u16).to_be_bytes());
+            packet.extend_from_slice(&[0, 0, 0x40, 0]); // Id, don't fragment.
+            packet.extend_from_slice(&[64, PROTOCOL_TCP, 0, 0]); // TTL, protocol, checksum.
+            packet.extend_from_slice(&s.octets());
+            packet.extend_from_slice(&d.octets());
+            let checksum = checksum(&packet);
+            packet[10..12].copy_from_slice(&checksum.to_be_bytes());
+
+            pseudo_header.extend_from_slice(&s.octets());
+            pseudo_header.extend_from_slice(&d.octets());
+            pseudo_header.extend_from_slice(&[0, PROTOCOL_TCP]);
+            pseudo_header.extend_from_slice(&(tcp_len as u16).to_be_bytes());
+        }
+        (IpAddr::V6(s), IpAddr::V6(d)) => {
+            packet.extend_from_slice(&[0x60, 0, 0, 0]);
+            packet.extend_from_slice(&(tcp_len as u16).to_be_bytes());
+            packet.extend_from_slice(&[PROTOCOL_TCP, 64]); // Next header, hop limit.
+            packet.extend_from_slice(&s.octets());
+            packet.extend_from_slice(&d.octets());
+            debug_assert_eq!(packet.len(), IPV6_HEADER_LEN);
+
+            pseudo_header.extend_from_slice(&s.octets());
+            pseudo_header.extend_from_slice(&d.octets());
+            pseudo_header.extend_from_slice(&(tcp_len as u32).to_be_bytes());
+            pseudo_header.extend_from_slice(&[0, 0, 0, PROTOCOL_TCP]);
+        }
+        _ => unreachable!("addresses are the same family"),
+    }
+
+    pseudo_header.extend_from_slice(&tcp);
+    let checksum = checksum(&pseudo_header);
+    tcp[16..18].copy_from_slice(&checksum.to_be_bytes());
+    packet.extend(tcp);
+    packet
+}
+
+/// Both ends of a connection as the same IP version, mapping IPv4 into IPv6 if they differ.
+fn same_family(a: IpAddr, b: IpAddr) -> (IpAddr, IpAddr) {
+    match (a, b) {
+        (IpAddr::V4(a), IpAddr::V6(b)) => (IpAddr::V6(a.to_ipv6_mapped()), IpAddr::V6(b)),
+        (IpAddr::V6(a), IpAddr::V4(b)) => (IpAddr::V6(a), IpAddr::V6(b.to_ipv6_mapped())),
+        pair => pair,
+    }
+}
+
+/// The internet checksum: the ones' complement of the ones' complement sum of 16 bit words.
+fn checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|w| u16::from_be_bytes([w[0], *w.get(1).unwrap_or(&0)]) as u32)
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::Direction;
+    use chrono::Utc;
+    use std::str::FromStr;
+
+    fn annotation(source: &str, destination: &str) -> Annotation {
+        Annotation {
+            packet: 0,
+            timestamp: Utc::now(),
+            direction: Direction::Send,
+            source: SocketAddr::from_str(source).unwrap(),
+            destination: SocketAddr::from_str(destination).unwrap(),
+            size: 0,
+        }
+    }
+
+    /// The type and body of each block in a pcapng file.
+    fn blocks(data: &[u8]) -> Vec<(u32, &[u8])> {
+        let mut blocks = vec![];
+        let mut rest = data;
+        while !rest.is_empty() {
+            let block_type = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]);
+            let len = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+            assert_eq!(&rest[len - 4..len], &rest[4..8]);
+            blocks.push((block_type, &rest[8..len - 4]));
+            rest = &rest[len..];
+        }
+        blocks
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("feeless-{}-{}.pcapng", name, std::process::id()))
+    }
+
+    #[test]
+    fn record() {
+        let path = temp_path("record");
+        let mut recorder = Recorder::new(path.clone(), u64::MAX);
+        let send = annotation("10.0.0.1:50000", "10.0.0.2:7075");
+        let recv = annotation("10.0.0.2:7075", "10.0.0.1:50000");
+        recorder.record(&send, b"hello").unwrap();
+        recorder.record(&recv, b"hi").unwrap();
+        recorder.record(&send, b"again").unwrap();
+        drop(recorder);
+
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let blocks = blocks(&data);
+        let types: Vec<u32> = blocks.iter().map(|b| b.0).collect();
+        assert_eq!(
+            types,
+            vec![
+                SECTION_HEADER_BLOCK,
+                INTERFACE_DESCRIPTION_BLOCK,
+                ENHANCED_PACKET_BLOCK,
+                ENHANCED_PACKET_BLOCK,
+                ENHANCED_PACKET_BLOCK,
+                ENHANCED_PACKET_BLOCK,
+                ENHANCED_PACKET_BLOCK,
+            ]
+        );
+        assert_eq!(&blocks[1].1[0..2], &LINKTYPE_RAW.to_le_bytes());
+
+        // The second segment sent follows on from the first.
+        let packet = |i: usize| {
+            let body = blocks[i].1;
+            let len = u32::from_le_bytes([body[12], body[13], body[14], body[15]]) as usize;
+            &body[20..20 + len]
+        };
+        let last = packet(6);
+        assert_eq!(checksum(&last[..IPV4_HEADER_LEN]), 0);
+        let tcp = &last[IPV4_HEADER_LEN..];
+        assert_eq!(u32::from_be_bytes([tcp[4], tcp[5], tcp[6], tcp[7]]), 6);
+        assert_eq!(u32::from_be_bytes([tcp[8], tcp[9], tcp[10], tcp[11]]), 3);
+        assert_eq!(&tcp[TCP_HEADER_LEN..], b"again");
+
+        // Replies start with a SYN ACK.
+        assert_eq!(packet(4)[IPV4_HEADER_LEN + 13], TCP_SYN | TCP_ACK);
+    }
+
+    #[test]
+    fn ipv6_checksum() {
+        let packet = ip_packet(
+            &SocketAddr::from_str("[::1]:7075").unwrap(),
+            &SocketAddr::from_str("10.0.0.1:50000").unwrap(),
+            1,
+            0,
+            TCP_ACK,
+            b"odd",
+        );
+        assert_eq!(packet.len(), IPV6_HEADER_LEN + TCP_HEADER_LEN + 3);
+
+        let mut pseudo_header = packet[8..IPV6_HEADER_LEN].to_vec();
+        pseudo_header.extend_from_slice(&((TCP_HEADER_LEN + 3) as u32).to_be_bytes());
+        pseudo_header.extend_from_slice(&[0, 0, 0, PROTOCOL_TCP]);
+        pseudo_header.extend_from_slice(&packet[IPV6_HEADER_LEN..]);
+        assert_eq!(checksum(&pseudo_header), 0);
+    }
+
+    #[test]
+    fn rotate() {
+        let path = temp_path("rotate");
+        let mut recorder = Recorder::new(path.clone(), 100);
+        let send = annotation("10.0.0.1:50000", "10.0.0.2:7075");
+        recorder.record(&send, &[0u8; 200]).unwrap();
+        recorder.record(&send, &[0u8; 200]).unwrap();
+        drop(recorder);
+
+        let rotated = rotated_path(&path, 1);
+        for path in &[&path, &rotated] {
+            let data = std::fs::read(path).unwrap();
+            std::fs::remove_file(path).unwrap();
+            let blocks = blocks(&data);
+            assert_eq!(blocks.len(), 4, "{:?}", path);
+        }
+        assert!(rotated.to_string_lossy().ends_with(".1.pcapng"));
+    }
+}
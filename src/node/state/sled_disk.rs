@@ -3,10 +3,12 @@ use crate::network::Network;
 use crate::node::cookie::Cookie;
 use crate::node::state::State;
 use crate::Public;
+use anyhow::Context;
 use async_trait::async_trait;
 use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::net::SocketAddr;
+use std::path::Path;
 
 /// Sled is an on disk key value pair.
 #[derive(Clone, Debug)]
@@ -15,54 +17,85 @@ pub struct SledDiskState {
     db: sled::Db,
     cookies: sled::Tree,
     peers: sled::Tree,
+
+    /// Block hash to the block as JSON.
+    blocks: sled::Tree,
+
+    /// Account to the hash of its latest block.
+    latest_block_hash: sled::Tree,
+
+    /// Block hash followed by representative, for each vote.
+    votes: sled::Tree,
 }
 
 impl SledDiskState {
     pub fn new(network: Network) -> Self {
         let path = format!("{:?}.db", network).to_ascii_lowercase();
-        let db: sled::Db =
-            sled::open(&path).unwrap_or_else(|_| panic!("Could not open database: {}", &path));
-        let cookies = db.open_tree("cookies").unwrap();
-        let peers = db.open_tree("peers").unwrap();
-        Self {
+        Self::open(network, Path::new(&path))
+            .unwrap_or_else(|_| panic!("Could not open database: {}", &path))
+    }
+
+    pub fn open(network: Network, path: &Path) -> anyhow::Result<Self> {
+        let db = sled::open(path).with_context(|| format!("Opening database {:?}", path))?;
+        Self::from_db(network, db)
+    }
+
+    fn from_db(network: Network, db: sled::Db) -> anyhow::Result<Self> {
+        Ok(Self {
             network,
+            cookies: db.open_tree("cookies")?,
+            peers: db.open_tree("peers")?,
+            blocks: db.open_tree("blocks")?,
+            latest_block_hash: db.open_tree("latest_block_hash")?,
+            votes: db.open_tree("votes")?,
             db,
-            cookies,
-            peers,
-        }
+        })
     }
 }
 
 #[async_trait]
 impl State for SledDiskState {
-    async fn add_block(&mut self, _block_holder: &Block) -> anyhow::Result<()> {
-        unimplemented!()
+    async fn add_block(&mut self, block: &Block) -> anyhow::Result<()> {
+        let hash = block.hash().context("Add block")?;
+        self.blocks
+            .insert(hash.as_bytes(), serde_json::to_vec(block)?)?;
+        self.latest_block_hash
+            .insert(block.account().as_bytes(), hash.as_bytes())?;
+        Ok(())
     }
 
-    async fn get_block_by_hash(&self, _hash: &BlockHash) -> anyhow::Result<Option<Block>> {
-        unimplemented!()
+    async fn get_block_by_hash(&self, hash: &BlockHash) -> anyhow::Result<Option<Block>> {
+        Ok(match self.blocks.get(hash.as_bytes())? {
+            None => None,
+            Some(json) => Some(serde_json::from_slice(&json).context("Decoding stored block")?),
+        })
     }
 
     async fn get_latest_block_hash_for_account(
         &self,
-        _account: &Public,
+        account: &Public,
     ) -> anyhow::Result<Option<BlockHash>> {
-        unimplemented!()
+        Ok(match self.latest_block_hash.get(account.as_bytes())? {
+            None => None,
+            Some(hash) => Some(BlockHash::try_from(hash.as_ref())?),
+        })
     }
 
     async fn account_for_block_hash(
         &mut self,
-        _block_hash: &BlockHash,
+        block_hash: &BlockHash,
     ) -> Result<Option<Public>, anyhow::Error> {
-        unimplemented!()
+        Ok(self
+            .get_block_by_hash(block_hash)
+            .await?
+            .map(|b| b.account().to_owned()))
     }
 
-    async fn add_vote(
-        &mut self,
-        _hash: &BlockHash,
-        _representative: &Public,
-    ) -> anyhow::Result<()> {
-        unimplemented!()
+    async fn add_vote(&mut self, hash: &BlockHash, representative: &Public) -> anyhow::Result<()> {
+        let mut key = hash.as_bytes().to_vec();
+        key.extend_from_slice(representative.as_bytes());
+        self.votes.insert(key, &[])?;
+        Ok(())
     }
 
     async fn set_cookie(&mut self, socket_addr: SocketAddr, cookie: Cookie) -> anyhow::Result<()> {
@@ -90,3 +123,33 @@ impl State for SledDiskState {
         unimplemented!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn blocks() {
+        let network = Network::Live;
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let mut state = SledDiskState::from_db(network, db).unwrap();
+        let genesis = network.genesis_block();
+        let hash = genesis.hash().unwrap();
+
+        assert!(state.get_block_by_hash(hash).await.unwrap().is_none());
+        state.add_block(&genesis).await.unwrap();
+        // The link's type isn't stored, so compare the rest.
+        let stored = state.get_block_by_hash(hash).await.unwrap().unwrap();
+        assert_eq!(stored.hash().unwrap(), hash);
+        assert_eq!(stored.balance(), genesis.balance());
+        assert_eq!(stored.signature(), genesis.signature());
+        assert_eq!(
+            &state
+                .get_latest_block_hash_for_account(genesis.account())
+                .await
+                .unwrap()
+                .unwrap(),
+            hash
+        );
+    }
+}
@@ -1,9 +1,10 @@
 use crate::encoding::len_err_msg;
 use anyhow::Context;
+use serde::Serialize;
 use std::convert::TryFrom;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-#[derive(Eq, PartialEq, Debug, Clone)]
+#[derive(Eq, PartialEq, Debug, Clone, Serialize)]
 pub struct Timestamp(u64);
 
 impl Timestamp {
@@ -21,7 +22,7 @@ impl Timestamp {
         Self(s)
     }
 
-    fn to_u64(&self) -> u64 {
+    pub(crate) fn to_u64(&self) -> u64 {
         self.0
     }
 
@@ -16,11 +16,21 @@ pub(crate) struct PathsOpts {
 }
 
 impl PathsOpts {
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
     pub fn wallet_path(&self) -> anyhow::Result<PathBuf> {
         let p = Paths::new_maybe_custom(self.network.clone(), self.data_dir.clone());
         p.ensure_data_path()?;
         Ok(p.wallet_path())
     }
+
+    pub fn data_path(&self, path: &Path) -> anyhow::Result<PathBuf> {
+        let p = Paths::new_maybe_custom(self.network, self.data_dir.clone());
+        p.ensure_data_path()?;
+        Ok(p.data_path(path))
+    }
 }
 
 /// Contains the base path to wallets, databases, etc.
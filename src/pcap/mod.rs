@@ -0,0 +1,516 @@
+mod record;
+mod replay;
+mod stream;
+
+use crate::network::Network;
+use crate::network::DEFAULT_PORT;
+use crate::node::ledger_event::LedgerEvent;
+use crate::node::peer::LoggedMessage;
+use crate::node::{
+    Annotation, ArcState, Direction, Header, MemoryState, Packet, Peer, SledDiskState,
+};
+use crate::pcap::record::{Record, RecordWriter, Summary};
+use crate::pcap::replay::ReplayReport;
+use crate::pcap::stream::{Chunk, Reassembler};
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Utc};
+use etherparse::{InternetSlice, SlicedPacket};
+use etherparse::{TcpHeaderSlice, TransportSlice};
+use pcarp::{Capture, LinkType};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Write;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, trace, warn};
+
+pub(crate) use record::OutputFormat;
+
+/// Subject is the focused peer that we act as "us", when showing if we're sending or
+/// receiving.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Subject {
+    AutoFirstSource,
+    Specified(IpAddr),
+}
+
+/// Link types of raw IPv4 and IPv6 captures, which pcarp doesn't name.
+const LINKTYPE_IPV4: u16 = 228;
+const LINKTYPE_IPV6: u16 = 229;
+
+/// EtherType of IPv4 and IPv6, as found in a Linux cooked capture header.
+const ETHER_TYPE_IPV4: u16 = 0x0800;
+const ETHER_TYPE_IPV6: u16 = 0x86dd;
+
+/// Length of a Linux cooked capture (SLL) header, which `any` interface captures use instead
+/// of Ethernet.
+const SLL_LEN: usize = 16;
+
+pub(crate) struct PcapDump {
+    /// Puts each direction of a connection back in order, keyed by stream id.
+    streams: HashMap<String, Reassembler>,
+
+    /// A peer for each stream, so messages from each direction are kept apart. Peers are driven
+    /// in capture order so a replay into the state is deterministic.
+    peers: HashMap<String, Peer>,
+
+    pub start_at: Option<usize>,
+    pub end_at: Option<usize>,
+    pub filter_addr: Option<IpAddr>,
+
+    /// Also look for Nano connections on ports other than the default, by checking if the first
+    /// data seen on a connection is a valid header.
+    pub any_port: bool,
+
+    /// Connections on other ports that have been seen to carry Nano messages.
+    learned: HashSet<String>,
+
+    /// How to write a record of each decoded message.
+    pub format: OutputFormat,
+
+    /// Where to write records, instead of stdout.
+    pub output: Option<PathBuf>,
+
+    /// Where to write the summary of each peer as JSON, instead of logging it.
+    pub summary: Option<PathBuf>,
+
+    /// Given to each peer to send back decoded messages.
+    message_tx: Option<UnboundedSender<LoggedMessage>>,
+
+    /// Process blocks and votes into the state, and report on what happened to them.
+    pub replay: bool,
+
+    /// Replay into a sled database at this path, instead of into memory.
+    pub sled: Option<PathBuf>,
+
+    /// Where to write the replay report as JSON, instead of logging it.
+    pub report: Option<PathBuf>,
+
+    /// Given to each peer to send back what happened to each block and vote.
+    ledger_tx: Option<UnboundedSender<LedgerEvent>>,
+
+    subject: Subject,
+    found_subject: Option<IpAddr>,
+
+    packet_idx: usize,
+    stream_id: String,
+}
+
+impl PcapDump {
+    pub fn new(subject: Subject) -> Self {
+        let found_subject = match subject {
+            Subject::Specified(s) => Some(s),
+            _ => None,
+        };
+
+        PcapDump {
+            streams: HashMap::new(),
+            subject,
+            found_subject,
+            packet_idx: 0,
+            stream_id: "".to_string(),
+            start_at: None,
+            end_at: None,
+            filter_addr: None,
+            any_port: false,
+            learned: HashSet::new(),
+            format: OutputFormat::Log,
+            output: None,
+            summary: None,
+            message_tx: None,
+            replay: false,
+            sled: None,
+            report: None,
+            ledger_tx: None,
+            peers: Default::default(),
+        }
+    }
+
+    pub async fn dump(&mut self, path: &str) -> anyhow::Result<()> {
+        let network = Network::Live;
+        let state: ArcState = match &self.sled {
+            Some(path) => Arc::new(Mutex::new(
+                SledDiskState::open(network, path)
+                    .with_context(|| format!("Opening sled database {:?}", path))?,
+            )),
+            None => Arc::new(Mutex::new(MemoryState::new(network))),
+        };
+
+        info!("Loading dump: {}", path);
+
+        let file = File::open(path).with_context(|| format!("Opening file {}", path))?;
+
+        let output: Box<dyn Write + Send> = match &self.output {
+            Some(path) => {
+                Box::new(File::create(path).with_context(|| format!("Creating output {:?}", path))?)
+            }
+            None => Box::new(std::io::stdout()),
+        };
+        let (message_tx, message_rx) = mpsc::unbounded_channel();
+        self.message_tx = Some(message_tx);
+        let collector = Self::collect(RecordWriter::new(self.format, output), message_rx);
+        let (ledger_tx, mut ledger_rx) = mpsc::unbounded_channel();
+        if self.replay {
+            self.ledger_tx = Some(ledger_tx);
+        }
+
+        let mut has_started = false;
+        let mut reader =
+            Capture::new(file).with_context(|| format!("Reading capture file {:?}", &path))?;
+        self.packet_idx = 0;
+        'next_packet: loop {
+            self.packet_idx += 1; // 1 based packet numbering because wireshark uses it.
+
+            let packet = reader
+                .next()
+                .transpose()
+                .with_context(|| format!("Reading next packet: {}", self.packet_idx))?;
+            let packet = match packet {
+                Some(packet) => packet,
+                None => {
+                    debug!("No more packets in pcap.");
+                    break;
+                }
+            };
+            let timestamp: DateTime<Utc> = packet.timestamp.unwrap().into();
+            // pcap files without interface information are always Ethernet.
+            let link_type = packet
+                .interface
+                .map(|i| i.link_type)
+                .unwrap_or(LinkType::ETHERNET);
+            let packet = match Self::slice(link_type, packet.data).with_context(|| {
+                format!(
+                    "Parsing {:?} packet data for packet {}",
+                    link_type, self.packet_idx
+                )
+            }) {
+                Ok(p) => p,
+                Err(err) => {
+                    warn!("Packet was no parsed correctly because: {:?}", err);
+                    continue 'next_packet;
+                }
+            };
+            let (source, destination, tcp, data) = match Self::process_packet(&packet) {
+                Some(r) => r,
+                None => continue,
+            };
+
+            // Work out direction based on subject
+            if self.subject == Subject::AutoFirstSource && self.found_subject.is_none() {
+                self.found_subject = Some(source);
+            }
+            let subject = self.found_subject.expect("a subject to be set by now");
+            let direction = if destination == subject {
+                Direction::Recv
+            } else if source == subject {
+                Direction::Send
+            } else {
+                warn!(
+                    "Unknown direction for {} and {} -> {}",
+                    subject, source, destination
+                );
+                Direction::Recv
+            };
+
+            // Start and end packet happens after the subject code, so we can still use the
+            // first source from the first packet.
+            if !has_started {
+                match self.start_at {
+                    Some(start_at) => {
+                        if start_at <= self.packet_idx {
+                            has_started = true;
+                        } else {
+                            continue;
+                        }
+                    }
+                    None => has_started = true,
+                }
+            }
+            if let Some(end_at) = self.end_at {
+                if self.packet_idx > end_at {
+                    break;
+                }
+            }
+
+            if let Some(addr) = self.filter_addr {
+                if source != addr && destination != addr {
+                    continue;
+                }
+            }
+
+            let source = SocketAddr::new(source, tcp.source_port());
+            let destination = SocketAddr::new(destination, tcp.destination_port());
+
+            // Only look at port 7075, unless a connection on another port looks like Nano.
+            if source.port() != DEFAULT_PORT && destination.port() != DEFAULT_PORT {
+                if !self.any_port {
+                    continue;
+                }
+                let connection_id = connection_id(&source, &destination);
+                if !self.learned.contains(&connection_id) {
+                    if Header::find(&network, data) != Some(0) {
+                        continue;
+                    }
+                    info!("Found Nano messages on {}", &connection_id);
+                    self.learned.insert(connection_id);
+                }
+            }
+
+            self.stream_id = format!("{}->{}", source, destination);
+
+            let chunks = self
+                .streams
+                .entry(self.stream_id.clone())
+                .or_default()
+                .segment(tcp.sequence_number(), tcp.syn(), tcp.rst(), data);
+            if chunks.is_empty() {
+                continue;
+            }
+
+            let annotation = Annotation {
+                packet: self.packet_idx,
+                timestamp,
+                direction,
+                source,
+                destination,
+                size: data.len(),
+            };
+
+            let stream_id = self.stream_id.clone();
+            let peer = self.peer(&stream_id, &state, network, destination).await?;
+            for chunk in chunks {
+                Self::send(
+                    peer,
+                    &stream_id,
+                    Self::chunk_to_packet(&stream_id, chunk, Some(&annotation)),
+                )
+                .await;
+            }
+
+            // The response to a bootstrap request has no headers, so tell the peer for the
+            // other direction to expect it.
+            if let Some(response) = peer.take_requested_stream() {
+                let reverse_id = format!("{}->{}", destination, source);
+                self.peer(&reverse_id, &state, network, source)
+                    .await?
+                    .expect_response_stream(response);
+            }
+        }
+
+        debug!("Flushing streams.");
+        self.finish().await;
+
+        // The collector stops once every peer has been dropped.
+        debug!("Waiting for cleanup, then exiting.");
+        self.peers.clear();
+        self.message_tx = None;
+        self.ledger_tx = None;
+        let summary = collector.await??;
+
+        match &self.summary {
+            Some(path) => std::fs::write(path, serde_json::to_string_pretty(&summary)?)
+                .with_context(|| format!("Writing summary {:?}", path))?,
+            None => summary.log(),
+        }
+
+        if self.replay {
+            let mut events = vec![];
+            while let Some(event) = ledger_rx.recv().await {
+                events.push(event);
+            }
+            let report = ReplayReport::new(&events, &state).await?;
+            match &self.report {
+                Some(path) => std::fs::write(path, serde_json::to_string_pretty(&report)?)
+                    .with_context(|| format!("Writing replay report {:?}", path))?,
+                None => report.log(),
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a record of each decoded message, and total them up for the summary.
+    fn collect(
+        mut writer: RecordWriter,
+        mut rx: UnboundedReceiver<LoggedMessage>,
+    ) -> JoinHandle<anyhow::Result<Summary>> {
+        tokio::spawn(async move {
+            let mut summary = Summary::default();
+            while let Some(message) = rx.recv().await {
+                summary.add(&message);
+                if let Some(record) = Record::new(&message) {
+                    writer.write(&record)?;
+                }
+            }
+            writer.flush()?;
+            Ok(summary)
+        })
+    }
+
+    /// Send whatever is left in each stream, skipping over missing segments.
+    async fn finish(&mut self) {
+        let mut stream_ids: Vec<_> = self.streams.keys().cloned().collect();
+        stream_ids.sort();
+        for stream_id in stream_ids {
+            let peer = match self.peers.get_mut(&stream_id) {
+                Some(peer) => peer,
+                None => continue,
+            };
+            for chunk in self.streams.get_mut(&stream_id).unwrap().finish() {
+                Self::send(
+                    peer,
+                    &stream_id,
+                    Self::chunk_to_packet(&stream_id, chunk, None),
+                )
+                .await;
+            }
+        }
+    }
+
+    /// Have a peer handle a packet, carrying on with the rest of the capture if it fails.
+    async fn send(peer: &mut Peer, stream_id: &str, packet: Packet) {
+        if let Err(err) = peer.handle_packet(packet).await {
+            error!("Error on pcap peer {}: {:?}", stream_id, err);
+        }
+    }
+
+    fn chunk_to_packet(stream_id: &str, chunk: Chunk, annotation: Option<&Annotation>) -> Packet {
+        match chunk {
+            Chunk::Data(data) => match annotation {
+                Some(a) => Packet::new_with_annotation(data, a.to_owned()),
+                None => Packet::new(data),
+            },
+            Chunk::Gap(missing) => {
+                warn!("{} is missing {} bytes", stream_id, missing);
+                let mut packet = Packet::new(vec![]);
+                packet.gap();
+                packet
+            }
+        }
+    }
+
+    /// The peer handling a stream, created on first use.
+    async fn peer(
+        &mut self,
+        stream_id: &str,
+        state: &ArcState,
+        network: Network,
+        peer_addr: SocketAddr,
+    ) -> anyhow::Result<&mut Peer> {
+        if !self.peers.contains_key(stream_id) {
+            let (mut peer, _tx, mut rx) =
+                Peer::new_with_channels(network, state.clone(), peer_addr);
+
+            // Discard all responses from the peer since we are just processing packets.
+            tokio::spawn(async move {
+                while rx.recv().await.is_some() {}
+                trace!("Receiving channel has closed.");
+            });
+
+            peer.validate_handshakes = false;
+            // Captures have missing and garbled data, so skip over it rather than give up.
+            peer.resync_on_error = true;
+            peer.message_log = self.message_tx.clone();
+            peer.ledger_log = self.ledger_tx.clone();
+            peer.init().await.context("Initialising pcap peer")?;
+            self.peers.insert(stream_id.to_owned(), peer);
+        }
+        Ok(self.peers.get_mut(stream_id).unwrap())
+    }
+
+    /// Parse the layers of a packet captured on an interface with this link type.
+    fn slice(link_type: LinkType, data: &[u8]) -> anyhow::Result<SlicedPacket<'_>> {
+        let packet = match link_type {
+            LinkType::ETHERNET => SlicedPacket::from_ethernet(data)?,
+            LinkType::LINUX_SLL => SlicedPacket::from_ip(strip_sll(data)?)?,
+            LinkType::RAW | LinkType::Unknown(LINKTYPE_IPV4) | LinkType::Unknown(LINKTYPE_IPV6) => {
+                SlicedPacket::from_ip(data)?
+            }
+            _ => return Err(anyhow!("Unsupported link type")),
+        };
+        Ok(packet)
+    }
+
+    /// The source and destination addresses, TCP header and TCP data of a packet.
+    fn process_packet<'p>(
+        packet: &'p SlicedPacket,
+    ) -> Option<(IpAddr, IpAddr, &'p TcpHeaderSlice<'p>, &'p [u8])> {
+        let tcp = if let Some(TransportSlice::Tcp(tcp)) = &packet.transport {
+            tcp
+        } else {
+            return None;
+        };
+
+        match &packet.ip {
+            Some(InternetSlice::Ipv4(ip)) => {
+                // Ethernet pads short frames, so use the IP length to find the end of the data.
+                let data_len = (ip.payload_len() as usize)
+                    .saturating_sub(tcp.slice().len())
+                    .min(packet.payload.len());
+                Some((
+                    IpAddr::V4(ip.source_addr()),
+                    IpAddr::V4(ip.destination_addr()),
+                    tcp,
+                    &packet.payload[..data_len],
+                ))
+            }
+            // IPv6 with TCP is never short enough to be padded.
+            Some(InternetSlice::Ipv6(ip, _)) => Some((
+                IpAddr::V6(ip.source_addr()),
+                IpAddr::V6(ip.destination_addr()),
+                tcp,
+                packet.payload,
+            )),
+            None => None,
+        }
+    }
+}
+
+/// The same for both directions of a connection.
+fn connection_id(a: &SocketAddr, b: &SocketAddr) -> String {
+    if a < b {
+        format!("{}<->{}", a, b)
+    } else {
+        format!("{}<->{}", b, a)
+    }
+}
+
+/// The IP packet in a Linux cooked capture frame.
+fn strip_sll(data: &[u8]) -> anyhow::Result<&[u8]> {
+    if data.len() < SLL_LEN {
+        return Err(anyhow!("Linux cooked capture header is too short"));
+    }
+    let protocol = u16::from_be_bytes([data[14], data[15]]);
+    match protocol {
+        ETHER_TYPE_IPV4 | ETHER_TYPE_IPV6 => Ok(&data[SLL_LEN..]),
+        _ => Err(anyhow!("Not an IP packet: protocol {:#06x}", protocol)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn connection_id_is_the_same_both_ways() {
+        let a = SocketAddr::from_str("[::1]:7075").unwrap();
+        let b = SocketAddr::from_str("10.0.0.1:54321").unwrap();
+        assert_eq!(connection_id(&a, &b), connection_id(&b, &a));
+    }
+
+    #[test]
+    fn sll() {
+        let mut data = vec![0u8; SLL_LEN];
+        data[14..16].copy_from_slice(&ETHER_TYPE_IPV6.to_be_bytes());
+        data.extend_from_slice(&[0x60, 0, 0, 0]);
+        assert_eq!(strip_sll(&data).unwrap(), &[0x60, 0, 0, 0]);
+
+        data[14..16].copy_from_slice(&0x0806u16.to_be_bytes());
+        assert!(strip_sll(&data).is_err());
+        assert!(strip_sll(&data[..10]).is_err());
+    }
+}
@@ -0,0 +1,226 @@
+//! Structured output of the messages decoded from a capture, and a summary of each peer.
+use crate::node::peer::LoggedMessage;
+use crate::node::{Direction, MessageType};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use strum_macros::EnumString;
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub(crate) enum OutputFormat {
+    /// Only log decoded messages.
+    Log,
+
+    /// One JSON object per line for each message.
+    Json,
+
+    /// One CSV row for each message, with the decoded fields as JSON.
+    Csv,
+}
+
+/// One decoded message.
+#[derive(Debug, Serialize)]
+pub(crate) struct Record {
+    pub packet: usize,
+    pub timestamp: DateTime<Utc>,
+    pub direction: Direction,
+    pub stream: String,
+    pub message_type: String,
+    pub fields: serde_json::Value,
+}
+
+impl Record {
+    const CSV_HEADER: &'static str = "packet,timestamp,direction,stream,message_type,fields";
+
+    /// Messages without an annotation didn't come from a capture, so can't be recorded.
+    pub fn new(message: &LoggedMessage) -> Option<Self> {
+        let annotation = message.annotation.as_ref()?;
+        Some(Self {
+            packet: annotation.packet,
+            timestamp: annotation.timestamp,
+            direction: annotation.direction,
+            stream: annotation.stream(),
+            message_type: format!("{:?}", message.message_type),
+            fields: message.payload.to_owned(),
+        })
+    }
+
+    fn to_csv(&self) -> anyhow::Result<String> {
+        let direction = match self.direction {
+            Direction::Send => "send",
+            Direction::Recv => "recv",
+        };
+        Ok([
+            self.packet.to_string(),
+            self.timestamp.format("%+").to_string(),
+            direction.to_owned(),
+            csv_escape(&self.stream),
+            csv_escape(&self.message_type),
+            csv_escape(&serde_json::to_string(&self.fields)?),
+        ]
+        .join(","))
+    }
+}
+
+/// Writes records in the chosen format.
+pub(crate) struct RecordWriter {
+    format: OutputFormat,
+    output: Box<dyn Write + Send>,
+    started: bool,
+}
+
+impl RecordWriter {
+    pub fn new(format: OutputFormat, output: Box<dyn Write + Send>) -> Self {
+        Self {
+            format,
+            output,
+            started: false,
+        }
+    }
+
+    pub fn write(&mut self, record: &Record) -> anyhow::Result<()> {
+        match self.format {
+            OutputFormat::Log => {}
+            OutputFormat::Json => {
+                serde_json::to_writer(&mut self.output, record)?;
+                self.output.write_all(b"\n")?;
+            }
+            OutputFormat::Csv => {
+                if !self.started {
+                    writeln!(self.output, "{}", Record::CSV_HEADER)?;
+                    self.started = true;
+                }
+                writeln!(self.output, "{}", record.to_csv()?)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        Ok(self.output.flush()?)
+    }
+}
+
+/// Totals for each peer over the whole capture, keyed by the peer's address.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct Summary {
+    peers: BTreeMap<String, PeerSummary>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct PeerSummary {
+    /// Count of each message type, in both directions.
+    messages: BTreeMap<String, usize>,
+    bytes_sent: usize,
+    bytes_received: usize,
+
+    /// Votes seen from each representative.
+    votes: BTreeMap<String, usize>,
+    blocks_published: usize,
+}
+
+impl Summary {
+    pub fn add(&mut self, message: &LoggedMessage) {
+        let annotation = match &message.annotation {
+            Some(a) => a,
+            None => return,
+        };
+        let peer = self
+            .peers
+            .entry(annotation.remote().to_string())
+            .or_default();
+        match annotation.direction {
+            Direction::Send => peer.bytes_sent += message.size,
+            Direction::Recv => peer.bytes_received += message.size,
+        }
+        match message.message_type {
+            MessageType::ConfirmAck => {
+                if let Some(account) = message.payload.get("account").and_then(|a| a.as_str()) {
+                    *peer.votes.entry(account.to_owned()).or_default() += 1;
+                }
+            }
+            MessageType::Publish => peer.blocks_published += 1,
+            _ => {}
+        }
+        *peer
+            .messages
+            .entry(format!("{:?}", message.message_type))
+            .or_default() += 1;
+    }
+
+    pub fn log(&self) {
+        for (addr, peer) in &self.peers {
+            info!(
+                "Peer {}: sent {} bytes, received {} bytes, {} blocks published, votes from {} representatives",
+                addr,
+                peer.bytes_sent,
+                peer.bytes_received,
+                peer.blocks_published,
+                peer.votes.len()
+            );
+            for (message_type, count) in &peer.messages {
+                info!("Peer {}: {} {}", addr, count, message_type);
+            }
+        }
+    }
+}
+
+fn csv_escape(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::Annotation;
+
+    fn message(direction: Direction, payload: serde_json::Value) -> LoggedMessage {
+        LoggedMessage {
+            annotation: Some(Annotation {
+                packet: 7,
+                timestamp: Utc::now(),
+                direction,
+                source: "10.0.0.1:7075".parse().unwrap(),
+                destination: "10.0.0.2:54321".parse().unwrap(),
+                size: 100,
+            }),
+            message_type: MessageType::ConfirmAck,
+            size: 50,
+            payload,
+        }
+    }
+
+    #[test]
+    fn csv() {
+        let m = message(Direction::Recv, serde_json::json!({"a": [1, "b"]}));
+        let record = Record::new(&m).unwrap();
+        let csv = record.to_csv().unwrap();
+        assert!(csv.starts_with("7,"));
+        assert!(csv.ends_with(r#",10.0.0.1:7075->10.0.0.2:54321,ConfirmAck,"{""a"":[1,""b""]}""#));
+    }
+
+    #[test]
+    fn summary() {
+        let mut summary = Summary::default();
+        summary.add(&message(
+            Direction::Recv,
+            serde_json::json!({"account": "nano_1"}),
+        ));
+        summary.add(&message(
+            Direction::Send,
+            serde_json::json!({"account": "nano_1"}),
+        ));
+        let peer = &summary.peers["10.0.0.1:7075"];
+        assert_eq!(peer.bytes_received, 50);
+        assert_eq!(peer.votes["nano_1"], 1);
+        assert_eq!(peer.messages["ConfirmAck"], 1);
+        assert_eq!(summary.peers["10.0.0.2:54321"].bytes_sent, 50);
+    }
+}
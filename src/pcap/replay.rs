@@ -0,0 +1,132 @@
+use crate::blocks::BlockHash;
+use crate::node::ledger_event::{BlockOutcome, LedgerEvent};
+use crate::node::ArcState;
+use crate::Public;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::info;
+
+/// What replaying a capture did to the state.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct ReplayReport {
+    /// How many blocks had each outcome, counting every time a block was seen.
+    pub blocks: BTreeMap<BlockOutcome, usize>,
+
+    /// How many distinct representatives voted for each block hash.
+    pub elections: BTreeMap<String, usize>,
+
+    /// The latest block hash of each account that had a block accepted.
+    pub frontiers: BTreeMap<String, String>,
+}
+
+impl ReplayReport {
+    pub async fn new(events: &[LedgerEvent], state: &ArcState) -> anyhow::Result<Self> {
+        let mut report = Self::default();
+        let mut votes: HashMap<&BlockHash, HashSet<&Public>> = HashMap::new();
+        let mut accounts: HashSet<&Public> = HashSet::new();
+
+        for event in events {
+            match event {
+                LedgerEvent::Block {
+                    account, outcome, ..
+                } => {
+                    *report.blocks.entry(*outcome).or_default() += 1;
+                    if let (BlockOutcome::Accepted, Some(account)) = (outcome, account) {
+                        accounts.insert(account);
+                    }
+                }
+                LedgerEvent::Vote {
+                    hash,
+                    representative,
+                    ..
+                } => {
+                    votes.entry(hash).or_default().insert(representative);
+                }
+                LedgerEvent::Telemetry { .. } => {}
+            }
+        }
+
+        report.elections = votes
+            .into_iter()
+            .map(|(hash, reps)| (hash.to_string(), reps.len()))
+            .collect();
+
+        let state = state.lock().await;
+        for account in accounts {
+            if let Some(hash) = state.get_latest_block_hash_for_account(account).await? {
+                report
+                    .frontiers
+                    .insert(account.to_address().to_string(), hash.to_string());
+            }
+        }
+
+        Ok(report)
+    }
+
+    pub fn log(&self) {
+        for (outcome, count) in &self.blocks {
+            info!("Blocks {:?}: {}", outcome, count);
+        }
+        info!("Elections observed: {}", self.elections.len());
+        for (address, hash) in &self.frontiers {
+            info!("Frontier {}: {}", address, hash);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::Network;
+    use crate::node::timestamp::Timestamp;
+    use crate::node::MemoryState;
+    use crate::{Seed, Signature};
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
+
+    #[tokio::test]
+    async fn report() {
+        let network = Network::Live;
+        let genesis = network.genesis_block();
+        let account = genesis.account().to_owned();
+        let state: ArcState = Arc::new(Mutex::new(MemoryState::new(network)));
+        state.lock().await.add_block(&genesis).await.unwrap();
+
+        let hash = genesis.hash().unwrap().to_owned();
+        let vote = |i| LedgerEvent::Vote {
+            hash: hash.clone(),
+            representative: Seed::zero().derive(i).to_public().unwrap(),
+            signature: Signature::zero(),
+            timestamp: Timestamp::from_u64(1),
+        };
+        let events = vec![
+            LedgerEvent::Block {
+                hash: Some(hash.clone()),
+                account: Some(account.clone()),
+                outcome: BlockOutcome::Accepted,
+            },
+            LedgerEvent::Block {
+                hash: Some(hash.clone()),
+                account: Some(account.clone()),
+                outcome: BlockOutcome::AlreadyExists,
+            },
+            LedgerEvent::Block {
+                hash: None,
+                account: None,
+                outcome: BlockOutcome::AlreadyExists,
+            },
+            vote(0),
+            vote(1),
+            vote(0),
+        ];
+
+        let report = ReplayReport::new(&events, &state).await.unwrap();
+        assert_eq!(report.blocks[&BlockOutcome::Accepted], 1);
+        assert_eq!(report.blocks[&BlockOutcome::AlreadyExists], 2);
+        assert_eq!(report.elections[&hash.to_string()], 2);
+        assert_eq!(
+            report.frontiers[&account.to_address().to_string()],
+            hash.to_string()
+        );
+    }
+}
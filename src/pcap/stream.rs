@@ -0,0 +1,233 @@
+//! Reassembly of one direction of a TCP connection from captured segments.
+//!
+//! Segments can be retransmitted, overlap, arrive out of order or be missing from the capture
+//! altogether. They are put back in sequence order here, and anything that can't be recovered is
+//! reported as a [Chunk::Gap] so the reader knows to resynchronize.
+
+/// How many bytes of out of order segments to hold on to while waiting for a missing segment,
+/// before giving up on it.
+const MAX_PENDING_BYTES: usize = 256 * 1024;
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Chunk {
+    /// The next bytes of the stream, in order.
+    Data(Vec<u8>),
+
+    /// Bytes are missing from the stream before the next chunk. The count is zero when it isn't
+    /// known, e.g. when the capture started part way through a connection.
+    Gap(u32),
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Reassembler {
+    /// The sequence number of the next byte expected, once known.
+    next_seq: Option<u32>,
+
+    /// Segments that arrived ahead of `next_seq`.
+    pending: Vec<(u32, Vec<u8>)>,
+    pending_bytes: usize,
+}
+
+impl Reassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a captured segment, returning the chunks of the stream that are now in order.
+    pub fn segment(&mut self, seq: u32, syn: bool, rst: bool, data: &[u8]) -> Vec<Chunk> {
+        let mut chunks = vec![];
+
+        if rst {
+            // Anything after a reset belongs to a new connection.
+            self.next_seq = None;
+            self.pending.clear();
+            self.pending_bytes = 0;
+            return chunks;
+        }
+
+        let seq = if syn {
+            // The SYN uses up a sequence number before the first byte of data.
+            let start = seq.wrapping_add(1);
+            self.next_seq = Some(start);
+            self.pending.clear();
+            self.pending_bytes = 0;
+            start
+        } else {
+            seq
+        };
+
+        if data.is_empty() {
+            return chunks;
+        }
+
+        if self.next_seq.is_none() {
+            // The start of the connection wasn't captured, so this is likely part way through a
+            // message.
+            self.next_seq = Some(seq);
+            chunks.push(Chunk::Gap(0));
+        }
+
+        self.pending_bytes += data.len();
+        self.pending.push((seq, data.to_vec()));
+        self.drain(&mut chunks);
+
+        while self.pending_bytes > MAX_PENDING_BYTES {
+            self.skip_to_pending(&mut chunks);
+        }
+        chunks
+    }
+
+    /// Give up waiting for missing segments, returning everything that is left.
+    pub fn finish(&mut self) -> Vec<Chunk> {
+        let mut chunks = vec![];
+        while !self.pending.is_empty() {
+            self.skip_to_pending(&mut chunks);
+        }
+        chunks
+    }
+
+    /// Move pending segments that continue the stream into `chunks`.
+    fn drain(&mut self, chunks: &mut Vec<Chunk>) {
+        let mut next_seq = match self.next_seq {
+            Some(n) => n,
+            None => return,
+        };
+
+        loop {
+            let mut progressed = false;
+            let mut idx = 0;
+            while idx < self.pending.len() {
+                let (seq, data) = &self.pending[idx];
+                let offset = seq_diff(next_seq, *seq);
+                if offset < 0 {
+                    // Still ahead of the stream.
+                    idx += 1;
+                    continue;
+                }
+
+                let offset = offset as usize;
+                if offset < data.len() {
+                    // Trim anything already seen, i.e. retransmissions and overlaps.
+                    chunks.push(Chunk::Data(data[offset..].to_vec()));
+                    next_seq = next_seq.wrapping_add((data.len() - offset) as u32);
+                    progressed = true;
+                }
+                self.pending_bytes -= data.len();
+                self.pending.swap_remove(idx);
+            }
+            if !progressed {
+                break;
+            }
+        }
+
+        self.next_seq = Some(next_seq);
+    }
+
+    /// Declare the bytes up to the earliest pending segment lost, and carry on from there.
+    fn skip_to_pending(&mut self, chunks: &mut Vec<Chunk>) {
+        let next_seq = match self.next_seq {
+            Some(n) => n,
+            None => return,
+        };
+        let earliest = match self
+            .pending
+            .iter()
+            .map(|(seq, _)| *seq)
+            .min_by_key(|seq| seq_diff(*seq, next_seq))
+        {
+            Some(seq) => seq,
+            None => return,
+        };
+
+        chunks.push(Chunk::Gap(earliest.wrapping_sub(next_seq)));
+        self.next_seq = Some(earliest);
+        self.drain(chunks);
+    }
+}
+
+/// How far `a` is ahead of `b`, allowing for the sequence number wrapping around.
+fn seq_diff(a: u32, b: u32) -> i32 {
+    a.wrapping_sub(b) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(chunks: Vec<Chunk>) -> Vec<u8> {
+        chunks
+            .into_iter()
+            .flat_map(|c| match c {
+                Chunk::Data(d) => d,
+                Chunk::Gap(_) => panic!("Unexpected gap"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn in_order() {
+        let mut r = Reassembler::new();
+        assert!(r.segment(100, true, false, &[]).is_empty());
+        assert_eq!(data(r.segment(101, false, false, b"abc")), b"abc");
+        assert_eq!(data(r.segment(104, false, false, b"def")), b"def");
+    }
+
+    #[test]
+    fn retransmission_and_overlap() {
+        let mut r = Reassembler::new();
+        r.segment(100, true, false, &[]);
+        assert_eq!(data(r.segment(101, false, false, b"abc")), b"abc");
+        assert!(r.segment(101, false, false, b"abc").is_empty());
+        assert_eq!(data(r.segment(102, false, false, b"bcde")), b"de");
+    }
+
+    #[test]
+    fn out_of_order() {
+        let mut r = Reassembler::new();
+        r.segment(100, true, false, &[]);
+        assert!(r.segment(104, false, false, b"def").is_empty());
+        assert!(r.segment(107, false, false, b"ghi").is_empty());
+        assert_eq!(data(r.segment(101, false, false, b"abc")), b"abcdefghi");
+    }
+
+    #[test]
+    fn wrapping() {
+        let mut r = Reassembler::new();
+        r.segment(u32::MAX - 2, true, false, &[]);
+        assert!(r.segment(1, false, false, b"def").is_empty());
+        assert_eq!(
+            data(r.segment(u32::MAX - 1, false, false, b"abc")),
+            b"abcdef"
+        );
+    }
+
+    #[test]
+    fn missing_segment() {
+        let mut r = Reassembler::new();
+        r.segment(100, true, false, &[]);
+        assert_eq!(data(r.segment(101, false, false, b"abc")), b"abc");
+        assert!(r.segment(107, false, false, b"ghi").is_empty());
+        assert_eq!(
+            r.finish(),
+            vec![Chunk::Gap(3), Chunk::Data(b"ghi".to_vec())]
+        );
+    }
+
+    #[test]
+    fn too_much_pending() {
+        let mut r = Reassembler::new();
+        r.segment(0, true, false, &[]);
+        let big = vec![0u8; MAX_PENDING_BYTES];
+        assert!(r.segment(11, false, false, &big).is_empty());
+        let chunks = r.segment(11 + big.len() as u32, false, false, b"x");
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0], Chunk::Gap(10));
+    }
+
+    #[test]
+    fn no_syn() {
+        let mut r = Reassembler::new();
+        let chunks = r.segment(5000, false, false, b"abc");
+        assert_eq!(chunks, vec![Chunk::Gap(0), Chunk::Data(b"abc".to_vec())]);
+    }
+}
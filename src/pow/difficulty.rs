@@ -1,5 +1,7 @@
-use crate::encoding::{deserialize_from_str, expect_len, to_hex};
-use crate::{Error, Result};
+use crate::blocks::Subtype;
+use crate::encoding::{deserialize_from_string, expect_len, to_hex};
+use crate::pow::Multiplier;
+use crate::{Error, Network, Result};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::cmp::Ordering;
 use std::convert::TryFrom;
@@ -17,14 +19,31 @@ impl Difficulty {
         Self(v)
     }
 
+    /// The live network threshold for receive, open and epoch blocks.
     pub fn receive() -> Self {
         Self::from_str("FFFFFE0000000000").unwrap()
     }
 
+    /// The live network threshold for send and change blocks.
     pub fn normal() -> Self {
         Self::from_str("FFFFFFF800000000").unwrap()
     }
 
+    /// The threshold a block of `subtype` needs on `network`.
+    ///
+    /// ```
+    /// use feeless::blocks::Subtype;
+    /// use feeless::{Difficulty, Epoch, Network};
+    ///
+    /// let threshold = Difficulty::threshold(Network::Live, Epoch::Epoch2, &Subtype::Receive);
+    /// assert_eq!(threshold, Difficulty::receive());
+    /// ```
+    pub fn threshold(network: Network, epoch: Epoch, subtype: &Subtype) -> Self {
+        WorkThresholds::for_network(network)
+            .threshold(epoch, subtype)
+            .to_owned()
+    }
+
     pub fn from_fixed_slice(s: &[u8; Self::LEN]) -> Result<Self> {
         Ok(Difficulty(u64::from_le_bytes(*s)))
     }
@@ -43,6 +62,85 @@ impl Difficulty {
     pub fn as_u64(&self) -> u64 {
         self.0
     }
+
+    /// How many times more work this is than `base`, e.g. `2.0` takes twice as long to generate.
+    pub fn to_multiplier(&self, base: &Difficulty) -> Multiplier {
+        Multiplier(base.inverse() / self.inverse())
+    }
+
+    /// The difficulty that is `multiplier` times more work than `base`.
+    pub fn from_multiplier(multiplier: &Multiplier, base: &Difficulty) -> Self {
+        let inverse = base.inverse() / multiplier.as_f64();
+        if inverse <= 1f64 {
+            return Self(u64::MAX);
+        }
+        if inverse >= u64::MAX as f64 {
+            return Self(0);
+        }
+        // Subtract as integers, since `inverse - 1` can round back up to `inverse` as a float.
+        Self(u64::MAX - inverse as u64 + 1)
+    }
+
+    /// The number of values at or above this difficulty, which is inversely proportional to the
+    /// work needed to reach it.
+    fn inverse(&self) -> f64 {
+        (u64::MAX - self.0) as f64 + 1f64
+    }
+}
+
+/// The work epoch of an account, which changes the thresholds its blocks need.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Epoch {
+    /// Before the account was upgraded to epoch 2, every block uses the same threshold.
+    Epoch1,
+
+    /// Receive blocks need less work than send and change blocks.
+    Epoch2,
+}
+
+impl Default for Epoch {
+    fn default() -> Self {
+        Epoch::Epoch2
+    }
+}
+
+/// The work thresholds of a [Network].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkThresholds {
+    pub epoch_1: Difficulty,
+    pub epoch_2: Difficulty,
+    pub epoch_2_receive: Difficulty,
+}
+
+impl WorkThresholds {
+    pub fn for_network(network: Network) -> Self {
+        let (epoch_1, epoch_2, epoch_2_receive) = match network {
+            Network::Live => (0xffffffc000000000, 0xfffffff800000000, 0xfffffe0000000000),
+            Network::Beta => (0xfffff00000000000, 0xfffff00000000000, 0xffffe00000000000),
+            Network::Test => (0xfe00000000000000, 0xffc0000000000000, 0xf000000000000000),
+        };
+        Self {
+            epoch_1: Difficulty(epoch_1),
+            epoch_2: Difficulty(epoch_2),
+            epoch_2_receive: Difficulty(epoch_2_receive),
+        }
+    }
+
+    /// The threshold for a block of `subtype` on an account in `epoch`.
+    pub fn threshold(&self, epoch: Epoch, subtype: &Subtype) -> &Difficulty {
+        match (epoch, subtype) {
+            (Epoch::Epoch1, _) => &self.epoch_1,
+            (Epoch::Epoch2, Subtype::Receive | Subtype::Open | Subtype::Epoch) => {
+                &self.epoch_2_receive
+            }
+            (Epoch::Epoch2, Subtype::Send | Subtype::Change) => &self.epoch_2,
+        }
+    }
+
+    /// The difficulty multipliers are relative to, like the `multiplier` fields in RPC responses.
+    pub fn base(&self) -> &Difficulty {
+        &self.epoch_2
+    }
 }
 
 impl Debug for Difficulty {
@@ -88,7 +186,7 @@ impl<'de> Deserialize<'de> for Difficulty {
     where
         D: Deserializer<'de>,
     {
-        deserialize_from_str(deserializer)
+        deserialize_from_string(deserializer)
     }
 }
 
@@ -104,6 +202,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn multiplier() {
+        let base = Difficulty::from_str("fffffff800000000").unwrap();
+        let double = Difficulty::from_str("fffffffc00000000").unwrap();
+        assert_eq!(double.to_multiplier(&base).as_f64(), 2.0);
+        assert_eq!(base.to_multiplier(&double).as_f64(), 0.5);
+
+        let two = Multiplier::new(2.0).unwrap();
+        assert_eq!(Difficulty::from_multiplier(&two, &base), double);
+        assert_eq!(
+            Difficulty::from_multiplier(&two, &Difficulty::new(0xff00000000000000)),
+            Difficulty::new(0xff80000000000000)
+        );
+        let half = Multiplier::new(0.5).unwrap();
+        assert_eq!(Difficulty::from_multiplier(&half, &double), base);
+        assert_eq!(Difficulty::from_multiplier(&Multiplier::one(), &base), base);
+
+        // The receive threshold is 64 times easier than send on the live network.
+        let live = WorkThresholds::for_network(Network::Live);
+        assert_eq!(
+            live.epoch_2_receive.to_multiplier(live.base()).as_f64(),
+            1f64 / 64f64
+        );
+    }
+
+    #[test]
+    fn thresholds() {
+        let live = WorkThresholds::for_network(Network::Live);
+        assert_eq!(live.epoch_2, Difficulty::normal());
+        assert_eq!(live.epoch_2_receive, Difficulty::receive());
+        assert_eq!(
+            live.threshold(Epoch::Epoch1, &Subtype::Receive),
+            &live.epoch_1
+        );
+        assert_eq!(
+            live.threshold(Epoch::Epoch2, &Subtype::Open),
+            &live.epoch_2_receive
+        );
+        assert_eq!(
+            live.threshold(Epoch::Epoch2, &Subtype::Change),
+            &live.epoch_2
+        );
+    }
+
     #[test]
     fn dont_panic() {
         // These have unwraps in them and so this is a sanity check to make sure it doesn't panic.
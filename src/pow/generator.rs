@@ -0,0 +1,342 @@
+use crate::encoding::blake2b_callback;
+use crate::pow::{Difficulty, Subject, Work};
+use crate::Error;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How many hashes each thread does before checking if it should stop and updating the counter.
+const BATCH: u64 = 4096;
+
+/// The longest the controlling thread waits before checking for cancellation.
+const POLL: Duration = Duration::from_millis(50);
+
+/// Stops a [WorkGenerator] from another thread or task.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Cancels when dropped, so dropping a future returned by [WorkGenerator::generate_async] stops
+/// the threads.
+struct CancelOnDrop(CancelToken);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// How far along a generation is, given to the progress callback.
+#[derive(Debug, Clone)]
+pub struct WorkProgress {
+    pub hashes: u64,
+    pub elapsed: Duration,
+}
+
+impl WorkProgress {
+    /// Hashes per second.
+    pub fn hash_rate(&self) -> f64 {
+        self.hashes as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Work that was found, with statistics about finding it.
+#[derive(Debug, Clone)]
+pub struct GeneratedWork {
+    pub work: Work,
+    pub difficulty: Difficulty,
+    pub progress: WorkProgress,
+}
+
+type ProgressCallback = Arc<dyn Fn(&WorkProgress) + Send + Sync>;
+
+/// Generates work on multiple threads, each searching its own part of the nonce space.
+///
+/// ```
+/// use feeless::blocks::BlockHash;
+/// use feeless::{Difficulty, Subject, WorkGenerator};
+/// use std::time::Duration;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let subject = Subject::Hash(BlockHash::zero());
+/// let difficulty = Difficulty::new(0xff00000000000000);
+/// let generated = WorkGenerator::new()
+///     .threads(2)
+///     .timeout(Duration::from_secs(60))
+///     .generate(&subject, &difficulty)?;
+/// assert!(generated.work.verify(&subject, &difficulty)?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct WorkGenerator {
+    threads: usize,
+    timeout: Option<Duration>,
+    cancel: CancelToken,
+    progress: Option<(Duration, ProgressCallback)>,
+}
+
+impl WorkGenerator {
+    /// Use a thread for each CPU, with no timeout.
+    pub fn new() -> Self {
+        Self {
+            threads: num_cpus::get(),
+            timeout: None,
+            cancel: CancelToken::new(),
+            progress: None,
+        }
+    }
+
+    pub fn threads(&mut self, v: usize) -> &mut Self {
+        self.threads = v.max(1);
+        self
+    }
+
+    /// Give up with [Error::WorkTimeout] after this long.
+    pub fn timeout(&mut self, v: Duration) -> &mut Self {
+        self.timeout = Some(v);
+        self
+    }
+
+    /// Give up with [Error::WorkCancelled] when this token is cancelled.
+    pub fn cancel_token(&mut self, v: CancelToken) -> &mut Self {
+        self.cancel = v;
+        self
+    }
+
+    /// Call `f` about every `interval` while generating.
+    pub fn on_progress<F>(&mut self, interval: Duration, f: F) -> &mut Self
+    where
+        F: Fn(&WorkProgress) + Send + Sync + 'static,
+    {
+        self.progress = Some((interval, Arc::new(f)));
+        self
+    }
+
+    /// Block until work is found, the timeout is reached, or the generator is cancelled.
+    pub fn generate(
+        &self,
+        subject: &Subject,
+        threshold: &Difficulty,
+    ) -> Result<GeneratedWork, Error> {
+        self.run(subject, threshold, &CancelToken::new())
+    }
+
+    /// Generate on a blocking thread. Dropping the returned future cancels the generation.
+    pub async fn generate_async(
+        &self,
+        subject: Subject,
+        threshold: Difficulty,
+    ) -> Result<GeneratedWork, Error> {
+        let dropped = CancelToken::new();
+        let _guard = CancelOnDrop(dropped.clone());
+        let generator = self.clone();
+        tokio::task::spawn_blocking(move || generator.run(&subject, &threshold, &dropped))
+            .await
+            .unwrap_or(Err(Error::WorkCancelled))
+    }
+
+    /// Generate, also stopping when `dropped` is cancelled.
+    fn run(
+        &self,
+        subject: &Subject,
+        threshold: &Difficulty,
+        dropped: &CancelToken,
+    ) -> Result<GeneratedWork, Error> {
+        let mut subject_bytes = [0u8; 32];
+        subject_bytes.copy_from_slice(subject.as_bytes());
+        let threshold = threshold.as_u64();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let hashes = Arc::new(AtomicU64::new(0));
+        let (tx, rx) = channel();
+
+        // Spread the threads evenly over the nonce space from a random starting point.
+        let start = rand::random::<u64>();
+        let stride = u64::MAX / self.threads as u64;
+        let handles: Vec<_> = (0..self.threads as u64)
+            .map(|i| {
+                let nonce = start.wrapping_add(i.wrapping_mul(stride));
+                let (stop, hashes, tx) = (stop.clone(), hashes.clone(), tx.clone());
+                thread::spawn(move || {
+                    if let Some(found) = search(nonce, &subject_bytes, threshold, &stop, &hashes) {
+                        let _ = tx.send(found);
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let started = Instant::now();
+        let mut last_progress = started;
+        let progress = |hashes: &AtomicU64| WorkProgress {
+            hashes: hashes.load(Ordering::Relaxed),
+            elapsed: started.elapsed(),
+        };
+        let result = loop {
+            if self.cancel.is_cancelled() || dropped.is_cancelled() {
+                break Err(Error::WorkCancelled);
+            }
+            if let Some(timeout) = self.timeout {
+                if started.elapsed() >= timeout {
+                    break Err(Error::WorkTimeout(timeout));
+                }
+            }
+            if let Some((interval, f)) = &self.progress {
+                if last_progress.elapsed() >= *interval {
+                    f(&progress(&hashes));
+                    last_progress = Instant::now();
+                }
+            }
+
+            match rx.recv_timeout(POLL) {
+                Ok((nonce, difficulty)) => {
+                    // This can't fail since the nonce is the same length as work.
+                    let work = Work::try_from(nonce.to_be_bytes().as_ref()).unwrap();
+                    break Ok(GeneratedWork {
+                        work,
+                        difficulty: Difficulty::new(difficulty),
+                        progress: progress(&hashes),
+                    });
+                }
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break Err(Error::WorkCancelled),
+            }
+        };
+
+        stop.store(true, Ordering::Relaxed);
+        for handle in handles {
+            let _ = handle.join();
+        }
+        result
+    }
+}
+
+impl Default for WorkGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Search from `nonce` until a solution is found or `stop` is set.
+fn search(
+    mut nonce: u64,
+    subject: &[u8; 32],
+    threshold: u64,
+    stop: &AtomicBool,
+    hashes: &AtomicU64,
+) -> Option<(u64, u64)> {
+    let mut work_and_subject = [0u8; 40];
+    work_and_subject[Work::LEN..].copy_from_slice(subject);
+
+    loop {
+        for _ in 0..BATCH {
+            work_and_subject[..Work::LEN].copy_from_slice(&nonce.to_le_bytes());
+            let mut difficulty = 0u64;
+            blake2b_callback(Work::LEN, &work_and_subject, |b| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(b);
+                difficulty = u64::from_le_bytes(bytes);
+            });
+            if difficulty >= threshold {
+                stop.store(true, Ordering::Relaxed);
+                return Some((nonce, difficulty));
+            }
+            nonce = nonce.wrapping_add(1);
+        }
+        hashes.fetch_add(BATCH, Ordering::Relaxed);
+        if stop.load(Ordering::Relaxed) {
+            return None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::BlockHash;
+    use std::sync::Mutex;
+
+    fn subject() -> Subject {
+        Subject::Hash(BlockHash::zero())
+    }
+
+    #[test]
+    fn generate() {
+        let threshold = Difficulty::new(0xffff000000000000);
+        let generated = WorkGenerator::new()
+            .threads(4)
+            .generate(&subject(), &threshold)
+            .unwrap();
+        assert!(generated.work.verify(&subject(), &threshold).unwrap());
+        assert_eq!(
+            generated.work.difficulty(&subject()).unwrap(),
+            generated.difficulty
+        );
+    }
+
+    #[test]
+    fn timeout_and_cancel() {
+        let impossible = Difficulty::new(u64::MAX);
+        let result = WorkGenerator::new()
+            .threads(1)
+            .timeout(Duration::from_millis(100))
+            .generate(&subject(), &impossible);
+        assert!(matches!(result, Err(Error::WorkTimeout(_))));
+
+        let token = CancelToken::new();
+        let cancel = token.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            cancel.cancel();
+        });
+        let result = WorkGenerator::new()
+            .threads(1)
+            .cancel_token(token)
+            .generate(&subject(), &impossible);
+        assert!(matches!(result, Err(Error::WorkCancelled)));
+    }
+
+    #[test]
+    fn progress() {
+        let reports = Arc::new(Mutex::new(vec![]));
+        let r = reports.clone();
+        let _ = WorkGenerator::new()
+            .threads(1)
+            .timeout(Duration::from_millis(300))
+            .on_progress(Duration::from_millis(50), move |p| {
+                r.lock().unwrap().push(p.hashes)
+            })
+            .generate(&subject(), &Difficulty::new(u64::MAX));
+        let reports = reports.lock().unwrap();
+        assert!(!reports.is_empty());
+        assert!(reports.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[tokio::test]
+    async fn dropping_the_future_cancels() {
+        let generator = WorkGenerator::new();
+        let result = tokio::time::timeout(
+            Duration::from_millis(100),
+            generator.generate_async(subject(), Difficulty::new(u64::MAX)),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+}
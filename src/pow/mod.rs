@@ -1,5 +1,9 @@
 mod difficulty;
+mod generator;
+mod multiplier;
 mod work;
 
-pub use difficulty::Difficulty;
+pub use difficulty::{Difficulty, Epoch, WorkThresholds};
+pub use generator::{CancelToken, GeneratedWork, WorkGenerator, WorkProgress};
+pub use multiplier::Multiplier;
 pub use work::{Subject, Work};
@@ -0,0 +1,84 @@
+use crate::encoding::deserialize_from_string;
+use crate::{Error, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// How much harder a [Difficulty](crate::Difficulty) is than a base difficulty.
+///
+/// A multiplier of `2.0` takes on average twice as many attempts to generate as the base. Nano
+/// RPC sends these as strings, e.g. `"1.5"`.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Multiplier(pub(super) f64);
+
+impl Multiplier {
+    pub fn new(v: f64) -> Result<Self> {
+        if !v.is_finite() || v <= 0f64 {
+            return Err(Error::InvalidMultiplier(v.to_string()));
+        }
+        Ok(Self(v))
+    }
+
+    /// The base difficulty itself.
+    pub fn one() -> Self {
+        Self(1f64)
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        self.0
+    }
+}
+
+impl Display for Multiplier {
+    /// Formatting options are passed through, so `{:.2}` works.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl FromStr for Multiplier {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let v = f64::from_str(s).map_err(|_| Error::InvalidMultiplier(s.into()))?;
+        Self::new(v)
+    }
+}
+
+impl Serialize for Multiplier {
+    fn serialize<S>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<<S as Serializer>::Ok, <S as Serializer>::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Multiplier {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, <D as Deserializer<'de>>::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_from_string(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Multiplier::from_str("1.5").unwrap().as_f64(), 1.5);
+        assert!(Multiplier::from_str("0").is_err());
+        assert!(Multiplier::from_str("-1").is_err());
+        assert!(Multiplier::from_str("abc").is_err());
+        assert_eq!(
+            format!("{:.2}", Multiplier::new(1.0 / 3.0).unwrap()),
+            "0.33"
+        );
+    }
+}
@@ -1,10 +1,9 @@
 use crate::blocks::BlockHash;
-use crate::encoding::{blake2b, blake2b_callback};
+use crate::encoding::blake2b;
 use crate::pow::difficulty::Difficulty;
+use crate::pow::WorkGenerator;
 use crate::{hexify, Public};
-use bytes::Buf;
 use rand::RngCore;
-use std::convert::TryFrom;
 use std::fmt::Debug;
 
 #[derive(Debug)]
@@ -41,46 +40,11 @@ impl Work {
         s
     }
 
-    /// Block and generate forever until we find a solution.
+    /// Block and generate forever until we find a solution, using a thread for each CPU.
+    ///
+    /// Use a [WorkGenerator] for control over threads, timeouts and cancellation.
     pub fn generate(subject: &Subject, threshold: &Difficulty) -> anyhow::Result<Work> {
-        let mut work_and_subject = [0u8; 40];
-
-        // We can place the subject in the second part of the slice which will not change.
-        let subject_slice = &mut work_and_subject[Self::LEN..];
-
-        // This will panic if the source slice is too big. The assertion here is a safety measure
-        // in debug builds.
-        debug_assert_eq!(subject.as_bytes().len(), subject_slice.len());
-        subject.as_bytes().copy_to_slice(subject_slice);
-
-        let mut difficulty: Difficulty = Difficulty::new(0);
-
-        // Fill the first 8 bytes with the random work.
-        let work_slice = &mut work_and_subject[0..Self::LEN];
-        rand::thread_rng().fill_bytes(work_slice);
-
-        loop {
-            // Pick a random byte position and increment.
-            // I'm guessing this is slightly faster than using fill_bytes for a new set of numbers.
-            // TODO: Bench this guess.
-            let idx = (rand::random::<u8>() % (Self::LEN as u8)) as usize;
-            let c = work_and_subject[idx];
-            work_and_subject[idx] = if c == 0xff { 0 } else { c + 1 };
-
-            blake2b_callback(Self::LEN, &work_and_subject, |b| {
-                difficulty = Difficulty::from_le_slice(b).unwrap();
-            });
-            // TODO: Check if this is > or >=
-            if &difficulty > threshold {
-                break;
-            }
-        }
-
-        let work_slice = &work_and_subject[0..Self::LEN];
-        let mut work_bytes = Vec::from(work_slice);
-        work_bytes.reverse();
-        let work = Work::try_from(work_bytes.as_slice()).unwrap();
-        return Ok(work);
+        Ok(WorkGenerator::new().generate(subject, threshold)?.work)
     }
 
     pub fn hash(work_and_subject: &[u8]) -> Box<[u8]> {
@@ -89,7 +53,7 @@ impl Work {
 
     pub fn verify(&self, subject: &Subject, threshold: &Difficulty) -> anyhow::Result<bool> {
         let difficulty = self.difficulty(subject)?;
-        Ok(&difficulty > threshold)
+        Ok(&difficulty >= threshold)
     }
 
     pub fn difficulty(&self, subject: &Subject) -> anyhow::Result<Difficulty> {
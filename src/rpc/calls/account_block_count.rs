@@ -33,7 +33,7 @@ impl AccountBlockCountRequest {
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct AccountBlockCountResponse {
     #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
-    block_count: u64,
+    pub block_count: u64,
 }
 
 #[cfg(test)]
@@ -14,7 +14,7 @@ impl RPCRequest for &AccountGetRequest {
     type Response = AccountGetResponse;
 
     fn action(&self) -> &str {
-        "account_block_count"
+        "account_get"
     }
 
     async fn call(&self, client: &RPCClient) -> Result<AccountGetResponse> {
@@ -30,7 +30,7 @@ impl AccountGetRequest {
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct AccountGetResponse {
-    account: Address,
+    pub account: Address,
 }
 
 #[cfg(test)]
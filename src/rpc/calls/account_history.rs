@@ -22,16 +22,16 @@ pub struct AccountHistoryRequest {
     /// Start displaying blocks from this hash. Useful for pagination.
     #[serde(skip_serializing_if = "Option::is_none")]
     #[clap(short, long)]
-    head: Option<BlockHash>,
+    pub head: Option<BlockHash>,
 
     /// Skips a number of blocks starting from head.
     #[serde(skip_serializing_if = "Option::is_none")]
     #[clap(short, long)]
-    offset: Option<u64>,
+    pub offset: Option<u64>,
 
     /// Request to reverse the results.
     #[clap(short, long)]
-    reverse: bool,
+    pub reverse: bool,
 
     /// Results will be filtered to only show sends/receives connected to the provided account(s).
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -71,8 +71,13 @@ pub struct AccountHistoryResponse {
     pub account: Address,
     pub history: Vec<AccountHistoryEntry>,
 
+    /// The `head` of the next page, when there are more blocks.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub previous: Option<BlockHash>,
+
+    /// The `head` of the next page when `reverse` is set, when there are more blocks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<BlockHash>,
 }
 
 #[serde_with::serde_as]
@@ -182,6 +187,7 @@ mod tests {
                     )
                     .unwrap()
                 ),
+                next: None,
             }
         );
     }
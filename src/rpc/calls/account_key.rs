@@ -14,7 +14,7 @@ impl RPCRequest for &AccountKeyRequest {
     type Response = AccountKeyResponse;
 
     fn action(&self) -> &str {
-        "account_block_count"
+        "account_key"
     }
 
     async fn call(&self, client: &RPCClient) -> Result<AccountKeyResponse> {
@@ -30,7 +30,7 @@ impl AccountKeyRequest {
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct AccountKeyResponse {
-    key: Public,
+    pub key: Public,
 }
 
 #[cfg(test)]
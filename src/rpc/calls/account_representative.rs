@@ -30,7 +30,7 @@ impl AccountRepresentativeRequest {
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct AccountRepresentativeResponse {
-    representative: Address,
+    pub representative: Address,
 }
 
 #[cfg(test)]
@@ -14,7 +14,7 @@ impl RPCRequest for &AccountWeightRequest {
     type Response = AccountWeightResponse;
 
     fn action(&self) -> &str {
-        "account_block_count"
+        "account_weight"
     }
 
     async fn call(&self, client: &RPCClient) -> Result<AccountWeightResponse> {
@@ -30,7 +30,7 @@ impl AccountWeightRequest {
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct AccountWeightResponse {
-    weight: Raw,
+    pub weight: Raw,
 }
 
 #[cfg(test)]
@@ -31,13 +31,13 @@ impl AccountsBalancesRequest {
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct AccountsBalancesResponse {
-    balances: HashMap<Address, AccountsBalancesEntry>,
+    pub balances: HashMap<Address, AccountsBalancesEntry>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct AccountsBalancesEntry {
-    balance: Raw,
-    pending: Raw,
+    pub balance: Raw,
+    pub pending: Raw,
 }
 
 #[cfg(test)]
@@ -32,7 +32,7 @@ impl AccountsFrontiersRequest {
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct AccountsFrontiersResponse {
-    frontiers: HashMap<Address, BlockHash>,
+    pub frontiers: HashMap<Address, BlockHash>,
 }
 
 #[cfg(test)]
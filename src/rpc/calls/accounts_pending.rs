@@ -8,27 +8,27 @@ use std::collections::HashMap;
 
 #[derive(Debug, Serialize, Deserialize, Clap, Clone)]
 pub struct AccountsPendingRequest {
-    accounts: Vec<Address>,
+    pub accounts: Vec<Address>,
 
     /// Limit the number of results to `count`.
     #[clap(short, long, default_value = "1")]
-    count: u64,
+    pub count: u64,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     #[clap(short, long)]
-    threshold: Option<Raw>,
+    pub threshold: Option<Raw>,
 
     #[clap(long)]
-    source: bool,
+    pub source: bool,
 
     #[clap(long)]
-    include_active: bool,
+    pub include_active: bool,
 
     #[clap(long)]
-    sorting: bool,
+    pub sorting: bool,
 
     #[clap(long)]
-    include_only_confirmed: bool,
+    pub include_only_confirmed: bool,
 }
 
 #[async_trait]
@@ -74,8 +74,8 @@ pub enum AccountsPendingResponse {
 
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct BlockEntry {
-    amount: Raw,
-    source: Address,
+    pub amount: Raw,
+    pub source: Address,
 }
 
 #[cfg(test)]
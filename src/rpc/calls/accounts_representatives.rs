@@ -0,0 +1,71 @@
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::{Address, Result};
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct AccountsRepresentativesRequest {
+    pub accounts: Vec<Address>,
+}
+
+#[async_trait]
+impl RPCRequest for &AccountsRepresentativesRequest {
+    type Response = AccountsRepresentativesResponse;
+
+    fn action(&self) -> &str {
+        "accounts_representatives"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<AccountsRepresentativesResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl AccountsRepresentativesRequest {
+    pub fn new(accounts: Vec<Address>) -> Self {
+        Self { accounts }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct AccountsRepresentativesResponse {
+    pub representatives: HashMap<Address, Address>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "representatives" : {
+                "nano_16u1uufyoig8777y6r8iqjtrw8sg8maqrm36zzcm95jmbd9i9aj5i8abr8u5": "nano_3hd4ezdgsp15iemx7h81in7xz5tpxi43b6b41zn3qmwiuypankocw3awes5k",
+                "nano_1hza3f7wiiqa7ig3jczyxj5yo86yegcmqk3criaz838j91sxcckpfhbhhra1": "nano_3hd4ezdgsp15iemx7h81in7xz5tpxi43b6b41zn3qmwiuypankocw3awes5k"
+            }
+        }
+        "#;
+
+        let r = serde_json::from_str::<AccountsRepresentativesResponse>(s).unwrap();
+
+        let rep =
+            Address::from_str("nano_3hd4ezdgsp15iemx7h81in7xz5tpxi43b6b41zn3qmwiuypankocw3awes5k")
+                .unwrap();
+        let mut representatives: HashMap<Address, Address> = HashMap::new();
+        representatives.insert(
+            Address::from_str("nano_16u1uufyoig8777y6r8iqjtrw8sg8maqrm36zzcm95jmbd9i9aj5i8abr8u5")
+                .unwrap(),
+            rep.clone(),
+        );
+        representatives.insert(
+            Address::from_str("nano_1hza3f7wiiqa7ig3jczyxj5yo86yegcmqk3criaz838j91sxcckpfhbhhra1")
+                .unwrap(),
+            rep,
+        );
+
+        assert_eq!(r, AccountsRepresentativesResponse { representatives })
+    }
+}
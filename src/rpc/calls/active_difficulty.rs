@@ -1,5 +1,4 @@
-use crate::pow::Difficulty;
-use crate::rpc::calls::{as_str, from_str};
+use crate::pow::{Difficulty, Multiplier};
 use crate::rpc::client::{RPCClient, RPCRequest};
 use crate::Result;
 use async_trait::async_trait;
@@ -30,8 +29,7 @@ impl ActiveDifficultyRequest {
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct ActiveDifficultyResponse {
-    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
-    pub multiplier: f64,
+    pub multiplier: Multiplier,
 
     pub network_current: Difficulty,
     pub network_minimum: Difficulty,
@@ -46,7 +44,7 @@ mod tests {
     #[test]
     fn response() {
         let r1 = ActiveDifficultyResponse {
-            multiplier: 0.1,
+            multiplier: Multiplier::new(0.1).unwrap(),
             network_current: Difficulty::new(1),
             network_minimum: Difficulty::new(2),
             network_receive_current: Difficulty::new(3),
@@ -30,7 +30,7 @@ impl AvailableSupplyRequest {
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct AvailableSupplyResponse {
-    available: Raw,
+    pub available: Raw,
 }
 
 #[cfg(test)]
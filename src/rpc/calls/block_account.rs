@@ -31,7 +31,7 @@ impl BlockAccountRequest {
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct BlockAccountResponse {
-    account: Address,
+    pub account: Address,
 }
 
 #[cfg(test)]
@@ -33,7 +33,7 @@ impl BlockConfirmRequest {
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct BlockConfirmResponse {
     #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
-    started: u8,
+    pub started: u8,
 }
 
 #[cfg(test)]
@@ -35,14 +35,14 @@ impl BlockCountRequest {
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct BlockCountResponse {
     #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
-    count: u64,
+    pub count: u64,
 
     #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
-    unchecked: u64,
+    pub unchecked: u64,
 
     #[serde(default)]
     #[serde(serialize_with = "as_str_option", deserialize_with = "from_str_option")]
-    cemented: Option<u64>,
+    pub cemented: Option<u64>,
 }
 
 #[cfg(test)]
@@ -118,7 +118,7 @@ impl BlockCreateRequest {
 #[serde_with::serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BlockCreateResponse {
-    hash: BlockHash,
-    difficulty: Difficulty,
-    block: StateBlock,
+    pub hash: BlockHash,
+    pub difficulty: Difficulty,
+    pub block: StateBlock,
 }
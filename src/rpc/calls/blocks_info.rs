@@ -0,0 +1,186 @@
+use crate::blocks::{BlockHash, Subtype};
+use crate::rpc::calls::{as_str, from_str, StateBlockRequest};
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::rpc::AlwaysTrue;
+use crate::{Address, Raw, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+use serde_with::TimestampSeconds;
+use std::collections::HashMap;
+
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct BlocksInfoRequest {
+    pub hashes: Vec<BlockHash>,
+
+    // We only support json_block being true.
+    #[clap(skip)]
+    json_block: AlwaysTrue,
+
+    /// Include whether each block is still pending for its destination.
+    #[clap(long)]
+    pub pending: bool,
+
+    /// Include the source account of each receive block.
+    #[clap(long)]
+    pub source: bool,
+
+    /// List unknown hashes in `blocks_not_found`, instead of failing the whole request.
+    #[clap(long)]
+    pub include_not_found: bool,
+}
+
+#[async_trait]
+impl RPCRequest for &BlocksInfoRequest {
+    type Response = BlocksInfoResponse;
+
+    fn action(&self) -> &str {
+        "blocks_info"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<BlocksInfoResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl BlocksInfoRequest {
+    pub fn new(hashes: Vec<BlockHash>) -> Self {
+        Self {
+            hashes,
+            json_block: Default::default(),
+            pending: false,
+            source: false,
+            include_not_found: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct BlocksInfoResponse {
+    pub blocks: HashMap<BlockHash, BlocksInfoEntry>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocks_not_found: Option<Vec<BlockHash>>,
+}
+
+/// Only state blocks are supported in `contents`.
+#[serde_with::serde_as]
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct BlocksInfoEntry {
+    pub block_account: Address,
+    pub amount: Raw,
+    pub balance: Raw,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub height: u64,
+
+    #[serde_as(as = "TimestampSeconds<String>")]
+    pub local_timestamp: chrono::DateTime<Utc>,
+
+    /// Zeros when this is the frontier of the account.
+    pub successor: BlockHash,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub confirmed: bool,
+
+    pub contents: StateBlockRequest,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtype: Option<Subtype>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_account: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::{BlockType, Link};
+    use crate::{Signature, Work};
+    use chrono::DateTime;
+    use std::str::FromStr;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "blocks": {
+                "87434F8041869A01C8F6F263B87972D7BA443A72E0A97D7A3FD0CCC2358FD6F9": {
+                    "block_account": "nano_1ipx847tk8o46pwxt5qjdbncjqcbwcc1rrmqnkztrfjy5k7z4imsrata9est",
+                    "amount": "30000000000000000000000000000000000",
+                    "balance": "5606157000000000000000000000000000000",
+                    "height": "58",
+                    "local_timestamp": "0",
+                    "successor": "0000000000000000000000000000000000000000000000000000000000000000",
+                    "confirmed": "true",
+                    "contents": {
+                        "type": "state",
+                        "account": "nano_1ipx847tk8o46pwxt5qjdbncjqcbwcc1rrmqnkztrfjy5k7z4imsrata9est",
+                        "previous": "CE898C131AAEE25E05362F247760F8A3ACF34A9796A5AE0D9204E86B0637965E",
+                        "representative": "nano_1stofnrxuz3cai7ze75o174bpm7scwj9jn3nxsn8ntzg784jf1gzn1jjdkou",
+                        "balance": "5606157000000000000000000000000000000",
+                        "link": "5D1AA8A45F8736519D707FCB375976A7F9AF795091021D7E9C7548D6F45DD8D5",
+                        "link_as_account": "nano_1qato4k7z3spc8gq1zyd8xeqfbzsoxwo36a45ozbrxcatut7up8ohyardu1z",
+                        "signature": "82D41BC16F313E4B2243D14DFFA2FB04679C540C2095FEE7EAE0F2F26880AD56DD48D87A7CC5DD760C5B2D76EE2C205506AA557BF00B60D8DEE312EC7343A501",
+                        "work": "8a142e07a10996d5"
+                    },
+                    "subtype": "send"
+                }
+            }
+        }
+        "#;
+
+        let r = serde_json::from_str::<BlocksInfoResponse>(s).unwrap();
+
+        let account =
+            Address::from_str("nano_1ipx847tk8o46pwxt5qjdbncjqcbwcc1rrmqnkztrfjy5k7z4imsrata9est")
+                .unwrap();
+        let mut blocks = HashMap::new();
+        blocks.insert(
+            BlockHash::from_str("87434F8041869A01C8F6F263B87972D7BA443A72E0A97D7A3FD0CCC2358FD6F9")
+                .unwrap(),
+            BlocksInfoEntry {
+                block_account: account.clone(),
+                amount: Raw::from(30000000000000000000000000000000000),
+                balance: Raw::from(5606157000000000000000000000000000000),
+                height: 58,
+                local_timestamp: DateTime::<Utc>::from_str("1970-01-01T00:00:00Z").unwrap(),
+                successor: BlockHash::zero(),
+                confirmed: true,
+                contents: StateBlockRequest {
+                    block_type: BlockType::State,
+                    account,
+                    previous: BlockHash::from_str(
+                        "CE898C131AAEE25E05362F247760F8A3ACF34A9796A5AE0D9204E86B0637965E",
+                    )
+                    .unwrap(),
+                    representative: Address::from_str(
+                        "nano_1stofnrxuz3cai7ze75o174bpm7scwj9jn3nxsn8ntzg784jf1gzn1jjdkou",
+                    )
+                    .unwrap(),
+                    balance: Raw::from(5606157000000000000000000000000000000),
+                    link: Link::unsure_from_str(
+                        "5D1AA8A45F8736519D707FCB375976A7F9AF795091021D7E9C7548D6F45DD8D5",
+                    )
+                    .unwrap(),
+                    work: Some(Work::from_str("8a142e07a10996d5").unwrap()),
+                    signature: Some(Signature::from_str("82D41BC16F313E4B2243D14DFFA2FB04679C540C2095FEE7EAE0F2F26880AD56DD48D87A7CC5DD760C5B2D76EE2C205506AA557BF00B60D8DEE312EC7343A501").unwrap()),
+                },
+                subtype: Some(Subtype::Send),
+                pending: None,
+                source_account: None,
+            },
+        );
+
+        assert_eq!(
+            r,
+            BlocksInfoResponse {
+                blocks,
+                blocks_not_found: None
+            }
+        )
+    }
+}
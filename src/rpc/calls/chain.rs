@@ -0,0 +1,83 @@
+use crate::blocks::BlockHash;
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::Result;
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+
+/// Follow the previous blocks of an account chain, starting at `block`. See `successors` for the
+/// other direction.
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct ChainRequest {
+    pub block: BlockHash,
+
+    /// Limit the number of results to `count`.
+    #[clap(short, long, default_value = "-1")]
+    pub count: i64,
+
+    /// Skip this many blocks from the start.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(short, long)]
+    pub offset: Option<u64>,
+
+    /// Follow the successors instead, the same as the `successors` action.
+    #[clap(short, long)]
+    pub reverse: bool,
+}
+
+#[async_trait]
+impl RPCRequest for &ChainRequest {
+    type Response = ChainResponse;
+
+    fn action(&self) -> &str {
+        "chain"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<ChainResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl ChainRequest {
+    pub fn new(block: BlockHash, count: i64) -> Self {
+        Self {
+            block,
+            count,
+            offset: None,
+            reverse: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChainResponse {
+    pub blocks: Vec<BlockHash>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "blocks" : [
+                "000D1BAEC8EC208142C99059B393051BAC8380F9B5A2E6B2489A277D81789F3F"
+            ]
+        }
+        "#;
+
+        let r = serde_json::from_str::<ChainResponse>(s).unwrap();
+
+        assert_eq!(
+            r,
+            ChainResponse {
+                blocks: vec![BlockHash::from_str(
+                    "000D1BAEC8EC208142C99059B393051BAC8380F9B5A2E6B2489A277D81789F3F"
+                )
+                .unwrap()]
+            }
+        )
+    }
+}
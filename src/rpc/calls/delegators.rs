@@ -0,0 +1,84 @@
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::{Address, Raw, Result};
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct DelegatorsRequest {
+    /// The representative.
+    pub account: Address,
+
+    /// Limit the number of results to `count`. All delegators are returned by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(short, long)]
+    pub count: Option<u64>,
+
+    /// Start after this delegator. Useful for pagination.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(short, long)]
+    pub start: Option<Address>,
+}
+
+#[async_trait]
+impl RPCRequest for &DelegatorsRequest {
+    type Response = DelegatorsResponse;
+
+    fn action(&self) -> &str {
+        "delegators"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<DelegatorsResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl DelegatorsRequest {
+    pub fn new(account: Address) -> Self {
+        Self {
+            account,
+            count: None,
+            start: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct DelegatorsResponse {
+    /// The balance of each delegator.
+    pub delegators: HashMap<Address, Raw>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "delegators": {
+                "nano_13bqhi1cdqq8yb9szneoc38qk899d58i5rcrgdk5mkdm86hekpoez3zxw5sd": "500000000000000000000000000000000000",
+                "nano_17k6ug685154an8gri9whhe5kb5z1mf5w6y39gokc1657sh95fegm8ht1zpn": "961647970820730000000000000000000000"
+            }
+        }
+        "#;
+
+        let r = serde_json::from_str::<DelegatorsResponse>(s).unwrap();
+
+        let mut delegators: HashMap<Address, Raw> = HashMap::new();
+        delegators.insert(
+            Address::from_str("nano_13bqhi1cdqq8yb9szneoc38qk899d58i5rcrgdk5mkdm86hekpoez3zxw5sd")
+                .unwrap(),
+            Raw::from(500000000000000000000000000000000000),
+        );
+        delegators.insert(
+            Address::from_str("nano_17k6ug685154an8gri9whhe5kb5z1mf5w6y39gokc1657sh95fegm8ht1zpn")
+                .unwrap(),
+            Raw::from(961647970820730000000000000000000000),
+        );
+
+        assert_eq!(r, DelegatorsResponse { delegators })
+    }
+}
@@ -0,0 +1,54 @@
+use crate::rpc::calls::{as_str, from_str};
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::{Address, Result};
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct DelegatorsCountRequest {
+    /// The representative.
+    pub account: Address,
+}
+
+#[async_trait]
+impl RPCRequest for &DelegatorsCountRequest {
+    type Response = DelegatorsCountResponse;
+
+    fn action(&self) -> &str {
+        "delegators_count"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<DelegatorsCountResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl DelegatorsCountRequest {
+    pub fn new(account: Address) -> Self {
+        Self { account }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct DelegatorsCountResponse {
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub count: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "count": "2"
+        }
+        "#;
+
+        let r = serde_json::from_str::<DelegatorsCountResponse>(s).unwrap();
+
+        assert_eq!(r, DelegatorsCountResponse { count: 2 })
+    }
+}
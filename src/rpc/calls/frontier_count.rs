@@ -0,0 +1,52 @@
+use crate::rpc::calls::{as_str, from_str};
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::Result;
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Serialize, Deserialize, Clap)]
+pub struct FrontierCountRequest {}
+
+#[async_trait]
+impl RPCRequest for &FrontierCountRequest {
+    type Response = FrontierCountResponse;
+
+    fn action(&self) -> &str {
+        "frontier_count"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<FrontierCountResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl FrontierCountRequest {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct FrontierCountResponse {
+    /// The number of accounts in the ledger.
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub count: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "count": "920471"
+        }
+        "#;
+
+        let r = serde_json::from_str::<FrontierCountResponse>(s).unwrap();
+
+        assert_eq!(r, FrontierCountResponse { count: 920471 })
+    }
+}
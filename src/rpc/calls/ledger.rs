@@ -0,0 +1,156 @@
+use crate::blocks::BlockHash;
+use crate::rpc::calls::{as_str, from_str};
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::{Address, Raw, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+use serde_with::TimestampSeconds;
+use std::collections::HashMap;
+
+/// Accounts in the ledger, in order of their public keys. This needs `enable_control` on the
+/// node.
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct LedgerRequest {
+    /// Start from this account. Useful for pagination.
+    pub account: Address,
+
+    /// Limit the number of results to `count`.
+    #[clap(short, long, default_value = "1")]
+    pub count: u64,
+
+    /// Include the representative of each account.
+    #[clap(long)]
+    pub representative: bool,
+
+    /// Include the voting weight of each account.
+    #[clap(long)]
+    pub weight: bool,
+
+    /// Include the pending balance of each account.
+    #[clap(long)]
+    pub pending: bool,
+
+    /// Only include accounts modified since this UNIX timestamp.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(short, long)]
+    pub modified_since: Option<u64>,
+
+    /// Sort by balance, largest first, instead of by public key.
+    #[clap(long)]
+    pub sorting: bool,
+
+    /// Only include accounts with at least this balance in raw.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(short, long)]
+    pub threshold: Option<Raw>,
+}
+
+#[async_trait]
+impl RPCRequest for &LedgerRequest {
+    type Response = LedgerResponse;
+
+    fn action(&self) -> &str {
+        "ledger"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<LedgerResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl LedgerRequest {
+    pub fn new(account: Address, count: u64) -> Self {
+        Self {
+            account,
+            count,
+            representative: false,
+            weight: false,
+            pending: false,
+            modified_since: None,
+            sorting: false,
+            threshold: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct LedgerResponse {
+    pub accounts: HashMap<Address, LedgerEntry>,
+}
+
+#[serde_with::serde_as]
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct LedgerEntry {
+    pub frontier: BlockHash,
+    pub open_block: BlockHash,
+    pub representative_block: BlockHash,
+    pub balance: Raw,
+
+    #[serde_as(as = "TimestampSeconds<String>")]
+    pub modified_timestamp: chrono::DateTime<Utc>,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub block_count: u64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub representative: Option<Address>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<Raw>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending: Option<Raw>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+    use std::str::FromStr;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "accounts": {
+                "nano_11119gbh8hb4hj1duf7fdtfyf5s75okzxdgupgpgm1bj78ex3kgy7frt3s9n": {
+                    "frontier": "E71AF3E9DD86BBD8B4620EFA63E065B34D358CFC091ACB4E103B965F95783321",
+                    "open_block": "643B77F1ECEFBDBE1CC909872964C1DBBE23A6149BD3CEF2B50B76044659B60F",
+                    "representative_block": "643B77F1ECEFBDBE1CC909872964C1DBBE23A6149BD3CEF2B50B76044659B60F",
+                    "balance": "0",
+                    "modified_timestamp": "1511476234",
+                    "block_count": "2"
+                }
+            }
+        }
+        "#;
+
+        let r = serde_json::from_str::<LedgerResponse>(s).unwrap();
+
+        let open =
+            BlockHash::from_str("643B77F1ECEFBDBE1CC909872964C1DBBE23A6149BD3CEF2B50B76044659B60F")
+                .unwrap();
+        let mut accounts = HashMap::new();
+        accounts.insert(
+            Address::from_str("nano_11119gbh8hb4hj1duf7fdtfyf5s75okzxdgupgpgm1bj78ex3kgy7frt3s9n")
+                .unwrap(),
+            LedgerEntry {
+                frontier: BlockHash::from_str(
+                    "E71AF3E9DD86BBD8B4620EFA63E065B34D358CFC091ACB4E103B965F95783321",
+                )
+                .unwrap(),
+                open_block: open.clone(),
+                representative_block: open,
+                balance: Raw::zero(),
+                modified_timestamp: DateTime::<Utc>::from_str("2017-11-23T22:30:34Z").unwrap(),
+                block_count: 2,
+                representative: None,
+                weight: None,
+                pending: None,
+            },
+        );
+
+        assert_eq!(r, LedgerResponse { accounts })
+    }
+}
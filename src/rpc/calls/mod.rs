@@ -9,6 +9,7 @@ mod account_weight;
 mod accounts_balances;
 mod accounts_frontiers;
 mod accounts_pending;
+mod accounts_representatives;
 mod active_difficulty;
 mod available_supply;
 mod block_account;
@@ -16,8 +17,29 @@ mod block_confirm;
 mod block_count;
 mod block_create;
 mod block_info;
+mod blocks_info;
+mod chain;
+mod delegators;
+mod delegators_count;
+mod frontier_count;
+mod ledger;
+mod nano_to_raw;
 mod peers;
+mod pending_exists;
 mod process;
+mod raw_to_nano;
+mod receivable;
+mod representatives;
+mod representatives_online;
+mod sign;
+mod successors;
+mod telemetry;
+mod unchecked;
+mod uptime;
+mod validate_account_number;
+mod version;
+mod work_cancel;
+mod work_generate;
 mod work_validate;
 
 #[cfg(feature = "node")]
@@ -37,9 +59,14 @@ pub use account_info::{AccountInfoRequest, AccountInfoResponse};
 pub use account_key::{AccountKeyRequest, AccountKeyResponse};
 pub use account_representative::{AccountRepresentativeRequest, AccountRepresentativeResponse};
 pub use account_weight::{AccountWeightRequest, AccountWeightResponse};
-pub use accounts_balances::{AccountsBalancesRequest, AccountsBalancesResponse};
+pub use accounts_balances::{
+    AccountsBalancesEntry, AccountsBalancesRequest, AccountsBalancesResponse,
+};
 pub use accounts_frontiers::{AccountsFrontiersRequest, AccountsFrontiersResponse};
-pub use accounts_pending::{AccountsPendingRequest, AccountsPendingResponse};
+pub use accounts_pending::{AccountsPendingRequest, AccountsPendingResponse, BlockEntry};
+pub use accounts_representatives::{
+    AccountsRepresentativesRequest, AccountsRepresentativesResponse,
+};
 pub use active_difficulty::{ActiveDifficultyRequest, ActiveDifficultyResponse};
 pub use available_supply::{AvailableSupplyRequest, AvailableSupplyResponse};
 pub use block_account::{BlockAccountRequest, BlockAccountResponse};
@@ -47,13 +74,36 @@ pub use block_confirm::{BlockConfirmRequest, BlockConfirmResponse};
 pub use block_count::{BlockCountRequest, BlockCountResponse};
 pub use block_create::{BlockCreateRequest, BlockCreateResponse};
 pub use block_info::{BlockInfoRequest, BlockInfoResponse};
+pub use blocks_info::{BlocksInfoEntry, BlocksInfoRequest, BlocksInfoResponse};
+pub use chain::{ChainRequest, ChainResponse};
 use clap::Clap;
-pub use peers::{DetailedPeerInfo, Peers, PeersRequest, PeersResponse};
-pub use process::{ProcessRequest, ProcessResponse};
+pub use delegators::{DelegatorsRequest, DelegatorsResponse};
+pub use delegators_count::{DelegatorsCountRequest, DelegatorsCountResponse};
+pub use frontier_count::{FrontierCountRequest, FrontierCountResponse};
+pub use ledger::{LedgerEntry, LedgerRequest, LedgerResponse};
+pub use nano_to_raw::{NanoToRawRequest, NanoToRawResponse};
+pub use peers::{DetailedPeerInfo, NetType, Peers, PeersRequest, PeersResponse};
+pub use pending_exists::{PendingExistsRequest, PendingExistsResponse};
+pub use process::{ProcessRequest, ProcessResponse, StateBlockRequest};
+pub use raw_to_nano::{RawToNanoRequest, RawToNanoResponse};
+pub use receivable::{ReceivableRequest, ReceivableResponse};
+pub use representatives::{RepresentativesRequest, RepresentativesResponse};
+pub use representatives_online::{
+    RepresentativeWeight, RepresentativesOnlineRequest, RepresentativesOnlineResponse,
+};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+pub use sign::{SignRequest, SignResponse};
 use std::fmt::Display;
 use std::ops::Deref;
 use std::str::FromStr;
+pub use successors::{SuccessorsRequest, SuccessorsResponse};
+pub use telemetry::{TelemetryRequest, TelemetryResponse};
+pub use unchecked::{UncheckedRequest, UncheckedResponse};
+pub use uptime::{UptimeRequest, UptimeResponse};
+pub use validate_account_number::{ValidateAccountNumberRequest, ValidateAccountNumberResponse};
+pub use version::{VersionRequest, VersionResponse};
+pub use work_cancel::{WorkCancelRequest, WorkCancelResponse};
+pub use work_generate::{WorkGenerateRequest, WorkGenerateResponse};
 pub use work_validate::{WorkValidateRequest, WorkValidateResponse};
 
 #[cfg(any(feature = "node"))]
@@ -87,6 +137,28 @@ pub enum RpcCommand {
     BlockConfirm(BlockConfirmRequest),
     Peers(PeersRequest),
     Process(ProcessRequest),
+    AccountsRepresentatives(AccountsRepresentativesRequest),
+    BlocksInfo(BlocksInfoRequest),
+    Chain(ChainRequest),
+    Delegators(DelegatorsRequest),
+    DelegatorsCount(DelegatorsCountRequest),
+    FrontierCount(FrontierCountRequest),
+    Ledger(LedgerRequest),
+    NanoToRaw(NanoToRawRequest),
+    PendingExists(PendingExistsRequest),
+    RawToNano(RawToNanoRequest),
+    Receivable(ReceivableRequest),
+    Representatives(RepresentativesRequest),
+    RepresentativesOnline(RepresentativesOnlineRequest),
+    Sign(SignRequest),
+    Successors(SuccessorsRequest),
+    Telemetry(TelemetryRequest),
+    Unchecked(UncheckedRequest),
+    Uptime(UptimeRequest),
+    ValidateAccountNumber(ValidateAccountNumberRequest),
+    Version(VersionRequest),
+    WorkCancel(WorkCancelRequest),
+    WorkGenerate(WorkGenerateRequest),
     WorkValidate(WorkValidateRequest),
 }
 
@@ -117,7 +189,7 @@ where
 pub fn as_str<V, S>(v: &V, serializer: S) -> std::result::Result<S::Ok, S::Error>
 where
     S: Serializer,
-    V: Display,
+    V: ToString,
 {
     serializer.serialize_str(v.to_string().as_str())
 }
@@ -133,6 +205,29 @@ where
     }
 }
 
+/// The node sends some booleans as `"1"` or `"0"`.
+pub(crate) fn from_int_bool<'de, D>(deserializer: D) -> std::result::Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    match s.as_str() {
+        "1" => Ok(true),
+        "0" => Ok(false),
+        s => Err(de::Error::custom(format!(
+            "Expected \"1\" or \"0\", got {:?}",
+            s
+        ))),
+    }
+}
+
+pub(crate) fn as_int_bool<S>(v: &bool, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(if *v { "1" } else { "0" })
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct AlwaysTrue(bool);
 
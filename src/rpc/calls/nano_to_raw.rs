@@ -0,0 +1,60 @@
+use crate::rpc::calls::{as_str, from_str};
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::units::Mnano;
+use crate::{Raw, Result};
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+
+/// Convert from Nano, which the node treats as Mnano, i.e. 10^30 raw, to raw.
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct NanoToRawRequest {
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub amount: Mnano,
+}
+
+#[async_trait]
+impl RPCRequest for &NanoToRawRequest {
+    type Response = NanoToRawResponse;
+
+    fn action(&self) -> &str {
+        "nano_to_raw"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<NanoToRawResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl NanoToRawRequest {
+    pub fn new(amount: Mnano) -> Self {
+        Self { amount }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct NanoToRawResponse {
+    pub amount: Raw,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "amount": "1000000000000000000000000000000"
+        }
+        "#;
+
+        let r = serde_json::from_str::<NanoToRawResponse>(s).unwrap();
+
+        assert_eq!(
+            r,
+            NanoToRawResponse {
+                amount: Raw::from(1000000000000000000000000000000),
+            }
+        )
+    }
+}
@@ -35,6 +35,14 @@ impl RPCRequest for &PeersRequest {
     }
 }
 
+impl PeersRequest {
+    pub fn new(peer_details: bool) -> Self {
+        Self {
+            peer_details: Some(peer_details),
+        }
+    }
+}
+
 #[cfg(feature = "node")]
 #[async_trait]
 impl NodeHandler for &PeersRequest {
@@ -53,7 +61,7 @@ impl NodeHandler for &PeersRequest {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PeersResponse {
     /// The type in peers depends on the value set in [PeersRequest::peer_details].
-    peers: Peers,
+    pub peers: Peers,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -66,12 +74,12 @@ pub enum Peers {
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct DetailedPeerInfo {
     #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
-    protocol_version: Version,
+    pub protocol_version: Version,
 
-    node_id: String, // TODO: NodeId type. It might be used in the node handshake!
+    pub node_id: String, // TODO: NodeId type. It might be used in the node handshake!
 
     #[serde(rename = "type")]
-    net_type: NetType,
+    pub net_type: NetType,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
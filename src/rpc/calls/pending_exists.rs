@@ -0,0 +1,65 @@
+use crate::blocks::BlockHash;
+use crate::rpc::calls::{as_int_bool, from_int_bool};
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::Result;
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct PendingExistsRequest {
+    /// The send block.
+    pub hash: BlockHash,
+
+    #[clap(long)]
+    pub include_active: bool,
+
+    #[clap(long)]
+    pub include_only_confirmed: bool,
+}
+
+#[async_trait]
+impl RPCRequest for &PendingExistsRequest {
+    type Response = PendingExistsResponse;
+
+    fn action(&self) -> &str {
+        "pending_exists"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<PendingExistsResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl PendingExistsRequest {
+    pub fn new(hash: BlockHash) -> Self {
+        Self {
+            hash,
+            include_active: false,
+            include_only_confirmed: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct PendingExistsResponse {
+    #[serde(deserialize_with = "from_int_bool", serialize_with = "as_int_bool")]
+    pub exists: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "exists": "1"
+        }
+        "#;
+
+        let r = serde_json::from_str::<PendingExistsResponse>(s).unwrap();
+
+        assert_eq!(r, PendingExistsResponse { exists: true })
+    }
+}
@@ -1,5 +1,5 @@
 use crate::blocks::{deserialize_to_unsure_link, BlockType, StateBlock};
-use crate::blocks::{BlockHash, Link, Subtype};
+use crate::blocks::{BlockHash, Link, Previous, Subtype};
 use crate::rpc::client::{RPCClient, RPCRequest};
 use crate::rpc::AlwaysTrue;
 use crate::{Address, Raw, Result, Signature, Work};
@@ -64,10 +64,104 @@ impl RPCRequest for &ProcessRequest {
 }
 
 impl ProcessRequest {
-    pub fn new(_subtype: Subtype, _block: StateBlock) -> Self {
-        todo!()
+    pub fn new(subtype: Subtype, block: StateBlock) -> Self {
+        Self {
+            json_block: AlwaysTrue::default(),
+            subtype,
+            block: StateBlockRequest::from(block),
+        }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ProcessResponse {}
+impl From<StateBlock> for StateBlockRequest {
+    fn from(block: StateBlock) -> Self {
+        let previous = match block.previous {
+            Previous::Block(hash) => hash,
+            Previous::Open => BlockHash::zero(),
+        };
+        Self {
+            block_type: BlockType::State,
+            account: block.account.to_address(),
+            previous,
+            representative: block.representative.to_address(),
+            balance: block.balance,
+            // The link is always sent as hex, since the node works out what it refers to.
+            link: block.link.to_unsure(),
+            work: block.work,
+            signature: block.signature,
+        }
+    }
+}
+
+/// The link stays [Link::Unsure], since the JSON form doesn't say what it refers to.
+impl From<StateBlockRequest> for StateBlock {
+    fn from(block: StateBlockRequest) -> Self {
+        let previous = if block.previous == BlockHash::zero() {
+            Previous::Open
+        } else {
+            Previous::Block(block.previous)
+        };
+        let mut state_block = StateBlock::new(
+            block.account.to_public(),
+            previous,
+            block.representative.to_public(),
+            block.balance,
+            block.link.to_unsure(),
+        );
+        state_block.work = block.work;
+        state_block.signature = block.signature;
+        state_block
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ProcessResponse {
+    pub hash: BlockHash,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Seed;
+    use std::str::FromStr;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "hash": "E2FB233EF4554077A7BF1AA85851D5BF0B36965D2B0FB504B2BC778AB89917D3"
+        }
+        "#;
+
+        let r = serde_json::from_str::<ProcessResponse>(s).unwrap();
+
+        assert_eq!(
+            r,
+            ProcessResponse {
+                hash: BlockHash::from_str(
+                    "E2FB233EF4554077A7BF1AA85851D5BF0B36965D2B0FB504B2BC778AB89917D3"
+                )
+                .unwrap()
+            }
+        )
+    }
+
+    #[test]
+    fn state_block_round_trip() {
+        let account = Seed::zero().derive(0).to_public().unwrap();
+        let mut block = StateBlock::new(
+            account.clone(),
+            Previous::Open,
+            account,
+            Raw::from(1),
+            Link::unsure_from_str(
+                "5D1AA8A45F8736519D707FCB375976A7F9AF795091021D7E9C7548D6F45DD8D5",
+            )
+            .unwrap(),
+        );
+        block.work = Some(Work::from_str("8a142e07a10996d5").unwrap());
+
+        let request = StateBlockRequest::from(block.clone());
+        assert_eq!(request.previous, BlockHash::zero());
+        assert_eq!(StateBlock::from(request), block);
+    }
+}
@@ -0,0 +1,61 @@
+use crate::rpc::calls::{as_str, from_str};
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::units::Mnano;
+use crate::{Raw, Result};
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+
+/// Convert from raw to Nano, which the node treats as Mnano, i.e. 10^30 raw.
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct RawToNanoRequest {
+    pub amount: Raw,
+}
+
+#[async_trait]
+impl RPCRequest for &RawToNanoRequest {
+    type Response = RawToNanoResponse;
+
+    fn action(&self) -> &str {
+        "raw_to_nano"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<RawToNanoResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl RawToNanoRequest {
+    pub fn new(amount: Raw) -> Self {
+        Self { amount }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct RawToNanoResponse {
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub amount: Mnano,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "amount": "1.5"
+        }
+        "#;
+
+        let r = serde_json::from_str::<RawToNanoResponse>(s).unwrap();
+
+        assert_eq!(
+            r,
+            RawToNanoResponse {
+                amount: Mnano::from_str("1.5").unwrap(),
+            }
+        )
+    }
+}
@@ -0,0 +1,133 @@
+use crate::blocks::BlockHash;
+use crate::rpc::calls::BlockEntry;
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::{Address, Raw, Result};
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The blocks waiting to be received by one account. See `accounts-pending` for many accounts.
+#[derive(Debug, Serialize, Deserialize, Clap, Clone)]
+pub struct ReceivableRequest {
+    pub account: Address,
+
+    /// Limit the number of results to `count`.
+    #[clap(short, long, default_value = "1")]
+    pub count: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(short, long)]
+    pub threshold: Option<Raw>,
+
+    #[clap(long)]
+    pub source: bool,
+
+    #[clap(long)]
+    pub include_active: bool,
+
+    #[clap(long)]
+    pub sorting: bool,
+
+    #[clap(long)]
+    pub include_only_confirmed: bool,
+}
+
+#[async_trait]
+impl RPCRequest for &ReceivableRequest {
+    type Response = ReceivableResponse;
+
+    fn action(&self) -> &str {
+        "receivable"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<Self::Response> {
+        client.rpc(self).await
+    }
+}
+
+impl ReceivableRequest {
+    pub fn new(account: Address, count: u64) -> Self {
+        Self {
+            account,
+            count,
+            threshold: None,
+            source: false,
+            include_active: false,
+            sorting: false,
+            include_only_confirmed: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum ReceivableResponse {
+    OnlyBlockHash {
+        blocks: Vec<BlockHash>,
+    },
+    Threshold {
+        blocks: HashMap<BlockHash, Raw>,
+    },
+    Source {
+        blocks: HashMap<BlockHash, BlockEntry>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn decode_hashes() {
+        let s = r#" {
+            "blocks" : [
+                "000D1BAEC8EC208142C99059B393051BAC8380F9B5A2E6B2489A277D81789F3F"
+            ]
+        }
+        "#;
+
+        let r = serde_json::from_str::<ReceivableResponse>(s).unwrap();
+
+        assert_eq!(
+            r,
+            ReceivableResponse::OnlyBlockHash {
+                blocks: vec![BlockHash::from_str(
+                    "000D1BAEC8EC208142C99059B393051BAC8380F9B5A2E6B2489A277D81789F3F"
+                )
+                .unwrap()]
+            }
+        );
+    }
+
+    #[test]
+    fn decode_source() {
+        let s = r#" {
+            "blocks" : {
+                "000D1BAEC8EC208142C99059B393051BAC8380F9B5A2E6B2489A277D81789F3F": {
+                    "amount": "6000000000000000000000000000000",
+                    "source": "nano_3dcfozsmekr1tr9skf1oa5wbgmxt81qepfdnt7zicq5x3hk65fg4fqj58mbr"
+                }
+            }
+        }
+        "#;
+
+        let r = serde_json::from_str::<ReceivableResponse>(s).unwrap();
+
+        let mut blocks = HashMap::new();
+        blocks.insert(
+            BlockHash::from_str("000D1BAEC8EC208142C99059B393051BAC8380F9B5A2E6B2489A277D81789F3F")
+                .unwrap(),
+            BlockEntry {
+                amount: Raw::from(6000000000000000000000000000000),
+                source: Address::from_str(
+                    "nano_3dcfozsmekr1tr9skf1oa5wbgmxt81qepfdnt7zicq5x3hk65fg4fqj58mbr",
+                )
+                .unwrap(),
+            },
+        );
+
+        assert_eq!(r, ReceivableResponse::Source { blocks });
+    }
+}
@@ -0,0 +1,79 @@
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::{Address, Raw, Result};
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Default, Serialize, Deserialize, Clap)]
+pub struct RepresentativesRequest {
+    /// Limit the number of results to `count`. All representatives are returned by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(short, long)]
+    pub count: Option<u64>,
+
+    /// Sort by weight, largest first.
+    #[clap(long)]
+    pub sorting: bool,
+}
+
+#[async_trait]
+impl RPCRequest for &RepresentativesRequest {
+    type Response = RepresentativesResponse;
+
+    fn action(&self) -> &str {
+        "representatives"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<RepresentativesResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl RepresentativesRequest {
+    pub fn new() -> Self {
+        Self {
+            count: None,
+            sorting: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct RepresentativesResponse {
+    /// The voting weight of each representative.
+    pub representatives: HashMap<Address, Raw>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "representatives": {
+                "nano_1111111111111111111111111111111111111111111111111117353trpda": "3822372327060170000000000000000000000",
+                "nano_3t6k35gi95xu6tergt6p69ck76ogmitsa8mnijtpxm9fkcm736xtoncuohr3": "0"
+            }
+        }
+        "#;
+
+        let r = serde_json::from_str::<RepresentativesResponse>(s).unwrap();
+
+        let mut representatives: HashMap<Address, Raw> = HashMap::new();
+        representatives.insert(
+            Address::from_str("nano_1111111111111111111111111111111111111111111111111117353trpda")
+                .unwrap(),
+            Raw::from(3822372327060170000000000000000000000),
+        );
+        representatives.insert(
+            Address::from_str("nano_3t6k35gi95xu6tergt6p69ck76ogmitsa8mnijtpxm9fkcm736xtoncuohr3")
+                .unwrap(),
+            Raw::zero(),
+        );
+
+        assert_eq!(r, RepresentativesResponse { representatives })
+    }
+}
@@ -0,0 +1,116 @@
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::{Address, Raw, Result};
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct RepresentativesOnlineRequest {
+    /// Include the voting weight of each representative.
+    #[clap(long)]
+    pub weight: bool,
+
+    /// Only include these representatives.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(short, long)]
+    pub accounts: Option<Vec<Address>>,
+}
+
+#[async_trait]
+impl RPCRequest for &RepresentativesOnlineRequest {
+    type Response = RepresentativesOnlineResponse;
+
+    fn action(&self) -> &str {
+        "representatives_online"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<RepresentativesOnlineResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl RepresentativesOnlineRequest {
+    pub fn new(weight: bool) -> Self {
+        Self {
+            weight,
+            accounts: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum RepresentativesOnlineResponse {
+    Accounts {
+        representatives: Vec<Address>,
+    },
+    Weight {
+        representatives: HashMap<Address, RepresentativeWeight>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct RepresentativeWeight {
+    pub weight: Raw,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn decode_accounts() {
+        let s = r#" {
+            "representatives": [
+                "nano_1111111111111111111111111111111111111111111111111117353trpda",
+                "nano_3t6k35gi95xu6tergt6p69ck76ogmitsa8mnijtpxm9fkcm736xtoncuohr3"
+            ]
+        }
+        "#;
+
+        let r = serde_json::from_str::<RepresentativesOnlineResponse>(s).unwrap();
+
+        assert_eq!(
+            r,
+            RepresentativesOnlineResponse::Accounts {
+                representatives: vec![
+                    Address::from_str(
+                        "nano_1111111111111111111111111111111111111111111111111117353trpda"
+                    )
+                    .unwrap(),
+                    Address::from_str(
+                        "nano_3t6k35gi95xu6tergt6p69ck76ogmitsa8mnijtpxm9fkcm736xtoncuohr3"
+                    )
+                    .unwrap(),
+                ]
+            }
+        )
+    }
+
+    #[test]
+    fn decode_weight() {
+        let s = r#" {
+            "representatives": {
+                "nano_114nk4rwjctu6n6tr6g6ps61g1w3hdpjxfas4xj1tq6i8jyomc5d858xr1xi": {
+                    "weight": "150462654614686936429917024683496890"
+                }
+            }
+        }
+        "#;
+
+        let r = serde_json::from_str::<RepresentativesOnlineResponse>(s).unwrap();
+
+        let mut representatives = HashMap::new();
+        representatives.insert(
+            Address::from_str("nano_114nk4rwjctu6n6tr6g6ps61g1w3hdpjxfas4xj1tq6i8jyomc5d858xr1xi")
+                .unwrap(),
+            RepresentativeWeight {
+                weight: Raw::from(150462654614686936429917024683496890),
+            },
+        );
+
+        assert_eq!(r, RepresentativesOnlineResponse::Weight { representatives })
+    }
+}
@@ -0,0 +1,110 @@
+use crate::rpc::calls::StateBlockRequest;
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::rpc::AlwaysTrue;
+use crate::{Private, Result, Signature};
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+
+/// Sign a block with a private key on the node. Prefer signing locally, so the key never leaves
+/// this machine.
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct SignRequest {
+    // We only support json_block being true.
+    #[clap(skip)]
+    json_block: AlwaysTrue,
+
+    #[clap(short, long, env = "FEELESS_PRIVATE_KEY")]
+    pub key: Private,
+
+    #[clap(flatten)]
+    pub block: StateBlockRequest,
+}
+
+#[async_trait]
+impl RPCRequest for &SignRequest {
+    type Response = SignResponse;
+
+    fn action(&self) -> &str {
+        "sign"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<SignResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl SignRequest {
+    pub fn new(key: Private, block: StateBlockRequest) -> Self {
+        Self {
+            json_block: Default::default(),
+            key,
+            block,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SignResponse {
+    pub signature: Signature,
+    pub block: StateBlockRequest,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::{BlockHash, BlockType, Link};
+    use crate::{Address, Raw};
+    use std::str::FromStr;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "signature": "82D41BC16F313E4B2243D14DFFA2FB04679C540C2095FEE7EAE0F2F26880AD56DD48D87A7CC5DD760C5B2D76EE2C205506AA557BF00B60D8DEE312EC7343A501",
+            "block": {
+                "type": "state",
+                "account": "nano_1ipx847tk8o46pwxt5qjdbncjqcbwcc1rrmqnkztrfjy5k7z4imsrata9est",
+                "previous": "CE898C131AAEE25E05362F247760F8A3ACF34A9796A5AE0D9204E86B0637965E",
+                "representative": "nano_1stofnrxuz3cai7ze75o174bpm7scwj9jn3nxsn8ntzg784jf1gzn1jjdkou",
+                "balance": "5606157000000000000000000000000000000",
+                "link": "5D1AA8A45F8736519D707FCB375976A7F9AF795091021D7E9C7548D6F45DD8D5",
+                "link_as_account": "nano_1qato4k7z3spc8gq1zyd8xeqfbzsoxwo36a45ozbrxcatut7up8ohyardu1z",
+                "signature": "82D41BC16F313E4B2243D14DFFA2FB04679C540C2095FEE7EAE0F2F26880AD56DD48D87A7CC5DD760C5B2D76EE2C205506AA557BF00B60D8DEE312EC7343A501",
+                "work": "8a142e07a10996d5"
+            }
+        }
+        "#;
+
+        let r = serde_json::from_str::<SignResponse>(s).unwrap();
+
+        let signature = Signature::from_str("82D41BC16F313E4B2243D14DFFA2FB04679C540C2095FEE7EAE0F2F26880AD56DD48D87A7CC5DD760C5B2D76EE2C205506AA557BF00B60D8DEE312EC7343A501").unwrap();
+        assert_eq!(
+            r,
+            SignResponse {
+                signature: signature.clone(),
+                block: StateBlockRequest {
+                    block_type: BlockType::State,
+                    account: Address::from_str(
+                        "nano_1ipx847tk8o46pwxt5qjdbncjqcbwcc1rrmqnkztrfjy5k7z4imsrata9est"
+                    )
+                    .unwrap(),
+                    previous: BlockHash::from_str(
+                        "CE898C131AAEE25E05362F247760F8A3ACF34A9796A5AE0D9204E86B0637965E"
+                    )
+                    .unwrap(),
+                    representative: Address::from_str(
+                        "nano_1stofnrxuz3cai7ze75o174bpm7scwj9jn3nxsn8ntzg784jf1gzn1jjdkou"
+                    )
+                    .unwrap(),
+                    balance: Raw::from(5606157000000000000000000000000000000),
+                    link: Link::unsure_from_str(
+                        "5D1AA8A45F8736519D707FCB375976A7F9AF795091021D7E9C7548D6F45DD8D5"
+                    )
+                    .unwrap(),
+                    work: Some(crate::Work::from_str("8a142e07a10996d5").unwrap()),
+                    signature: Some(signature),
+                },
+            }
+        )
+    }
+}
@@ -0,0 +1,89 @@
+use crate::blocks::BlockHash;
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::Result;
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+
+/// Follow the successors of an account chain, starting at `block`.
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct SuccessorsRequest {
+    pub block: BlockHash,
+
+    /// Limit the number of results to `count`.
+    #[clap(short, long, default_value = "-1")]
+    pub count: i64,
+
+    /// Skip this many blocks from the start.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(short, long)]
+    pub offset: Option<u64>,
+
+    /// Follow the previous blocks instead, the same as the `chain` action.
+    #[clap(short, long)]
+    pub reverse: bool,
+}
+
+#[async_trait]
+impl RPCRequest for &SuccessorsRequest {
+    type Response = SuccessorsResponse;
+
+    fn action(&self) -> &str {
+        "successors"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<SuccessorsResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl SuccessorsRequest {
+    pub fn new(block: BlockHash, count: i64) -> Self {
+        Self {
+            block,
+            count,
+            offset: None,
+            reverse: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SuccessorsResponse {
+    pub blocks: Vec<BlockHash>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "blocks" : [
+                "991CF190094C00F0B68E2E5F75F6BEE95A2E0BD93CEAA4A6734DB9F19B728948",
+                "A170D51B94E00371ACE76E35AC81DC9405D5D04D4CEBC399AEACE07AE05DD293"
+            ]
+        }
+        "#;
+
+        let r = serde_json::from_str::<SuccessorsResponse>(s).unwrap();
+
+        assert_eq!(
+            r,
+            SuccessorsResponse {
+                blocks: vec![
+                    BlockHash::from_str(
+                        "991CF190094C00F0B68E2E5F75F6BEE95A2E0BD93CEAA4A6734DB9F19B728948"
+                    )
+                    .unwrap(),
+                    BlockHash::from_str(
+                        "A170D51B94E00371ACE76E35AC81DC9405D5D04D4CEBC399AEACE07AE05DD293"
+                    )
+                    .unwrap()
+                ]
+            }
+        )
+    }
+}
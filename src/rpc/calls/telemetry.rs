@@ -0,0 +1,149 @@
+use crate::blocks::BlockHash;
+use crate::rpc::calls::{as_str, from_str};
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::{Difficulty, Result};
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+
+/// Telemetry averaged over the peers of the node, or from one peer when `address` and `port` are
+/// set.
+#[derive(Debug, Default, Serialize, Deserialize, Clap)]
+pub struct TelemetryRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(short, long, requires = "port")]
+    pub address: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(short, long, requires = "address")]
+    pub port: Option<u16>,
+}
+
+#[async_trait]
+impl RPCRequest for &TelemetryRequest {
+    type Response = TelemetryResponse;
+
+    fn action(&self) -> &str {
+        "telemetry"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<TelemetryResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl TelemetryRequest {
+    pub fn new() -> Self {
+        Self {
+            address: None,
+            port: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TelemetryResponse {
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub block_count: u64,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub cemented_count: u64,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub unchecked_count: u64,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub account_count: u64,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub bandwidth_cap: u64,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub peer_count: u32,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub protocol_version: u8,
+
+    /// In seconds.
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub uptime: u64,
+
+    pub genesis_block: BlockHash,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub major_version: u8,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub minor_version: u8,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub patch_version: u8,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub pre_release_version: u8,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub maker: u8,
+
+    /// Milliseconds since the UNIX epoch.
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub timestamp: u64,
+
+    pub active_difficulty: Difficulty,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "block_count": "5777903",
+            "cemented_count": "688819",
+            "unchecked_count": "443468",
+            "account_count": "620750",
+            "bandwidth_cap": "1572864",
+            "peer_count": "32",
+            "protocol_version": "18",
+            "uptime": "556896",
+            "genesis_block": "F824C697633FAB78B703D75189B7A7E18DA438A2ED5FFE7495F02F681CD56D41",
+            "major_version": "21",
+            "minor_version": "0",
+            "patch_version": "0",
+            "pre_release_version": "0",
+            "maker": "0",
+            "timestamp": "1587055945990",
+            "active_difficulty": "ffffffcdbf40aa45"
+        }
+        "#;
+
+        let r = serde_json::from_str::<TelemetryResponse>(s).unwrap();
+
+        assert_eq!(
+            r,
+            TelemetryResponse {
+                block_count: 5777903,
+                cemented_count: 688819,
+                unchecked_count: 443468,
+                account_count: 620750,
+                bandwidth_cap: 1572864,
+                peer_count: 32,
+                protocol_version: 18,
+                uptime: 556896,
+                genesis_block: BlockHash::from_str(
+                    "F824C697633FAB78B703D75189B7A7E18DA438A2ED5FFE7495F02F681CD56D41"
+                )
+                .unwrap(),
+                major_version: 21,
+                minor_version: 0,
+                patch_version: 0,
+                pre_release_version: 0,
+                maker: 0,
+                timestamp: 1587055945990,
+                active_difficulty: Difficulty::from_str("ffffffcdbf40aa45").unwrap(),
+            }
+        )
+    }
+}
@@ -0,0 +1,110 @@
+use crate::blocks::BlockHash;
+use crate::rpc::calls::StateBlockRequest;
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::rpc::AlwaysTrue;
+use crate::Result;
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Blocks the node has received but can't process yet, e.g. because the previous block is
+/// missing. This needs `enable_control` on the node.
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct UncheckedRequest {
+    /// Limit the number of results to `count`.
+    #[clap(short, long, default_value = "1")]
+    pub count: u64,
+
+    // We only support json_block being true.
+    #[clap(skip)]
+    json_block: AlwaysTrue,
+}
+
+#[async_trait]
+impl RPCRequest for &UncheckedRequest {
+    type Response = UncheckedResponse;
+
+    fn action(&self) -> &str {
+        "unchecked"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<UncheckedResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl UncheckedRequest {
+    pub fn new(count: u64) -> Self {
+        Self {
+            count,
+            json_block: Default::default(),
+        }
+    }
+}
+
+/// Only state blocks are supported.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct UncheckedResponse {
+    pub blocks: HashMap<BlockHash, StateBlockRequest>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::{BlockType, Link};
+    use crate::{Address, Raw, Signature, Work};
+    use std::str::FromStr;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "blocks": {
+                "000D1BAEC8EC208142C99059B393051BAC8380F9B5A2E6B2489A277D81789F3F": {
+                    "type": "state",
+                    "account": "nano_1ipx847tk8o46pwxt5qjdbncjqcbwcc1rrmqnkztrfjy5k7z4imsrata9est",
+                    "previous": "CE898C131AAEE25E05362F247760F8A3ACF34A9796A5AE0D9204E86B0637965E",
+                    "representative": "nano_1stofnrxuz3cai7ze75o174bpm7scwj9jn3nxsn8ntzg784jf1gzn1jjdkou",
+                    "balance": "5606157000000000000000000000000000000",
+                    "link": "5D1AA8A45F8736519D707FCB375976A7F9AF795091021D7E9C7548D6F45DD8D5",
+                    "link_as_account": "nano_1qato4k7z3spc8gq1zyd8xeqfbzsoxwo36a45ozbrxcatut7up8ohyardu1z",
+                    "signature": "82D41BC16F313E4B2243D14DFFA2FB04679C540C2095FEE7EAE0F2F26880AD56DD48D87A7CC5DD760C5B2D76EE2C205506AA557BF00B60D8DEE312EC7343A501",
+                    "work": "8a142e07a10996d5"
+                }
+            }
+        }
+        "#;
+
+        let r = serde_json::from_str::<UncheckedResponse>(s).unwrap();
+
+        let mut blocks = HashMap::new();
+        blocks.insert(
+            BlockHash::from_str("000D1BAEC8EC208142C99059B393051BAC8380F9B5A2E6B2489A277D81789F3F")
+                .unwrap(),
+            StateBlockRequest {
+                block_type: BlockType::State,
+                account: Address::from_str(
+                    "nano_1ipx847tk8o46pwxt5qjdbncjqcbwcc1rrmqnkztrfjy5k7z4imsrata9est",
+                )
+                .unwrap(),
+                previous: BlockHash::from_str(
+                    "CE898C131AAEE25E05362F247760F8A3ACF34A9796A5AE0D9204E86B0637965E",
+                )
+                .unwrap(),
+                representative: Address::from_str(
+                    "nano_1stofnrxuz3cai7ze75o174bpm7scwj9jn3nxsn8ntzg784jf1gzn1jjdkou",
+                )
+                .unwrap(),
+                balance: Raw::from(5606157000000000000000000000000000000),
+                link: Link::unsure_from_str(
+                    "5D1AA8A45F8736519D707FCB375976A7F9AF795091021D7E9C7548D6F45DD8D5",
+                )
+                .unwrap(),
+                work: Some(Work::from_str("8a142e07a10996d5").unwrap()),
+                signature: Some(Signature::from_str("82D41BC16F313E4B2243D14DFFA2FB04679C540C2095FEE7EAE0F2F26880AD56DD48D87A7CC5DD760C5B2D76EE2C205506AA557BF00B60D8DEE312EC7343A501").unwrap()),
+            },
+        );
+
+        assert_eq!(r, UncheckedResponse { blocks })
+    }
+}
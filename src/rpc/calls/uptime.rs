@@ -0,0 +1,51 @@
+use crate::rpc::calls::{as_str, from_str};
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::Result;
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Serialize, Deserialize, Clap)]
+pub struct UptimeRequest {}
+
+#[async_trait]
+impl RPCRequest for &UptimeRequest {
+    type Response = UptimeResponse;
+
+    fn action(&self) -> &str {
+        "uptime"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<UptimeResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl UptimeRequest {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct UptimeResponse {
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub seconds: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "seconds": "6000"
+        }
+        "#;
+
+        let r = serde_json::from_str::<UptimeResponse>(s).unwrap();
+
+        assert_eq!(r, UptimeResponse { seconds: 6000 })
+    }
+}
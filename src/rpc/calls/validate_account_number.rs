@@ -0,0 +1,58 @@
+use crate::rpc::calls::{as_int_bool, from_int_bool};
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::Result;
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct ValidateAccountNumberRequest {
+    /// This is a string rather than an [Address](crate::Address), since it might be invalid.
+    pub account: String,
+}
+
+#[async_trait]
+impl RPCRequest for &ValidateAccountNumberRequest {
+    type Response = ValidateAccountNumberResponse;
+
+    fn action(&self) -> &str {
+        "validate_account_number"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<ValidateAccountNumberResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl ValidateAccountNumberRequest {
+    pub fn new<S: Into<String>>(account: S) -> Self {
+        Self {
+            account: account.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ValidateAccountNumberResponse {
+    #[serde(deserialize_with = "from_int_bool", serialize_with = "as_int_bool")]
+    pub valid: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "valid": "1"
+        }
+        "#;
+
+        let r = serde_json::from_str::<ValidateAccountNumberResponse>(s).unwrap();
+        assert_eq!(r, ValidateAccountNumberResponse { valid: true });
+
+        let r = serde_json::from_str::<ValidateAccountNumberResponse>(r#"{"valid": "0"}"#).unwrap();
+        assert_eq!(r, ValidateAccountNumberResponse { valid: false });
+    }
+}
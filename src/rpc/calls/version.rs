@@ -0,0 +1,90 @@
+use crate::blocks::BlockHash;
+use crate::rpc::calls::{as_str, from_str};
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::Result;
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Serialize, Deserialize, Clap)]
+pub struct VersionRequest {}
+
+#[async_trait]
+impl RPCRequest for &VersionRequest {
+    type Response = VersionResponse;
+
+    fn action(&self) -> &str {
+        "version"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<VersionResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl VersionRequest {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct VersionResponse {
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub rpc_version: u32,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub store_version: u32,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub protocol_version: u8,
+
+    pub node_vendor: String,
+    pub store_vendor: String,
+    pub network: String,
+
+    /// The hash of the genesis block.
+    pub network_identifier: BlockHash,
+
+    pub build_info: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "rpc_version": "1",
+            "store_version": "14",
+            "protocol_version": "17",
+            "node_vendor": "Nano V22.0",
+            "store_vendor": "LMDB 0.9.23",
+            "network": "live",
+            "network_identifier": "991CF190094C00F0B68E2E5F75F6BEE95A2E0BD93CEAA4A6734DB9F19B728948",
+            "build_info": "Build Info <git hash> \"<compiler> version \" \"<compiler version string>\" \"BOOST <boost version>\" BUILT \"<build date>\""
+        }
+        "#;
+
+        let r = serde_json::from_str::<VersionResponse>(s).unwrap();
+
+        assert_eq!(
+            r,
+            VersionResponse {
+                rpc_version: 1,
+                store_version: 14,
+                protocol_version: 17,
+                node_vendor: "Nano V22.0".into(),
+                store_vendor: "LMDB 0.9.23".into(),
+                network: "live".into(),
+                network_identifier: BlockHash::from_str(
+                    "991CF190094C00F0B68E2E5F75F6BEE95A2E0BD93CEAA4A6734DB9F19B728948"
+                )
+                .unwrap(),
+                build_info: "Build Info <git hash> \"<compiler> version \" \"<compiler version string>\" \"BOOST <boost version>\" BUILT \"<build date>\"".into(),
+            }
+        )
+    }
+}
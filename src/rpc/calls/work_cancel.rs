@@ -0,0 +1,53 @@
+use crate::blocks::BlockHash;
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::Result;
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct WorkCancelRequest {
+    pub hash: BlockHash,
+}
+
+#[async_trait]
+impl RPCRequest for &WorkCancelRequest {
+    type Response = WorkCancelResponse;
+
+    fn action(&self) -> &str {
+        "work_cancel"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<WorkCancelResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl WorkCancelRequest {
+    pub fn new(hash: BlockHash) -> Self {
+        Self { hash }
+    }
+}
+
+/// The node always responds with an empty success string.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+pub struct WorkCancelResponse {
+    pub success: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        let s = r#" {
+            "success": ""
+        }
+        "#;
+
+        let r = serde_json::from_str::<WorkCancelResponse>(s).unwrap();
+
+        assert_eq!(r, WorkCancelResponse::default())
+    }
+}
@@ -0,0 +1,79 @@
+use crate::blocks::BlockHash;
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::{Difficulty, Multiplier, Result, Work};
+use async_trait::async_trait;
+use clap::Clap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clap)]
+pub struct WorkGenerateRequest {
+    pub hash: BlockHash,
+
+    /// The difficulty in hex. Defaults to the base difficulty of the server.
+    #[clap(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<Difficulty>,
+}
+
+#[async_trait]
+impl RPCRequest for &WorkGenerateRequest {
+    type Response = WorkGenerateResponse;
+
+    fn action(&self) -> &str {
+        "work_generate"
+    }
+
+    async fn call(&self, client: &RPCClient) -> Result<WorkGenerateResponse> {
+        client.rpc(self).await
+    }
+}
+
+impl WorkGenerateRequest {
+    pub fn new(hash: BlockHash) -> Self {
+        Self {
+            hash,
+            difficulty: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct WorkGenerateResponse {
+    pub hash: BlockHash,
+    pub work: Work,
+    pub difficulty: Difficulty,
+
+    pub multiplier: Multiplier,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn decode() {
+        let s = r#"
+        {
+            "work": "2b3d689bbcb21dca",
+            "difficulty": "fffffff93c41ec94",
+            "multiplier": "1.182623871097636",
+            "hash": "718CC2121C3E641059BC1C2CFC45666C99E8AE922F7A807B7D07B62C995D79E2"
+        }
+        "#;
+
+        let r = serde_json::from_str::<WorkGenerateResponse>(s).unwrap();
+        assert_eq!(
+            r,
+            WorkGenerateResponse {
+                hash: BlockHash::from_str(
+                    "718CC2121C3E641059BC1C2CFC45666C99E8AE922F7A807B7D07B62C995D79E2"
+                )
+                .unwrap(),
+                work: Work::from_str("2b3d689bbcb21dca").unwrap(),
+                difficulty: Difficulty::from_str("fffffff93c41ec94").unwrap(),
+                multiplier: Multiplier::from_str("1.182623871097636").unwrap(),
+            }
+        );
+    }
+}
@@ -1,7 +1,6 @@
 use crate::blocks::BlockHash;
-use crate::rpc::calls::{as_str, from_str};
 use crate::rpc::client::{RPCClient, RPCRequest};
-use crate::{Difficulty, Result, Work};
+use crate::{Difficulty, Multiplier, Result, Work};
 use async_trait::async_trait;
 use clap::Clap;
 use serde::{Deserialize, Serialize};
@@ -10,6 +9,11 @@ use serde::{Deserialize, Serialize};
 pub struct WorkValidateRequest {
     pub hash: BlockHash,
     pub work: Work,
+
+    /// Also check the work against this difficulty in hex, setting `valid` in the response.
+    #[clap(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<Difficulty>,
 }
 
 #[async_trait]
@@ -27,20 +31,26 @@ impl RPCRequest for &WorkValidateRequest {
 
 impl WorkValidateRequest {
     pub fn new(work: Work, hash: BlockHash) -> Self {
-        Self { work, hash }
+        Self {
+            work,
+            hash,
+            difficulty: None,
+        }
     }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct WorkValidateResponse {
+    /// Only set when a difficulty was given in the request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid: Option<String>,
+
     // TODO: This is meant to be a bool as a number in a string?
-    valid_all: String,
-    valid_receive: String,
-    difficulty: Difficulty,
+    pub valid_all: String,
+    pub valid_receive: String,
+    pub difficulty: Difficulty,
 
-    // TODO: Make multiplier a type? It's used in multiple areas.
-    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
-    multiplier: f64,
+    pub multiplier: Multiplier,
 }
 
 #[cfg(test)]
@@ -63,10 +73,11 @@ mod tests {
         assert_eq!(
             r,
             WorkValidateResponse {
+                valid: None,
                 valid_all: String::from("1"),
                 valid_receive: String::from("1"),
                 difficulty: Difficulty::from_str("fffffff93c41ec94").unwrap(),
-                multiplier: 1.182623871097636,
+                multiplier: Multiplier::from_str("1.182623871097636").unwrap(),
             }
         );
     }
@@ -0,0 +1,285 @@
+//! A method on [RPCClient] for each action, using the defaults of each request. Use
+//! [RPCClient::request] to set other options.
+
+use crate::blocks::{BlockHash, StateBlock, Subtype};
+use crate::rpc::calls::*;
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::units::Mnano;
+use crate::{Address, Difficulty, Private, Public, Raw, Result, Work};
+
+impl RPCClient {
+    pub async fn account_balance(&self, account: &Address) -> Result<AccountBalanceResponse> {
+        (&AccountBalanceRequest::new(account.to_owned()))
+            .call(self)
+            .await
+    }
+
+    pub async fn account_block_count(
+        &self,
+        account: &Address,
+    ) -> Result<AccountBlockCountResponse> {
+        (&AccountBlockCountRequest::new(account.to_owned()))
+            .call(self)
+            .await
+    }
+
+    pub async fn account_get(&self, key: &Public) -> Result<AccountGetResponse> {
+        (&AccountGetRequest::new(key.to_owned())).call(self).await
+    }
+
+    /// Up to `count` blocks of an account, or all of them when `count` is -1.
+    pub async fn account_history(
+        &self,
+        account: &Address,
+        count: i64,
+    ) -> Result<AccountHistoryResponse> {
+        (&AccountHistoryRequest::new(account.to_owned(), count))
+            .call(self)
+            .await
+    }
+
+    /// Includes the representative, weight and pending amount.
+    pub async fn account_info(&self, account: &Address) -> Result<AccountInfoResponse> {
+        (&AccountInfoRequest::new(account.to_owned()))
+            .call(self)
+            .await
+    }
+
+    pub async fn account_key(&self, account: &Address) -> Result<AccountKeyResponse> {
+        (&AccountKeyRequest::new(account.to_owned()))
+            .call(self)
+            .await
+    }
+
+    pub async fn account_representative(
+        &self,
+        account: &Address,
+    ) -> Result<AccountRepresentativeResponse> {
+        (&AccountRepresentativeRequest::new(account.to_owned()))
+            .call(self)
+            .await
+    }
+
+    pub async fn account_weight(&self, account: &Address) -> Result<AccountWeightResponse> {
+        (&AccountWeightRequest::new(account.to_owned()))
+            .call(self)
+            .await
+    }
+
+    pub async fn accounts_balances(
+        &self,
+        accounts: &[Address],
+    ) -> Result<AccountsBalancesResponse> {
+        (&AccountsBalancesRequest::new(accounts.to_vec()))
+            .call(self)
+            .await
+    }
+
+    pub async fn accounts_frontiers(
+        &self,
+        accounts: &[Address],
+    ) -> Result<AccountsFrontiersResponse> {
+        (&AccountsFrontiersRequest::new(accounts.to_vec()))
+            .call(self)
+            .await
+    }
+
+    /// Up to `count` pending block hashes for each account.
+    pub async fn accounts_pending(
+        &self,
+        accounts: &[Address],
+        count: u64,
+    ) -> Result<AccountsPendingResponse> {
+        (&AccountsPendingRequest::new(accounts.to_vec(), count))
+            .call(self)
+            .await
+    }
+
+    pub async fn accounts_representatives(
+        &self,
+        accounts: &[Address],
+    ) -> Result<AccountsRepresentativesResponse> {
+        (&AccountsRepresentativesRequest::new(accounts.to_vec()))
+            .call(self)
+            .await
+    }
+
+    pub async fn active_difficulty(&self) -> Result<ActiveDifficultyResponse> {
+        (&ActiveDifficultyRequest::new()).call(self).await
+    }
+
+    pub async fn available_supply(&self) -> Result<AvailableSupplyResponse> {
+        (&AvailableSupplyRequest::new()).call(self).await
+    }
+
+    pub async fn block_account(&self, hash: &BlockHash) -> Result<BlockAccountResponse> {
+        (&BlockAccountRequest::new(hash.to_owned()))
+            .call(self)
+            .await
+    }
+
+    pub async fn block_confirm(&self, hash: &BlockHash) -> Result<BlockConfirmResponse> {
+        (&BlockConfirmRequest::new(hash.to_owned()))
+            .call(self)
+            .await
+    }
+
+    /// Includes the cemented count.
+    pub async fn block_count(&self) -> Result<BlockCountResponse> {
+        (&BlockCountRequest::new()).call(self).await
+    }
+
+    /// There are too many ways to create a block for a shortcut, so this takes the whole request.
+    pub async fn block_create(&self, request: &BlockCreateRequest) -> Result<BlockCreateResponse> {
+        request.call(self).await
+    }
+
+    pub async fn block_info(&self, hash: &BlockHash) -> Result<BlockInfoResponse> {
+        (&BlockInfoRequest::new(hash.to_owned())).call(self).await
+    }
+
+    pub async fn blocks_info(&self, hashes: &[BlockHash]) -> Result<BlocksInfoResponse> {
+        (&BlocksInfoRequest::new(hashes.to_vec())).call(self).await
+    }
+
+    /// Up to `count` previous blocks from `block`, or all of them when `count` is -1.
+    pub async fn chain(&self, block: &BlockHash, count: i64) -> Result<ChainResponse> {
+        (&ChainRequest::new(block.to_owned(), count))
+            .call(self)
+            .await
+    }
+
+    pub async fn delegators(&self, representative: &Address) -> Result<DelegatorsResponse> {
+        (&DelegatorsRequest::new(representative.to_owned()))
+            .call(self)
+            .await
+    }
+
+    pub async fn delegators_count(
+        &self,
+        representative: &Address,
+    ) -> Result<DelegatorsCountResponse> {
+        (&DelegatorsCountRequest::new(representative.to_owned()))
+            .call(self)
+            .await
+    }
+
+    pub async fn frontier_count(&self) -> Result<FrontierCountResponse> {
+        (&FrontierCountRequest::new()).call(self).await
+    }
+
+    /// Up to `count` accounts starting from `account`.
+    pub async fn ledger(&self, account: &Address, count: u64) -> Result<LedgerResponse> {
+        (&LedgerRequest::new(account.to_owned(), count))
+            .call(self)
+            .await
+    }
+
+    pub async fn nano_to_raw(&self, amount: Mnano) -> Result<NanoToRawResponse> {
+        (&NanoToRawRequest::new(amount)).call(self).await
+    }
+
+    /// The response is [Peers::Details] when `peer_details` is set.
+    pub async fn peers(&self, peer_details: bool) -> Result<PeersResponse> {
+        (&PeersRequest::new(peer_details)).call(self).await
+    }
+
+    pub async fn pending_exists(&self, hash: &BlockHash) -> Result<PendingExistsResponse> {
+        (&PendingExistsRequest::new(hash.to_owned()))
+            .call(self)
+            .await
+    }
+
+    pub async fn process(&self, subtype: Subtype, block: StateBlock) -> Result<ProcessResponse> {
+        (&ProcessRequest::new(subtype, block)).call(self).await
+    }
+
+    pub async fn raw_to_nano(&self, amount: Raw) -> Result<RawToNanoResponse> {
+        (&RawToNanoRequest::new(amount)).call(self).await
+    }
+
+    /// Up to `count` pending block hashes for the account.
+    pub async fn receivable(&self, account: &Address, count: u64) -> Result<ReceivableResponse> {
+        (&ReceivableRequest::new(account.to_owned(), count))
+            .call(self)
+            .await
+    }
+
+    pub async fn representatives(&self) -> Result<RepresentativesResponse> {
+        (&RepresentativesRequest::new()).call(self).await
+    }
+
+    /// The response is [RepresentativesOnlineResponse::Weight] when `weight` is set.
+    pub async fn representatives_online(
+        &self,
+        weight: bool,
+    ) -> Result<RepresentativesOnlineResponse> {
+        (&RepresentativesOnlineRequest::new(weight))
+            .call(self)
+            .await
+    }
+
+    /// This sends the private key to the node.
+    pub async fn sign(&self, key: &Private, block: StateBlock) -> Result<SignResponse> {
+        (&SignRequest::new(key.to_owned(), StateBlockRequest::from(block)))
+            .call(self)
+            .await
+    }
+
+    /// Up to `count` successors from `block`, or all of them when `count` is -1.
+    pub async fn successors(&self, block: &BlockHash, count: i64) -> Result<SuccessorsResponse> {
+        (&SuccessorsRequest::new(block.to_owned(), count))
+            .call(self)
+            .await
+    }
+
+    pub async fn telemetry(&self) -> Result<TelemetryResponse> {
+        (&TelemetryRequest::new()).call(self).await
+    }
+
+    pub async fn unchecked(&self, count: u64) -> Result<UncheckedResponse> {
+        (&UncheckedRequest::new(count)).call(self).await
+    }
+
+    pub async fn uptime(&self) -> Result<UptimeResponse> {
+        (&UptimeRequest::new()).call(self).await
+    }
+
+    pub async fn validate_account_number(
+        &self,
+        account: &str,
+    ) -> Result<ValidateAccountNumberResponse> {
+        (&ValidateAccountNumberRequest::new(account))
+            .call(self)
+            .await
+    }
+
+    pub async fn version(&self) -> Result<VersionResponse> {
+        (&VersionRequest::new()).call(self).await
+    }
+
+    pub async fn work_cancel(&self, hash: &BlockHash) -> Result<WorkCancelResponse> {
+        (&WorkCancelRequest::new(hash.to_owned())).call(self).await
+    }
+
+    /// Uses the base difficulty of the server when `difficulty` is `None`.
+    pub async fn work_generate(
+        &self,
+        hash: &BlockHash,
+        difficulty: Option<&Difficulty>,
+    ) -> Result<WorkGenerateResponse> {
+        let mut request = WorkGenerateRequest::new(hash.to_owned());
+        request.difficulty = difficulty.cloned();
+        (&request).call(self).await
+    }
+
+    pub async fn work_validate(
+        &self,
+        work: &Work,
+        hash: &BlockHash,
+    ) -> Result<WorkValidateResponse> {
+        (&WorkValidateRequest::new(work.to_owned(), hash.to_owned()))
+            .call(self)
+            .await
+    }
+}
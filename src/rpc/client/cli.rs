@@ -47,6 +47,28 @@ impl RPCClientOpts {
             RpcCommand::BlockInfo(c) => self.show(c).await?,
             RpcCommand::Peers(c) => self.show(c).await?,
             RpcCommand::Process(c) => self.show(c).await?,
+            RpcCommand::AccountsRepresentatives(c) => self.show(c).await?,
+            RpcCommand::BlocksInfo(c) => self.show(c).await?,
+            RpcCommand::Chain(c) => self.show(c).await?,
+            RpcCommand::Delegators(c) => self.show(c).await?,
+            RpcCommand::DelegatorsCount(c) => self.show(c).await?,
+            RpcCommand::FrontierCount(c) => self.show(c).await?,
+            RpcCommand::Ledger(c) => self.show(c).await?,
+            RpcCommand::NanoToRaw(c) => self.show(c).await?,
+            RpcCommand::PendingExists(c) => self.show(c).await?,
+            RpcCommand::RawToNano(c) => self.show(c).await?,
+            RpcCommand::Receivable(c) => self.show(c).await?,
+            RpcCommand::Representatives(c) => self.show(c).await?,
+            RpcCommand::RepresentativesOnline(c) => self.show(c).await?,
+            RpcCommand::Sign(c) => self.show(c).await?,
+            RpcCommand::Successors(c) => self.show(c).await?,
+            RpcCommand::Telemetry(c) => self.show(c).await?,
+            RpcCommand::Unchecked(c) => self.show(c).await?,
+            RpcCommand::Uptime(c) => self.show(c).await?,
+            RpcCommand::ValidateAccountNumber(c) => self.show(c).await?,
+            RpcCommand::Version(c) => self.show(c).await?,
+            RpcCommand::WorkCancel(c) => self.show(c).await?,
+            RpcCommand::WorkGenerate(c) => self.show(c).await?,
             RpcCommand::WorkValidate(c) => self.show(c).await?,
         };
         Ok(())
@@ -1,15 +1,24 @@
+mod actions;
 mod cli;
 
 use crate::{Error, Result};
 use async_trait::async_trait;
 pub(crate) use cli::RPCClientOpts;
+use reqwest::StatusCode;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
-use tracing::debug;
+use std::time::Duration;
+use tracing::{debug, warn};
 
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_RETRIES: usize = 2;
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// A request for one of the actions in [crate::rpc::calls], which are implemented for references,
+/// e.g. `(&AccountInfoRequest::new(address)).call(&client)`.
 #[async_trait]
-pub(crate) trait RPCRequest {
+pub trait RPCRequest {
     type Response: Serialize;
 
     fn action(&self) -> &str;
@@ -35,9 +44,26 @@ pub struct RPCError {
     pub(crate) error: String,
 }
 
+/// A client for a Nano RPC server, with a method for each action, e.g. [RPCClient::account_info].
+///
+/// Clones share the same pool of connections.
+#[derive(Clone)]
 pub struct RPCClient {
     url: String,
     authorization: Option<String>,
+    client: reqwest::Client,
+
+    /// How long to wait for a whole request, including the response. There's no limit by default,
+    /// since generating work can take a while.
+    timeout: Option<Duration>,
+
+    /// How many times to retry a request after a transient error, e.g. a refused connection or a
+    /// 503 response. A request that timed out isn't retried, since the node might have handled it,
+    /// e.g. published a block that a retry would then report as old.
+    retries: usize,
+
+    /// How long to wait before the first retry, doubling after each one.
+    retry_delay: Duration,
 }
 
 impl RPCClient {
@@ -46,35 +72,89 @@ impl RPCClient {
         Self {
             url,
             authorization: None,
+            client: Self::build_client(DEFAULT_CONNECT_TIMEOUT),
+            timeout: None,
+            retries: DEFAULT_RETRIES,
+            retry_delay: DEFAULT_RETRY_DELAY,
         }
     }
 
-    pub fn authorization<S: Into<String>>(&mut self, auth: S) {
+    fn build_client(connect_timeout: Duration) -> reqwest::Client {
+        reqwest::Client::builder()
+            .connect_timeout(connect_timeout)
+            .build()
+            .expect("Could not build HTTP client")
+    }
+
+    pub fn authorization<S: Into<String>>(&mut self, auth: S) -> &mut Self {
         self.authorization = Some(auth.into());
+        self
+    }
+
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
     }
 
-    pub(crate) async fn rpc<S, R>(&self, request: &S) -> Result<R>
+    /// This starts a new pool of connections.
+    pub fn connect_timeout(&mut self, connect_timeout: Duration) -> &mut Self {
+        self.client = Self::build_client(connect_timeout);
+        self
+    }
+
+    pub fn retries(&mut self, retries: usize) -> &mut Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn retry_delay(&mut self, retry_delay: Duration) -> &mut Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Send any request, for options the action methods don't cover.
+    pub async fn request<R>(&self, request: R) -> Result<R::Response>
+    where
+        R: RPCRequest + Sync,
+    {
+        request.call(self).await
+    }
+
+    pub async fn rpc<S, R>(&self, request: &S) -> Result<R>
     where
         S: Sized + Serialize + RPCRequest,
         R: Sized + DeserializeOwned + Debug,
     {
         let action = request.action();
-        let client = reqwest::Client::new();
 
         let body = Request::new(action, request);
         let body = serde_json::to_string(&body).expect("Could not serialize request");
         debug!("SEND: {}", body);
 
-        let mut request = client.post(&self.url);
-        if let Some(auth) = &self.authorization {
-            request = request.header("Authorization", auth);
-        }
-        let res = request
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json")
-            .body(body)
-            .send()
-            .await?;
+        let mut attempt = 0;
+        let res = loop {
+            let result = self.send(&body).await;
+            let transient = match &result {
+                Ok(res) => is_transient_status(res.status()),
+                Err(err) => err.is_connect(),
+            };
+            if !transient || attempt >= self.retries {
+                break result?;
+            }
+
+            let delay = self.retry_delay * 2u32.pow(attempt as u32);
+            match &result {
+                Ok(res) => warn!(
+                    "RPC {} got {}, retrying in {:?}",
+                    action,
+                    res.status(),
+                    delay
+                ),
+                Err(err) => warn!("RPC {} failed, retrying in {:?}: {}", action, delay, err),
+            }
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        };
 
         let text = res.text().await?;
         debug!("RECV: {}", text);
@@ -103,4 +183,124 @@ impl RPCClient {
             }
         }
     }
+
+    async fn send(&self, body: &str) -> std::result::Result<reqwest::Response, reqwest::Error> {
+        let mut request = self.client.post(&self.url);
+        if let Some(auth) = &self.authorization {
+            request = request.header("Authorization", auth);
+        }
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        request
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json")
+            .body(body.to_owned())
+            .send()
+            .await
+    }
+}
+
+/// Responses from a server that's overloaded or restarting, or from a proxy in front of it.
+fn is_transient_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answer each connection with the next response, returning how many requests were served.
+    async fn serve(
+        responses: Vec<(u16, &'static str)>,
+    ) -> (String, tokio::task::JoinHandle<usize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let mut served = 0;
+            for (status, body) in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await.unwrap();
+                let response = format!(
+                    "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+                served += 1;
+            }
+            served
+        });
+        (url, handle)
+    }
+
+    fn client(url: String) -> RPCClient {
+        let mut client = RPCClient::new(url);
+        client.retry_delay(Duration::from_millis(1));
+        client
+    }
+
+    #[tokio::test]
+    async fn retry_unavailable() {
+        let (url, handle) = serve(vec![
+            (503, ""),
+            (200, r#"{"count":"123","unchecked":"0","cemented":"100"}"#),
+        ])
+        .await;
+        let response = client(url).block_count().await.unwrap();
+        assert_eq!(response.count, 123);
+        assert_eq!(handle.await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn no_retry_on_rpc_error() {
+        let (url, handle) = serve(vec![(200, r#"{"error":"Unknown command"}"#)]).await;
+        let result = client(url).block_count().await;
+        assert!(matches!(result, Err(Error::RPCError(e)) if e == "Unknown command"));
+        assert_eq!(handle.await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn no_retry_on_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = connections.clone();
+        tokio::spawn(async move {
+            // Hold on to each connection without answering.
+            let mut sockets = vec![];
+            while let Ok((socket, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                sockets.push(socket);
+            }
+        });
+
+        let mut client = client(url);
+        client.timeout(Duration::from_millis(50));
+        assert!(client.block_count().await.is_err());
+        // A retry would have connected again by now.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn give_up_after_retries() {
+        let (url, handle) = serve(vec![(502, ""), (502, "")]).await;
+        let mut client = client(url);
+        client.retries(1);
+        assert!(client.block_count().await.is_err());
+        assert_eq!(handle.await.unwrap(), 2);
+    }
 }
@@ -7,5 +7,11 @@ pub mod client;
 #[cfg(feature = "rpc_server")]
 pub mod server;
 
+#[cfg(any(feature = "ws_client", feature = "rpc_server"))]
+pub mod websocket;
+
+#[cfg(feature = "work_server")]
+pub mod work_server;
+
 #[cfg(any(feature = "rpc_client", feature = "rpc_server"))]
 pub use calls::*;
@@ -1,5 +1,6 @@
 use crate::node::{ArcState, NodeCommandReceiver, NodeCommandSender};
 use crate::rpc::client::RPCError;
+use crate::rpc::websocket::WebSocketServer;
 use crate::rpc::{NodeHandler, RpcCommand};
 use crate::Result;
 use serde::Serialize;
@@ -11,20 +12,25 @@ use warp::Filter;
 pub struct RPCServer {
     state: ArcState,
     node_cmd_tx: NodeCommandSender,
+    websocket: WebSocketServer,
 }
 
 impl RPCServer {
-    pub fn new_with_channel(state: ArcState) -> (Self, NodeCommandReceiver) {
+    pub fn new_with_channel(
+        state: ArcState,
+        websocket: WebSocketServer,
+    ) -> (Self, NodeCommandReceiver) {
         let (tx, rx) = mpsc::channel(100);
         let s = Self {
             node_cmd_tx: tx,
             state,
+            websocket,
         };
         (s, rx)
     }
 
     pub async fn run(self) -> anyhow::Result<()> {
-        info!("Starting RPC server");
+        info!("Starting RPC and WebSocket servers");
         let rpc = warp::post()
             .and(warp::body::content_length_limit(1024 * 16))
             .and(with_state(self.state.clone()))
@@ -32,8 +38,12 @@ impl RPCServer {
             .and(warp::body::json())
             .and_then(Self::handle);
 
+        // Served on the same port as nano_node, so its clients work unchanged.
+        let websocket = warp::serve(self.websocket.route()).run(([127, 0, 0, 1], 7078));
+
         // TODO: Configurable
-        warp::serve(rpc).run(([127, 0, 0, 1], 7076)).await;
+        let rpc = warp::serve(rpc).run(([127, 0, 0, 1], 7076));
+        tokio::join!(rpc, websocket);
         Ok(())
     }
 
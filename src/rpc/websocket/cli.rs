@@ -0,0 +1,70 @@
+use crate::rpc::websocket::{Subscription, Topic, WebSocketClient};
+use crate::{Address, Error};
+use clap::Clap;
+use futures::StreamExt;
+use tracing::warn;
+
+#[derive(Clap)]
+pub(crate) struct WebSocketOpts {
+    /// The URL of the WebSocket server of the node.
+    #[clap(
+        long,
+        short,
+        default_value = "ws://localhost:7078",
+        env = "FEELESS_WS_URL"
+    )]
+    url: String,
+
+    #[clap(subcommand)]
+    command: WebSocketCommand,
+}
+
+#[derive(Clap)]
+enum WebSocketCommand {
+    /// Print events from the node as JSON lines, until the node closes the connection.
+    Subscribe(SubscribeOpts),
+}
+
+#[derive(Clap)]
+struct SubscribeOpts {
+    /// One or more of confirmation, vote, new_unconfirmed_block, telemetry, work,
+    /// started_election and stopped_election.
+    #[clap(required = true)]
+    topics: Vec<Topic>,
+
+    /// Only show confirmations of blocks for these accounts, including sends to them, and votes
+    /// from these representatives.
+    #[clap(short, long)]
+    accounts: Option<Vec<Address>>,
+}
+
+impl WebSocketOpts {
+    pub(crate) async fn handle(&self) -> anyhow::Result<()> {
+        match &self.command {
+            WebSocketCommand::Subscribe(o) => {
+                let accounts = o.accounts.clone().unwrap_or_default();
+                let subscriptions: Vec<_> = o
+                    .topics
+                    .iter()
+                    .map(|topic| Subscription::new(*topic, accounts.clone()))
+                    .collect();
+
+                let mut notifications = WebSocketClient::new(&self.url)
+                    .subscribe(&subscriptions)
+                    .await?;
+                while let Some(result) = notifications.next().await {
+                    match result {
+                        Ok(notification) => {
+                            println!("{}", serde_json::to_string(&notification)?)
+                        }
+                        Err(Error::BadWebSocketMessage { err, message }) => {
+                            warn!("Skipping message: {} {}", err, message)
+                        }
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::rpc::websocket::{Notification, Subscription};
+use crate::{Error, Result};
+use futures::{SinkExt, Stream};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use tracing::debug;
+
+/// A client for the WebSocket server of a Nano node, which is enabled with `websocket.enable` in
+/// the node config.
+pub struct WebSocketClient {
+    url: String,
+}
+
+impl WebSocketClient {
+    pub fn new<S: Into<String>>(url: S) -> Self {
+        Self { url: url.into() }
+    }
+
+    /// Connect and subscribe to each topic, returning the notifications for all of them.
+    pub async fn subscribe(&self, subscriptions: &[Subscription]) -> Result<Notifications> {
+        let (mut socket, _) = tokio_tungstenite::connect_async(self.url.as_str()).await?;
+        for subscription in subscriptions {
+            let message = subscription.to_message().to_string();
+            debug!("SEND: {}", message);
+            socket.send(Message::Text(message)).await?;
+        }
+        Ok(Notifications { socket })
+    }
+}
+
+/// Ends when the node closes the connection.
+///
+/// A message that can't be decoded is returned as [Error::BadWebSocketMessage], and the stream
+/// carries on after it.
+pub struct Notifications {
+    socket: WebSocketStream<TcpStream>,
+}
+
+impl Stream for Notifications {
+    type Item = Result<Notification>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let message = match Pin::new(&mut self.socket).poll_next(cx) {
+                Poll::Ready(Some(Ok(message))) => message,
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err.into()))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+            match message {
+                Message::Text(text) => {
+                    debug!("RECV: {}", text);
+                    let result = serde_json::from_str::<Notification>(&text)
+                        .map_err(|err| Error::BadWebSocketMessage { err, message: text });
+                    return Poll::Ready(Some(result));
+                }
+                Message::Close(_) => return Poll::Ready(None),
+                // Pings are answered by tungstenite.
+                Message::Ping(_) | Message::Pong(_) | Message::Binary(_) => continue,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::websocket::{Event, Topic};
+    use crate::Address;
+    use futures::StreamExt;
+    use std::str::FromStr;
+    use tokio::net::TcpListener;
+
+    const CONFIRMATION: &str = r#"{"topic":"confirmation","time":"1564935350664","message":{"account":"nano_1ipx847tk8o46pwxt5qjdbncjqcbwcc1rrmqnkztrfjy5k7z4imsrata9est","amount":"30000000000000000000000000000000000","hash":"87434F8041869A01C8F6F263B87972D7BA443A72E0A97D7A3FD0CCC2358FD6F9","confirmation_type":"active_quorum","block":{"type":"state","account":"nano_1ipx847tk8o46pwxt5qjdbncjqcbwcc1rrmqnkztrfjy5k7z4imsrata9est","previous":"CE898C131AAEE25E05362F247760F8A3ACF34A9796A5AE0D9204E86B0637965E","representative":"nano_1stofnrxuz3cai7ze75o174bpm7scwj9jn3nxsn8ntzg784jf1gzn1jjdkou","balance":"5606157000000000000000000000000000000","link":"5D1AA8A45F8736519D707FCB375976A7F9AF795091021D7E9C7548D6F45DD8D5","link_as_account":"nano_1qato4k7z3spc8gq1zyd8xeqfbzsoxwo36a45ozbrxcatut7up8ohyardu1z","signature":"82D41BC16F313E4B2243D14DFFA2FB04679C540C2095FEE7EAE0F2F26880AD56DD48D87A7CC5DD760C5B2D76EE2C205506AA557BF00B60D8DEE312EC7343A501","work":"8a142e07a10996d5","subtype":"send"}}}"#;
+
+    /// Accept one connection, expect `subscribes` messages, then send each of `messages` and close.
+    async fn mock_node(
+        subscribes: usize,
+        messages: Vec<&'static str>,
+    ) -> (String, tokio::task::JoinHandle<Vec<serde_json::Value>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let mut received = vec![];
+            while received.len() < subscribes {
+                if let Message::Text(text) = socket.next().await.unwrap().unwrap() {
+                    received.push(serde_json::from_str(&text).unwrap());
+                }
+            }
+            for message in messages {
+                socket.send(Message::Text(message.into())).await.unwrap();
+            }
+            socket.close(None).await.unwrap();
+            received
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn subscribe() {
+        let (url, handle) = mock_node(2, vec![CONFIRMATION, "not json", CONFIRMATION]).await;
+        let account =
+            Address::from_str("nano_1ipx847tk8o46pwxt5qjdbncjqcbwcc1rrmqnkztrfjy5k7z4imsrata9est")
+                .unwrap();
+
+        let notifications = WebSocketClient::new(url)
+            .subscribe(&[
+                Subscription::new(Topic::Confirmation, vec![account.clone()]),
+                Subscription::new(Topic::Telemetry, vec![]),
+            ])
+            .await
+            .unwrap();
+        let results: Vec<_> = notifications.collect().await;
+
+        let subscribes = handle.await.unwrap();
+        assert_eq!(subscribes[0]["topic"], "confirmation");
+        assert_eq!(subscribes[0]["options"]["accounts"][0], account.to_string());
+        assert_eq!(subscribes[1]["topic"], "telemetry");
+
+        assert_eq!(results.len(), 3);
+        assert!(
+            matches!(&results[0], Ok(Notification { event: Event::Confirmation(c), .. }) if c.account == account)
+        );
+        assert!(matches!(
+            &results[1],
+            Err(Error::BadWebSocketMessage { .. })
+        ));
+        assert!(results[2].is_ok());
+    }
+}
@@ -0,0 +1,471 @@
+use crate::blocks::{BlockHash, StateBlock, Subtype};
+use crate::rpc::calls::{as_str, from_str, StateBlockRequest, TelemetryResponse};
+use crate::{Address, Difficulty, Multiplier, Raw, Signature, Work};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ws_client")]
+use serde_json::json;
+use serde_with::TimestampMilliSeconds;
+use strum_macros::{Display, EnumString};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, EnumString, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Topic {
+    /// Blocks confirmed by the network.
+    Confirmation,
+
+    /// Votes from representatives.
+    Vote,
+
+    /// Blocks the node has seen for the first time, before they're confirmed.
+    NewUnconfirmedBlock,
+
+    /// Telemetry of peers, as it's received.
+    Telemetry,
+
+    /// Work generated by the node or its work peers.
+    Work,
+
+    /// Blocks the node has started an election for.
+    StartedElection,
+
+    /// Elections that ended without the block being confirmed.
+    StoppedElection,
+}
+
+/// A topic to subscribe to, optionally only for some accounts.
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    pub topic: Topic,
+
+    /// For [Topic::Confirmation] these are the accounts of the blocks, including the destination
+    /// of sends. For [Topic::Vote] these are the representatives. Other topics ignore them.
+    ///
+    /// Everything is included when this is empty.
+    pub accounts: Vec<Address>,
+}
+
+impl Subscription {
+    pub fn new(topic: Topic, accounts: Vec<Address>) -> Self {
+        Self { topic, accounts }
+    }
+
+    /// The message sent to the node to start this subscription.
+    #[cfg(feature = "ws_client")]
+    pub(crate) fn to_message(&self) -> serde_json::Value {
+        let mut message = json!({
+            "action": "subscribe",
+            "topic": self.topic,
+        });
+        if self.accounts.is_empty() {
+            return message;
+        }
+
+        let options = match self.topic {
+            Topic::Confirmation => json!({ "accounts": self.accounts }),
+            Topic::Vote => json!({ "representatives": self.accounts }),
+            _ => return message,
+        };
+        message["options"] = options;
+        message
+    }
+}
+
+/// A message from the node for one of the subscribed topics.
+#[serde_with::serde_as]
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Notification {
+    #[serde_as(as = "TimestampMilliSeconds<String>")]
+    pub time: chrono::DateTime<Utc>,
+
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+impl Notification {
+    /// A notification that the event happened now.
+    pub fn new(event: Event) -> Self {
+        Self {
+            time: Utc::now(),
+            event,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "topic", content = "message", rename_all = "snake_case")]
+pub enum Event {
+    Confirmation(ConfirmationEvent),
+    Vote(VoteEvent),
+    NewUnconfirmedBlock(BlockEvent),
+    Telemetry(TelemetryEvent),
+    Work(WorkEvent),
+    StartedElection(ElectionEvent),
+    StoppedElection(ElectionEvent),
+}
+
+impl Event {
+    pub fn topic(&self) -> Topic {
+        match self {
+            Event::Confirmation(_) => Topic::Confirmation,
+            Event::Vote(_) => Topic::Vote,
+            Event::NewUnconfirmedBlock(_) => Topic::NewUnconfirmedBlock,
+            Event::Telemetry(_) => Topic::Telemetry,
+            Event::Work(_) => Topic::Work,
+            Event::StartedElection(_) => Topic::StartedElection,
+            Event::StoppedElection(_) => Topic::StoppedElection,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ConfirmationEvent {
+    pub account: Address,
+    pub amount: Raw,
+    pub hash: BlockHash,
+
+    /// e.g. `active_quorum`, `active_confirmation_height` or `inactive`.
+    pub confirmation_type: String,
+
+    /// Left out when subscribed with `include_block` set to false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block: Option<BlockEvent>,
+}
+
+/// A state block with the subtype the node adds to it. Only state blocks are supported.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(from = "JsonBlock", into = "JsonBlock")]
+pub struct BlockEvent {
+    pub block: StateBlock,
+    pub subtype: Option<Subtype>,
+}
+
+/// The JSON form of [BlockEvent].
+#[derive(Serialize, Deserialize)]
+struct JsonBlock {
+    #[serde(flatten)]
+    block: StateBlockRequest,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    subtype: Option<Subtype>,
+}
+
+impl From<JsonBlock> for BlockEvent {
+    fn from(json: JsonBlock) -> Self {
+        Self {
+            block: StateBlock::from(json.block),
+            subtype: json.subtype,
+        }
+    }
+}
+
+impl From<BlockEvent> for JsonBlock {
+    fn from(event: BlockEvent) -> Self {
+        Self {
+            block: StateBlockRequest::from(event.block),
+            subtype: event.subtype,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct VoteEvent {
+    /// The representative.
+    pub account: Address,
+
+    pub signature: Signature,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+
+    pub blocks: Vec<BlockHash>,
+
+    #[serde(rename = "type")]
+    pub vote_type: VoteType,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteType {
+    /// A vote for an active election.
+    Vote,
+
+    /// A vote for a block that's already confirmed.
+    Replay,
+
+    /// A vote the node couldn't match to an election.
+    Indeterminate,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct TelemetryEvent {
+    /// The address of the peer.
+    pub address: String,
+
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub port: u16,
+
+    #[serde(flatten)]
+    pub telemetry: TelemetryResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct WorkEvent {
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub success: bool,
+
+    /// Why the work failed, e.g. `cancelled`, or empty when it succeeded.
+    pub reason: String,
+
+    /// In milliseconds.
+    #[serde(deserialize_with = "from_str", serialize_with = "as_str")]
+    pub duration: u64,
+
+    pub request: WorkEventRequest,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<WorkEventResult>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ElectionEvent {
+    pub hash: BlockHash,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct WorkEventRequest {
+    pub hash: BlockHash,
+    pub difficulty: Difficulty,
+    pub multiplier: Multiplier,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct WorkEventResult {
+    /// Where the work came from, e.g. `local` or the address of a work peer.
+    pub source: String,
+
+    pub work: Work,
+    pub difficulty: Difficulty,
+    pub multiplier: Multiplier,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::Link;
+    use chrono::DateTime;
+    use std::str::FromStr;
+
+    #[test]
+    #[cfg(feature = "ws_client")]
+    fn subscribe_message() {
+        let account =
+            Address::from_str("nano_1ipx847tk8o46pwxt5qjdbncjqcbwcc1rrmqnkztrfjy5k7z4imsrata9est")
+                .unwrap();
+
+        let s = Subscription::new(Topic::Confirmation, vec![account.clone()]);
+        assert_eq!(
+            s.to_message(),
+            json!({
+                "action": "subscribe",
+                "topic": "confirmation",
+                "options": {"accounts": [account.to_string()]},
+            })
+        );
+
+        let s = Subscription::new(Topic::Vote, vec![account.clone()]);
+        assert_eq!(
+            s.to_message()["options"],
+            json!({"representatives": [account.to_string()]})
+        );
+
+        let s = Subscription::new(Topic::NewUnconfirmedBlock, vec![account]);
+        assert_eq!(
+            s.to_message(),
+            json!({"action": "subscribe", "topic": "new_unconfirmed_block"})
+        );
+    }
+
+    #[test]
+    fn decode_confirmation() {
+        let s = r#" {
+            "topic": "confirmation",
+            "time": "1564935350664",
+            "message": {
+                "account": "nano_1ipx847tk8o46pwxt5qjdbncjqcbwcc1rrmqnkztrfjy5k7z4imsrata9est",
+                "amount": "30000000000000000000000000000000000",
+                "hash": "87434F8041869A01C8F6F263B87972D7BA443A72E0A97D7A3FD0CCC2358FD6F9",
+                "confirmation_type": "active_quorum",
+                "block": {
+                    "type": "state",
+                    "account": "nano_1ipx847tk8o46pwxt5qjdbncjqcbwcc1rrmqnkztrfjy5k7z4imsrata9est",
+                    "previous": "CE898C131AAEE25E05362F247760F8A3ACF34A9796A5AE0D9204E86B0637965E",
+                    "representative": "nano_1stofnrxuz3cai7ze75o174bpm7scwj9jn3nxsn8ntzg784jf1gzn1jjdkou",
+                    "balance": "5606157000000000000000000000000000000",
+                    "link": "5D1AA8A45F8736519D707FCB375976A7F9AF795091021D7E9C7548D6F45DD8D5",
+                    "link_as_account": "nano_1qato4k7z3spc8gq1zyd8xeqfbzsoxwo36a45ozbrxcatut7up8ohyardu1z",
+                    "signature": "82D41BC16F313E4B2243D14DFFA2FB04679C540C2095FEE7EAE0F2F26880AD56DD48D87A7CC5DD760C5B2D76EE2C205506AA557BF00B60D8DEE312EC7343A501",
+                    "work": "8a142e07a10996d5",
+                    "subtype": "send"
+                }
+            }
+        }
+        "#;
+
+        let n = serde_json::from_str::<Notification>(s).unwrap();
+        assert_eq!(
+            n.time,
+            DateTime::<Utc>::from_str("2019-08-04T16:15:50.664Z").unwrap()
+        );
+        let confirmation = match n.event {
+            Event::Confirmation(c) => c,
+            event => panic!("Unexpected event: {:?}", event),
+        };
+        assert_eq!(
+            confirmation.amount,
+            Raw::from(30000000000000000000000000000000000)
+        );
+        assert_eq!(confirmation.confirmation_type, "active_quorum");
+        let block = confirmation.block.unwrap();
+        assert_eq!(block.subtype, Some(Subtype::Send));
+
+        let block = &block.block;
+        assert_eq!(block.hash, confirmation.hash);
+        assert_eq!(block.account, confirmation.account.to_public());
+        assert_eq!(
+            block.link,
+            Link::unsure_from_str(
+                "5D1AA8A45F8736519D707FCB375976A7F9AF795091021D7E9C7548D6F45DD8D5"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn decode_vote() {
+        let s = r#" {
+            "topic": "vote",
+            "time": "1554995525343",
+            "message": {
+                "account": "nano_1n5aisgwmq1oibg8c7aerrubboccp3mfcjgm8jaas1fwhxmcndaf4jrt75fy",
+                "signature": "82D41BC16F313E4B2243D14DFFA2FB04679C540C2095FEE7EAE0F2F26880AD56DD48D87A7CC5DD760C5B2D76EE2C205506AA557BF00B60D8DEE312EC7343A501",
+                "sequence": "855471574",
+                "blocks": [
+                    "6FB9DE5D7908DEB8A2EA391AEA95041587CBF3420EF8A606F1489FECEE75C869"
+                ],
+                "type": "replay"
+            }
+        }
+        "#;
+
+        let n = serde_json::from_str::<Notification>(s).unwrap();
+        assert_eq!(
+            n.event,
+            Event::Vote(VoteEvent {
+                account: Address::from_str(
+                    "nano_1n5aisgwmq1oibg8c7aerrubboccp3mfcjgm8jaas1fwhxmcndaf4jrt75fy"
+                )
+                .unwrap(),
+                signature: Signature::from_str("82D41BC16F313E4B2243D14DFFA2FB04679C540C2095FEE7EAE0F2F26880AD56DD48D87A7CC5DD760C5B2D76EE2C205506AA557BF00B60D8DEE312EC7343A501").unwrap(),
+                sequence: Some("855471574".into()),
+                timestamp: None,
+                blocks: vec![BlockHash::from_str(
+                    "6FB9DE5D7908DEB8A2EA391AEA95041587CBF3420EF8A606F1489FECEE75C869"
+                )
+                .unwrap()],
+                vote_type: VoteType::Replay,
+            })
+        );
+    }
+
+    #[test]
+    fn decode_telemetry() {
+        let s = r#" {
+            "topic": "telemetry",
+            "time": "1587109495082",
+            "message": {
+                "block_count": "51",
+                "cemented_count": "51",
+                "unchecked_count": "0",
+                "account_count": "1",
+                "bandwidth_cap": "10485760",
+                "peer_count": "3",
+                "protocol_version": "18",
+                "uptime": "200",
+                "genesis_block": "F824C697633FAB78B703D75189B7A7E18DA438A2ED5FFE7495F02F681CD56D41",
+                "major_version": "21",
+                "minor_version": "0",
+                "patch_version": "0",
+                "pre_release_version": "0",
+                "maker": "0",
+                "timestamp": "1587109495082",
+                "active_difficulty": "ffffffc000000000",
+                "node_id": "node_1cmi8difuruopgzpnb4ybrnnj5rproxwuwe5mad7ucbsekakiwn37qqg1zo5",
+                "signature": "5F8DEE5F895D53E122FDEB4B1B4118A41F9DDB818C6B299B09DF59131AF9F201BB7057769423F6B0C868B57509177B54D5D2C731405FE607527F5E2B6B2E290F",
+                "address": "::ffff:192.168.0.10",
+                "port": "7075"
+            }
+        }
+        "#;
+
+        let n = serde_json::from_str::<Notification>(s).unwrap();
+        let telemetry = match n.event {
+            Event::Telemetry(t) => t,
+            event => panic!("Unexpected event: {:?}", event),
+        };
+        assert_eq!(telemetry.address, "::ffff:192.168.0.10");
+        assert_eq!(telemetry.port, 7075);
+        assert_eq!(telemetry.telemetry.block_count, 51);
+        assert_eq!(telemetry.telemetry.major_version, 21);
+    }
+
+    #[test]
+    fn decode_work() {
+        let s = r#" {
+            "topic": "work",
+            "time": "1561661740065",
+            "message": {
+                "success": "true",
+                "reason": "",
+                "duration": "306",
+                "request": {
+                    "hash": "3ECAFF96D29B5BB3C4B2F7F3A7B1F0D1C0F2D3E1F4A5B6C7D8E9FA0B1C2D3E4F",
+                    "difficulty": "ffffffd21c3933f3",
+                    "multiplier": "7.111111111111111"
+                },
+                "result": {
+                    "source": "192.168.1.101:7000",
+                    "work": "4a8fb104eebbd336",
+                    "difficulty": "ffffffd3d3b5a6b3",
+                    "multiplier": "7.539244942029125"
+                },
+                "bad_peers": ""
+            }
+        }
+        "#;
+
+        let n = serde_json::from_str::<Notification>(s).unwrap();
+        let work = match n.event {
+            Event::Work(w) => w,
+            event => panic!("Unexpected event: {:?}", event),
+        };
+        assert!(work.success);
+        assert_eq!(work.duration, 306);
+        let result = work.result.unwrap();
+        assert_eq!(result.source, "192.168.1.101:7000");
+        assert_eq!(result.work, Work::from_str("4a8fb104eebbd336").unwrap());
+    }
+
+    #[test]
+    fn round_trip() {
+        let s = r#"{"time":"1564935350664","topic":"new_unconfirmed_block","message":{"type":"state","account":"nano_1ipx847tk8o46pwxt5qjdbncjqcbwcc1rrmqnkztrfjy5k7z4imsrata9est","previous":"CE898C131AAEE25E05362F247760F8A3ACF34A9796A5AE0D9204E86B0637965E","representative":"nano_1stofnrxuz3cai7ze75o174bpm7scwj9jn3nxsn8ntzg784jf1gzn1jjdkou","balance":"5606157000000000000000000000000000000","link":"5D1AA8A45F8736519D707FCB375976A7F9AF795091021D7E9C7548D6F45DD8D5","work":"8A142E07A10996D5","signature":"82D41BC16F313E4B2243D14DFFA2FB04679C540C2095FEE7EAE0F2F26880AD56DD48D87A7CC5DD760C5B2D76EE2C205506AA557BF00B60D8DEE312EC7343A501","subtype":"send"}}"#;
+
+        let n = serde_json::from_str::<Notification>(s).unwrap();
+        assert!(matches!(n.event, Event::NewUnconfirmedBlock(_)));
+        assert_eq!(serde_json::to_string(&n).unwrap(), s);
+    }
+}
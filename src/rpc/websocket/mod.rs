@@ -0,0 +1,48 @@
+//! Subscriptions to the WebSocket server of a Nano node, to be told about confirmations, votes,
+//! etc. as they happen instead of polling the RPC server.
+//!
+//! [WebSocketClient] subscribes to a node, and [WebSocketServer] serves the same protocol from a
+//! feeless node.
+//!
+//! ## Example
+//! ```no_run
+//! use feeless::rpc::websocket::{Event, Subscription, Topic, WebSocketClient};
+//! use feeless::Address;
+//! use futures::StreamExt;
+//! use std::str::FromStr;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! let account =
+//!     Address::from_str("nano_3t6k35gi95xu6tergt6p69ck76ogmitsa8mnijtpxm9fkcm736xtoncuohr3")?;
+//! let subscription = Subscription::new(Topic::Confirmation, vec![account]);
+//! let mut notifications = WebSocketClient::new("ws://localhost:7078")
+//!     .subscribe(&[subscription])
+//!     .await?;
+//!
+//! while let Some(notification) = notifications.next().await {
+//!     if let Event::Confirmation(confirmation) = notification?.event {
+//!         println!("{} {}", confirmation.hash, confirmation.amount);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+#[cfg(feature = "ws_client")]
+mod cli;
+#[cfg(feature = "ws_client")]
+mod client;
+mod messages;
+#[cfg(feature = "rpc_server")]
+mod server;
+
+#[cfg(feature = "ws_client")]
+pub(crate) use cli::WebSocketOpts;
+#[cfg(feature = "ws_client")]
+pub use client::{Notifications, WebSocketClient};
+pub use messages::{
+    BlockEvent, ConfirmationEvent, ElectionEvent, Event, Notification, Subscription,
+    TelemetryEvent, Topic, VoteEvent, VoteType, WorkEvent, WorkEventRequest, WorkEventResult,
+};
+#[cfg(feature = "rpc_server")]
+pub use server::WebSocketServer;
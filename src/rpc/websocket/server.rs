@@ -0,0 +1,467 @@
+use crate::blocks::Subtype;
+use crate::rpc::websocket::{ConfirmationEvent, Event, Notification, Topic};
+use crate::{Address, Public};
+use chrono::Utc;
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, warn};
+use warp::ws::{Message, WebSocket, Ws};
+use warp::Filter;
+
+/// Publishes events from the node to WebSocket clients, with the same protocol as nano_node so
+/// tools written for it can subscribe to a feeless node.
+#[derive(Clone)]
+pub struct WebSocketServer {
+    tx: broadcast::Sender<Arc<Notification>>,
+}
+
+impl Default for WebSocketServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WebSocketServer {
+    pub fn new() -> Self {
+        // Clients that fall further behind than this miss notifications.
+        let (tx, _) = broadcast::channel(1000);
+        Self { tx }
+    }
+
+    /// Send an event to every client subscribed to its topic.
+    pub fn publish(&self, event: Event) {
+        // Nothing to do when nobody is connected.
+        let _ = self.tx.send(Arc::new(Notification::new(event)));
+    }
+
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<Arc<Notification>> {
+        self.tx.subscribe()
+    }
+
+    /// Upgrades a connection to a WebSocket, then serves its subscriptions.
+    pub fn route(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        let server = self.clone();
+        warp::ws().map(move |ws: Ws| {
+            let notifications = server.subscribe();
+            ws.on_upgrade(move |socket| Session::default().run(socket, notifications))
+        })
+    }
+}
+
+/// The subscriptions of one client.
+#[derive(Default)]
+struct Session {
+    subscriptions: HashMap<Topic, Options>,
+}
+
+impl Session {
+    async fn run(
+        mut self,
+        socket: WebSocket,
+        mut notifications: broadcast::Receiver<Arc<Notification>>,
+    ) {
+        let (mut tx, mut rx) = socket.split();
+        loop {
+            let reply = tokio::select! {
+                message = rx.next() => {
+                    let message = match message {
+                        Some(Ok(message)) => message,
+                        Some(Err(err)) => {
+                            debug!("WebSocket client disconnected: {:?}", err);
+                            break;
+                        }
+                        None => break,
+                    };
+                    if message.is_close() {
+                        break;
+                    }
+                    // Pings are answered by warp.
+                    match message.to_str() {
+                        Ok(text) => self.handle(text),
+                        Err(_) => None,
+                    }
+                }
+                notification = notifications.recv() => {
+                    match notification {
+                        Ok(notification) => self.filter(&notification),
+                        Err(RecvError::Lagged(missed)) => {
+                            warn!("WebSocket client missed {} notifications", missed);
+                            None
+                        }
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            };
+
+            if let Some(reply) = reply {
+                if tx.send(Message::text(reply)).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Handle a request from the client, returning what to reply with.
+    fn handle(&mut self, text: &str) -> Option<String> {
+        let request: Request = match serde_json::from_str(text) {
+            Ok(request) => request,
+            Err(err) => {
+                warn!("Bad WebSocket request: {} {}", err, text);
+                return None;
+            }
+        };
+
+        let ack = match (&request.action, request.topic) {
+            (Action::Ping, _) => "pong",
+            (Action::Subscribe, Some(topic)) => {
+                self.subscriptions.insert(topic, request.options);
+                "subscribe"
+            }
+            (Action::Unsubscribe, Some(topic)) => {
+                self.subscriptions.remove(&topic);
+                "unsubscribe"
+            }
+            (Action::Update, Some(topic)) => {
+                if let Some(options) = self.subscriptions.get_mut(&topic) {
+                    options.update(request.options);
+                }
+                "update"
+            }
+            (action, None) => {
+                warn!("WebSocket request {:?} is missing a topic", action);
+                return None;
+            }
+        };
+
+        // Pings are always answered.
+        let wants_ack = Flag::is_set(&request.ack, false);
+        if request.action != Action::Ping && !wants_ack {
+            return None;
+        }
+        let mut reply = json!({
+            "ack": ack,
+            "time": Utc::now().timestamp_millis().to_string(),
+        });
+        if let Some(id) = request.id {
+            reply["id"] = json!(id);
+        }
+        Some(reply.to_string())
+    }
+
+    /// The JSON to send for a notification, if the client is subscribed to it.
+    fn filter(&self, notification: &Notification) -> Option<String> {
+        let options = self.subscriptions.get(&notification.event.topic())?;
+        let result = match &notification.event {
+            Event::Confirmation(confirmation) => {
+                if !options.accounts.is_empty()
+                    && !confirmation_accounts(confirmation)
+                        .iter()
+                        .any(|account| options.accounts.contains(account))
+                {
+                    return None;
+                }
+                if options.include_block() {
+                    serde_json::to_string(notification)
+                } else {
+                    serde_json::to_value(notification).map(|mut value| {
+                        if let Some(message) = value["message"].as_object_mut() {
+                            message.remove("block");
+                        }
+                        value.to_string()
+                    })
+                }
+            }
+            Event::Vote(vote) => {
+                if !options.representatives.is_empty()
+                    && !options.representatives.contains(&vote.account)
+                {
+                    return None;
+                }
+                serde_json::to_string(notification)
+            }
+            _ => serde_json::to_string(notification),
+        };
+
+        match result {
+            Ok(json) => Some(json),
+            Err(err) => {
+                warn!(
+                    "Could not encode notification: {:?} {:?}",
+                    err, notification
+                );
+                None
+            }
+        }
+    }
+}
+
+/// The account of a confirmed block, and the destination when it's a send.
+fn confirmation_accounts(confirmation: &ConfirmationEvent) -> Vec<Address> {
+    let mut accounts = vec![confirmation.account.clone()];
+    if let Some(block) = &confirmation.block {
+        if block.subtype == Some(Subtype::Send) {
+            if let Ok(destination) = Public::try_from(block.block.link.as_bytes()) {
+                accounts.push(destination.to_address());
+            }
+        }
+    }
+    accounts
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    action: Action,
+    topic: Option<Topic>,
+    ack: Option<Flag>,
+    id: Option<String>,
+
+    #[serde(default)]
+    options: Options,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+    Subscribe,
+    Unsubscribe,
+
+    /// Change the accounts of a confirmation subscription.
+    Update,
+
+    Ping,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Options {
+    /// Confirmations involving these accounts, or all of them when empty.
+    #[serde(default)]
+    accounts: HashSet<Address>,
+
+    /// Votes from these representatives, or all of them when empty.
+    #[serde(default)]
+    representatives: HashSet<Address>,
+
+    /// Defaults to true.
+    include_block: Option<Flag>,
+
+    /// Only used by [Action::Update].
+    #[serde(default)]
+    accounts_add: Vec<Address>,
+
+    /// Only used by [Action::Update].
+    #[serde(default)]
+    accounts_del: Vec<Address>,
+}
+
+impl Options {
+    fn include_block(&self) -> bool {
+        Flag::is_set(&self.include_block, true)
+    }
+
+    fn update(&mut self, update: Options) {
+        self.accounts.extend(update.accounts_add);
+        for account in &update.accounts_del {
+            self.accounts.remove(account);
+        }
+    }
+}
+
+/// nano_node accepts both `true` and `"true"`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Flag {
+    Bool(bool),
+    String(String),
+}
+
+impl Flag {
+    fn is_set(flag: &Option<Flag>, default: bool) -> bool {
+        match flag {
+            Some(Flag::Bool(b)) => *b,
+            Some(Flag::String(s)) => s == "true",
+            None => default,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::BlockHash;
+    use crate::rpc::websocket::{BlockEvent, ElectionEvent, VoteEvent, VoteType};
+    use crate::{Raw, Seed, Signature};
+    use std::str::FromStr;
+    use warp::test::WsClient;
+
+    fn account(index: u32) -> Address {
+        Seed::zero().derive(index).to_address().unwrap()
+    }
+
+    fn confirmation(account: Address) -> Event {
+        let block = json!({
+            "type": "state",
+            "account": account,
+            "previous": "CE898C131AAEE25E05362F247760F8A3ACF34A9796A5AE0D9204E86B0637965E",
+            "representative": account,
+            "balance": "1",
+            "link": "5D1AA8A45F8736519D707FCB375976A7F9AF795091021D7E9C7548D6F45DD8D5",
+            "signature": "82D41BC16F313E4B2243D14DFFA2FB04679C540C2095FEE7EAE0F2F26880AD56DD48D87A7CC5DD760C5B2D76EE2C205506AA557BF00B60D8DEE312EC7343A501",
+            "work": "8a142e07a10996d5",
+            "subtype": "send",
+        });
+        // Raw is only decoded from borrowed strings.
+        let block = serde_json::from_str::<BlockEvent>(&block.to_string()).unwrap();
+        Event::Confirmation(ConfirmationEvent {
+            account,
+            amount: Raw::from(1),
+            hash: block.block.hash.clone(),
+            confirmation_type: "active_quorum".into(),
+            block: Some(block),
+        })
+    }
+
+    fn vote(account: Address) -> Event {
+        Event::Vote(VoteEvent {
+            account,
+            signature: Signature::zero(),
+            sequence: None,
+            timestamp: Some("1".into()),
+            blocks: vec![],
+            vote_type: VoteType::Vote,
+        })
+    }
+
+    async fn recv(client: &mut WsClient) -> serde_json::Value {
+        let message = client.recv().await.unwrap();
+        serde_json::from_str(message.to_str().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn subscribe_with_filters() {
+        let server = WebSocketServer::new();
+        let mut client = warp::test::ws().handshake(server.route()).await.unwrap();
+
+        client
+            .send_text(
+                json!({
+                    "action": "subscribe",
+                    "topic": "confirmation",
+                    "ack": true,
+                    "id": "one",
+                    "options": {"accounts": [account(1)], "include_block": "false"},
+                })
+                .to_string(),
+            )
+            .await;
+        let ack = recv(&mut client).await;
+        assert_eq!(ack["ack"], "subscribe");
+        assert_eq!(ack["id"], "one");
+
+        client
+            .send_text(
+                json!({
+                    "action": "subscribe",
+                    "topic": "vote",
+                    "options": {"representatives": [account(2)]},
+                })
+                .to_string(),
+            )
+            .await;
+        client.send_text(r#"{"action": "ping"}"#).await;
+        assert_eq!(recv(&mut client).await["ack"], "pong");
+
+        server.publish(confirmation(account(0)));
+        server.publish(vote(account(0)));
+        server.publish(Event::StartedElection(ElectionEvent {
+            hash: BlockHash::from_str(
+                "87434F8041869A01C8F6F263B87972D7BA443A72E0A97D7A3FD0CCC2358FD6F9",
+            )
+            .unwrap(),
+        }));
+        server.publish(confirmation(account(1)));
+        server.publish(vote(account(2)));
+
+        let confirmation = recv(&mut client).await;
+        assert_eq!(confirmation["topic"], "confirmation");
+        assert_eq!(
+            confirmation["message"]["account"],
+            json!(account(1).to_string())
+        );
+        assert!(confirmation["message"].get("block").is_none());
+
+        let vote = recv(&mut client).await;
+        assert_eq!(vote["topic"], "vote");
+        assert_eq!(vote["message"]["account"], json!(account(2).to_string()));
+    }
+
+    #[test]
+    fn send_destination() {
+        let event = match confirmation(account(0)) {
+            Event::Confirmation(c) => c,
+            _ => unreachable!(),
+        };
+        let destination =
+            Address::from_str("nano_1qato4k7z3spc8gq1zyd8xeqfbzsoxwo36a45ozbrxcatut7up8ohyardu1z")
+                .unwrap();
+        assert_eq!(confirmation_accounts(&event), vec![account(0), destination]);
+    }
+
+    #[test]
+    fn send_to_subscribed_account() {
+        let destination =
+            Address::from_str("nano_1qato4k7z3spc8gq1zyd8xeqfbzsoxwo36a45ozbrxcatut7up8ohyardu1z")
+                .unwrap();
+        let notification = Notification::new(confirmation(account(0)));
+
+        let mut session = Session::default();
+        let subscribe = json!({
+            "action": "subscribe",
+            "topic": "confirmation",
+            "options": {"accounts": [account(3)]},
+        });
+        session.handle(&subscribe.to_string());
+        assert_eq!(session.filter(&notification), None);
+
+        let subscribe = json!({
+            "action": "subscribe",
+            "topic": "confirmation",
+            "options": {"accounts": [destination]},
+        });
+        session.handle(&subscribe.to_string());
+        let json: serde_json::Value =
+            serde_json::from_str(&session.filter(&notification).unwrap()).unwrap();
+        assert_eq!(json["message"]["account"], json!(account(0).to_string()));
+        assert!(json["message"].get("block").is_some());
+    }
+
+    #[test]
+    fn update_accounts() {
+        let mut session = Session::default();
+        let subscribe = json!({
+            "action": "subscribe",
+            "topic": "confirmation",
+            "options": {"accounts": [account(0)]},
+        });
+        assert_eq!(session.handle(&subscribe.to_string()), None);
+
+        let update = json!({
+            "action": "update",
+            "topic": "confirmation",
+            "ack": true,
+            "options": {"accounts_add": [account(1)], "accounts_del": [account(0)]},
+        });
+        assert!(session.handle(&update.to_string()).is_some());
+
+        let accounts = &session.subscriptions[&Topic::Confirmation].accounts;
+        assert_eq!(accounts.len(), 1);
+        assert!(accounts.contains(&account(1)));
+    }
+}
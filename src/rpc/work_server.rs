@@ -0,0 +1,525 @@
+//! A work server speaking the `work_generate`, `work_cancel` and `work_validate` RPC actions.
+//!
+//! This is compatible with nano_node's work peers and wallets which can use a remote work server.
+//!
+//! Requests are queued and the highest difficulty is worked on first. Requests for a hash that is
+//! already queued or being worked on share the same job. Each job is worked on locally with a
+//! [WorkGenerator] and raced against any configured work peers, using whichever finishes first.
+use crate::blocks::BlockHash;
+use crate::rpc::calls::{
+    WorkCancelRequest, WorkCancelResponse, WorkGenerateRequest, WorkGenerateResponse,
+    WorkValidateRequest, WorkValidateResponse,
+};
+use crate::rpc::client::{RPCClient, RPCError, RPCRequest};
+use crate::{CancelToken, Difficulty, Subject, Work, WorkGenerator};
+use anyhow::anyhow;
+use bytes::Bytes;
+use futures::future::{select_ok, BoxFuture};
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::convert::TryFrom;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{oneshot, Notify};
+use tracing::{debug, info, warn};
+use warp::Filter;
+
+/// Work and its difficulty, or why it couldn't be generated.
+type JobResult = Result<(Work, Difficulty), String>;
+
+struct Waiter {
+    difficulty: Difficulty,
+    tx: oneshot::Sender<JobResult>,
+}
+
+struct Job {
+    difficulty: Difficulty,
+    waiters: Vec<Waiter>,
+
+    /// Set while a worker is generating this job.
+    running: Option<CancelToken>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    jobs: HashMap<BlockHash, Job>,
+
+    /// Queued jobs by difficulty, then by age. Entries are left behind when a job is raised to a
+    /// higher difficulty or cancelled, and skipped when popped.
+    heap: BinaryHeap<(u64, Reverse<u64>, [u8; BlockHash::LEN])>,
+    sequence: u64,
+}
+
+impl QueueState {
+    fn push(&mut self, hash: BlockHash, difficulty: &Difficulty) {
+        self.sequence += 1;
+        let mut bytes = [0u8; BlockHash::LEN];
+        bytes.copy_from_slice(hash.as_bytes());
+        self.heap
+            .push((difficulty.as_u64(), Reverse(self.sequence), bytes));
+    }
+}
+
+/// Jobs waiting for, or being worked on by, a worker.
+#[derive(Default)]
+struct WorkQueue {
+    state: Mutex<QueueState>,
+    notify: Notify,
+}
+
+impl WorkQueue {
+    /// Add a request, returning a receiver for the result.
+    fn submit(&self, hash: BlockHash, difficulty: Difficulty) -> oneshot::Receiver<JobResult> {
+        let (tx, rx) = oneshot::channel();
+        let mut state = self.state.lock().unwrap();
+        let waiter = Waiter {
+            difficulty: difficulty.to_owned(),
+            tx,
+        };
+        match state.jobs.get_mut(&hash) {
+            Some(job) => {
+                job.waiters.push(waiter);
+                // A running job with a lower difficulty is requeued when it finishes.
+                if job.running.is_none() && difficulty > job.difficulty {
+                    job.difficulty = difficulty.to_owned();
+                    state.push(hash, &difficulty);
+                }
+            }
+            None => {
+                let job = Job {
+                    difficulty: difficulty.to_owned(),
+                    waiters: vec![waiter],
+                    running: None,
+                };
+                state.jobs.insert(hash.to_owned(), job);
+                state.push(hash, &difficulty);
+                drop(state);
+                self.notify.notify_one();
+            }
+        }
+        rx
+    }
+
+    /// Take the highest difficulty job that isn't running, if any.
+    fn pop(&self) -> Option<(BlockHash, Difficulty, CancelToken)> {
+        let mut state = self.state.lock().unwrap();
+        while let Some((difficulty, _, bytes)) = state.heap.pop() {
+            let hash = BlockHash::try_from(bytes.as_ref()).unwrap();
+            if let Some(job) = state.jobs.get_mut(&hash) {
+                if job.running.is_none() && job.difficulty.as_u64() == difficulty {
+                    let token = CancelToken::new();
+                    job.running = Some(token.clone());
+                    return Some((hash, job.difficulty.to_owned(), token));
+                }
+            }
+        }
+        None
+    }
+
+    /// Wait for the next job.
+    async fn next(&self) -> (BlockHash, Difficulty, CancelToken) {
+        loop {
+            if let Some(job) = self.pop() {
+                return job;
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    /// Give the result to everyone waiting on the job. Waiters that need a higher difficulty than
+    /// what was found are queued again.
+    fn finish(&self, hash: &BlockHash, result: JobResult) {
+        let mut state = self.state.lock().unwrap();
+        let job = match state.jobs.remove(hash) {
+            Some(job) => job,
+            None => return,
+        };
+
+        let mut remaining = vec![];
+        for waiter in job.waiters {
+            match &result {
+                Ok((_, found)) if found < &waiter.difficulty => remaining.push(waiter),
+                _ => {
+                    let _ = waiter.tx.send(result.to_owned());
+                }
+            }
+        }
+
+        let difficulty = remaining
+            .iter()
+            .map(|w| w.difficulty.to_owned())
+            .max_by_key(|d| d.as_u64());
+        if let Some(difficulty) = difficulty {
+            state.push(hash.to_owned(), &difficulty);
+            let job = Job {
+                difficulty,
+                waiters: remaining,
+                running: None,
+            };
+            state.jobs.insert(hash.to_owned(), job);
+            drop(state);
+            self.notify.notify_one();
+        }
+    }
+
+    /// Stop a job, telling everyone waiting on it that it was cancelled.
+    fn cancel(&self, hash: &BlockHash) {
+        let state = self.state.lock().unwrap();
+        let running = match state.jobs.get(hash) {
+            Some(job) => job.running.to_owned(),
+            None => return,
+        };
+        match running {
+            // The worker calls finish when it notices.
+            Some(token) => token.cancel(),
+            None => {
+                drop(state);
+                self.finish(hash, Err("Cancelled".into()));
+            }
+        }
+    }
+}
+
+/// Serves work over HTTP using local CPU threads and optionally remote work peers.
+pub struct WorkServer {
+    queue: Arc<WorkQueue>,
+    generator: WorkGenerator,
+    local: bool,
+    workers: usize,
+    peers: Vec<RPCClient>,
+    base: Difficulty,
+}
+
+impl WorkServer {
+    pub fn new() -> Self {
+        Self {
+            queue: Arc::new(WorkQueue::default()),
+            generator: WorkGenerator::new(),
+            local: true,
+            workers: 1,
+            peers: vec![],
+            base: Difficulty::normal(),
+        }
+    }
+
+    /// CPU threads used by each worker.
+    pub fn threads(&mut self, v: usize) -> &mut Self {
+        self.generator.threads(v);
+        self
+    }
+
+    /// Generate work with local CPU threads. When off, only peers are used.
+    pub fn local(&mut self, v: bool) -> &mut Self {
+        self.local = v;
+        self
+    }
+
+    /// How many jobs can be worked on at the same time.
+    pub fn workers(&mut self, v: usize) -> &mut Self {
+        self.workers = v.max(1);
+        self
+    }
+
+    /// A remote work server to race against local generation.
+    pub fn peer(&mut self, v: RPCClient) -> &mut Self {
+        self.peers.push(v);
+        self
+    }
+
+    /// The difficulty used when a request doesn't give one, and as the base for multipliers.
+    pub fn base_difficulty(&mut self, v: Difficulty) -> &mut Self {
+        self.base = v;
+        self
+    }
+
+    /// Start the workers. This needs to be called before any work is generated.
+    pub fn start(&self) -> anyhow::Result<()> {
+        if !self.local && self.peers.is_empty() {
+            return Err(anyhow!(
+                "There are no work peers and local generation is off"
+            ));
+        }
+        let peers = Arc::new(self.peers.to_owned());
+        for _ in 0..self.workers {
+            let queue = self.queue.clone();
+            let generator = if self.local {
+                Some(self.generator.to_owned())
+            } else {
+                None
+            };
+            let peers = peers.clone();
+            tokio::spawn(async move {
+                loop {
+                    let (hash, difficulty, token) = queue.next().await;
+                    debug!("Generating work for {:?} at {:?}", hash, difficulty);
+                    let result =
+                        solve(generator.as_ref(), &peers, &hash, &difficulty, &token).await;
+                    if let Err(err) = &result {
+                        warn!("Work for {:?} failed: {}", hash, err);
+                    }
+                    queue.finish(&hash, result);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Queue a request and wait for the work.
+    pub async fn generate(
+        &self,
+        hash: BlockHash,
+        difficulty: Option<Difficulty>,
+    ) -> anyhow::Result<(Work, Difficulty)> {
+        let difficulty = difficulty.unwrap_or_else(|| self.base.to_owned());
+        let rx = self.queue.submit(hash, difficulty);
+        rx.await
+            .map_err(|_| anyhow!("Work server stopped"))?
+            .map_err(|err| anyhow!(err))
+    }
+
+    pub fn cancel(&self, hash: &BlockHash) {
+        self.queue.cancel(hash);
+    }
+
+    /// Start the workers and serve HTTP requests forever.
+    pub async fn serve(self, addr: SocketAddr) -> anyhow::Result<()> {
+        self.start()?;
+        info!("Work server listening on {}", addr);
+        warp::serve(self.routes()).run(addr).await;
+        Ok(())
+    }
+
+    fn routes(self) -> impl Filter<Extract = (String,), Error = warp::Rejection> + Clone {
+        let server = Arc::new(self);
+        warp::post()
+            .and(warp::body::content_length_limit(1024 * 16))
+            // Like nano_node, don't require a JSON content type.
+            .and(warp::body::bytes())
+            .and(warp::any().map(move || server.clone()))
+            .and_then(|body, server: Arc<WorkServer>| async move {
+                let response = match server.handle(body).await {
+                    Ok(json) => json,
+                    Err(err) => json(&RPCError {
+                        error: err.to_string(),
+                    }),
+                };
+                Ok::<_, warp::Rejection>(response)
+            })
+    }
+
+    async fn handle(&self, body: Bytes) -> anyhow::Result<String> {
+        let command: WorkCommand = serde_json::from_slice(&body)?;
+        Ok(match command {
+            WorkCommand::WorkGenerate(request) => {
+                let (work, difficulty) = self
+                    .generate(request.hash.to_owned(), request.difficulty)
+                    .await?;
+                json(&WorkGenerateResponse {
+                    hash: request.hash,
+                    work,
+                    multiplier: difficulty.to_multiplier(&self.base),
+                    difficulty,
+                })
+            }
+            WorkCommand::WorkCancel(request) => {
+                self.cancel(&request.hash);
+                json(&WorkCancelResponse::default())
+            }
+            WorkCommand::WorkValidate(request) => json(&self.validate(&request)?),
+        })
+    }
+
+    fn validate(&self, request: &WorkValidateRequest) -> anyhow::Result<WorkValidateResponse> {
+        let subject = Subject::Hash(request.hash.to_owned());
+        let flag = |v: bool| if v { "1" } else { "0" }.to_string();
+        let valid = match &request.difficulty {
+            Some(d) => Some(flag(request.work.verify(&subject, d)?)),
+            None => None,
+        };
+        let difficulty = request.work.difficulty(&subject)?;
+        Ok(WorkValidateResponse {
+            valid,
+            valid_all: flag(request.work.verify(&subject, &Difficulty::normal())?),
+            valid_receive: flag(request.work.verify(&subject, &Difficulty::receive())?),
+            multiplier: difficulty.to_multiplier(&self.base),
+            difficulty,
+        })
+    }
+}
+
+impl Default for WorkServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+enum WorkCommand {
+    WorkGenerate(WorkGenerateRequest),
+    WorkCancel(WorkCancelRequest),
+    WorkValidate(WorkValidateRequest),
+}
+
+fn json<T: Serialize>(o: &T) -> String {
+    serde_json::to_string(o).expect("Could not serialize response")
+}
+
+/// Race local generation against the peers, cancelling the others when one finishes.
+async fn solve(
+    generator: Option<&WorkGenerator>,
+    peers: &[RPCClient],
+    hash: &BlockHash,
+    difficulty: &Difficulty,
+    token: &CancelToken,
+) -> JobResult {
+    let mut candidates: Vec<BoxFuture<anyhow::Result<(Work, Difficulty)>>> = vec![];
+    if let Some(generator) = generator {
+        let mut generator = generator.to_owned();
+        generator.cancel_token(token.to_owned());
+        let subject = Subject::Hash(hash.to_owned());
+        let difficulty = difficulty.to_owned();
+        candidates.push(
+            async move {
+                let generated = generator.generate_async(subject, difficulty).await?;
+                Ok((generated.work, generated.difficulty))
+            }
+            .boxed(),
+        );
+    }
+    for peer in peers {
+        candidates.push(from_peer(peer, hash, difficulty).boxed());
+    }
+
+    let result = tokio::select! {
+        result = select_ok(candidates) => result.map(|(found, _)| found),
+        _ = cancelled(token) => Err(anyhow!("Cancelled")),
+    };
+
+    // Peers might still be working on it.
+    token.cancel();
+    for peer in peers {
+        let peer = peer.to_owned();
+        let request = WorkCancelRequest::new(hash.to_owned());
+        tokio::spawn(async move { (&request).call(&peer).await });
+    }
+
+    result.map_err(|err| err.to_string())
+}
+
+async fn from_peer(
+    peer: &RPCClient,
+    hash: &BlockHash,
+    difficulty: &Difficulty,
+) -> anyhow::Result<(Work, Difficulty)> {
+    let mut request = WorkGenerateRequest::new(hash.to_owned());
+    request.difficulty = Some(difficulty.to_owned());
+    let response = (&request).call(peer).await?;
+
+    // Don't trust the peer.
+    let subject = Subject::Hash(hash.to_owned());
+    let found = response.work.difficulty(&subject)?;
+    if !response.work.verify(&subject, difficulty)? {
+        return Err(anyhow!("Work peer returned invalid work {}", response.work));
+    }
+    Ok((response.work, found))
+}
+
+async fn cancelled(token: &CancelToken) {
+    while !token.is_cancelled() {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Multiplier;
+
+    fn hash(byte: u8) -> BlockHash {
+        BlockHash::try_from([byte; BlockHash::LEN].as_ref()).unwrap()
+    }
+
+    #[test]
+    fn queue_priority_and_dedup() {
+        let queue = WorkQueue::default();
+        let mut low = queue.submit(hash(1), Difficulty::new(10));
+        let mut high = queue.submit(hash(2), Difficulty::new(20));
+        let mut same = queue.submit(hash(1), Difficulty::new(5));
+
+        let (first, difficulty, _) = queue.pop().unwrap();
+        assert_eq!((first, difficulty), (hash(2), Difficulty::new(20)));
+        let (second, difficulty, _) = queue.pop().unwrap();
+        assert_eq!((second, difficulty), (hash(1), Difficulty::new(10)));
+        assert!(queue.pop().is_none());
+
+        queue.finish(&hash(1), Ok((Work::zero(), Difficulty::new(15))));
+        assert!(low.try_recv().unwrap().is_ok());
+        assert!(same.try_recv().unwrap().is_ok());
+
+        queue.cancel(&hash(2));
+        assert!(queue.pop().is_none());
+        queue.finish(&hash(2), Err("Cancelled".into()));
+        assert!(high.try_recv().unwrap().is_err());
+    }
+
+    #[test]
+    fn requeue_higher_difficulty() {
+        let queue = WorkQueue::default();
+        let mut low = queue.submit(hash(1), Difficulty::new(10));
+        queue.pop().unwrap();
+        let mut higher = queue.submit(hash(1), Difficulty::new(30));
+
+        queue.finish(&hash(1), Ok((Work::zero(), Difficulty::new(15))));
+        assert!(low.try_recv().unwrap().is_ok());
+        assert!(higher.try_recv().is_err());
+
+        let (_, difficulty, _) = queue.pop().unwrap();
+        assert_eq!(difficulty, Difficulty::new(30));
+    }
+
+    #[tokio::test]
+    async fn serve_requests() {
+        let mut server = WorkServer::new();
+        server
+            .threads(1)
+            .base_difficulty(Difficulty::new(0xff00000000000000));
+        server.start().unwrap();
+        let routes = server.routes();
+
+        let response = warp::test::request()
+            .method("POST")
+            .json(&serde_json::json!({"action": "work_generate", "hash": hash(1)}))
+            .reply(&routes)
+            .await;
+        let generated: WorkGenerateResponse = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(generated.hash, hash(1));
+        assert!(generated.multiplier >= Multiplier::one());
+
+        let response = warp::test::request()
+            .method("POST")
+            .json(&serde_json::json!({
+                "action": "work_validate",
+                "hash": hash(1),
+                "work": generated.work,
+                "difficulty": "ff00000000000000",
+            }))
+            .reply(&routes)
+            .await;
+        let validated: WorkValidateResponse = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(validated.valid, Some("1".into()));
+        assert_eq!(validated.difficulty, generated.difficulty);
+
+        let response = warp::test::request()
+            .method("POST")
+            .json(&serde_json::json!({"action": "account_info"}))
+            .reply(&routes)
+            .await;
+        let error: RPCError = serde_json::from_slice(response.body()).unwrap();
+        assert!(error.error.contains("unknown variant"));
+    }
+}
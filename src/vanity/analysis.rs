@@ -0,0 +1,395 @@
+//! Working out what a vanity regex can match before spending hours searching for it.
+//!
+//! Addresses only use the characters in [ALPHABET], so a regex that needs anything else, like
+//! `0`, `2`, `l`, `v` or upper case letters, will never match. The regex is parsed to find these,
+//! to pull out literal text it must start or end with for a quick check before running the regex,
+//! and to estimate how many addresses need to be checked to find a match.
+use crate::encoding::ALPHABET;
+use anyhow::anyhow;
+use rand::seq::SliceRandom;
+use regex::Regex;
+use regex_syntax::hir::{Anchor, Class, Hir, HirKind, Literal, RepetitionKind, RepetitionRange};
+
+/// The number of address characters after `nano_` and the first digit.
+const SEARCHABLE_LEN: usize = 59;
+
+/// The first digit after `nano_` is always one of these.
+const FIRST_DIGITS: &str = "13";
+
+/// How many random addresses to try when a regex is too complex to estimate directly.
+const SAMPLES: usize = 100_000;
+
+#[derive(Clone, Debug)]
+pub struct RegexMatch {
+    regex: Regex,
+    hir: Hir,
+
+    /// Text the searchable part must start with, when the regex is anchored with `^`.
+    prefix: String,
+
+    /// Text the searchable part must end with, when the regex is anchored with `$`.
+    suffix: String,
+}
+
+impl RegexMatch {
+    pub fn new(s: &str) -> anyhow::Result<Self> {
+        let regex = Regex::new(s)?;
+        let hir = regex_syntax::Parser::new().parse(s)?;
+        let (start, items, end) = split_anchors(&hir);
+        let prefix = if start {
+            literals(items.iter())
+        } else {
+            String::new()
+        };
+        let suffix = if end {
+            literals(items.iter().rev()).chars().rev().collect()
+        } else {
+            String::new()
+        };
+        Ok(Self {
+            regex,
+            hir,
+            prefix,
+            suffix,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    pub fn is_match(&self, searchable: &str) -> bool {
+        searchable.starts_with(&self.prefix)
+            && searchable.ends_with(&self.suffix)
+            && self.regex.is_match(searchable)
+    }
+
+    /// Literal text the match must start with, if the regex is anchored with `^`.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Literal text the match must end with, if the regex is anchored with `$`.
+    pub fn suffix(&self) -> &str {
+        &self.suffix
+    }
+
+    /// Make sure the regex can match at least one address.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if can_match(&self.hir) {
+            return Ok(());
+        }
+        let mut invalid = vec![];
+        invalid_literals(&self.hir, &mut invalid);
+        invalid.sort_unstable();
+        invalid.dedup();
+        let invalid: String = invalid.into_iter().collect();
+        Err(anyhow!(
+            "Your regex won't ever match because it needs characters that aren't valid: {:?}. Valid characters: {}",
+            invalid,
+            ALPHABET
+        ))
+    }
+
+    /// The average number of addresses to check to find a match, or `None` if it's too rare to
+    /// estimate.
+    ///
+    /// Regexes made of single characters and fixed repetitions, like `^ab[cd]{2}`, are calculated
+    /// exactly when anchored, and closely approximated when not. Anything else is estimated by
+    /// matching random addresses.
+    pub fn expected_attempts(&self, include_first_digit: bool) -> Option<f64> {
+        match self.fixed_probability(include_first_digit) {
+            Some(p) => Some(1f64 / p),
+            None => self.sample(include_first_digit),
+        }
+    }
+
+    fn fixed_probability(&self, include_first_digit: bool) -> Option<f64> {
+        let (start, items, end) = split_anchors(&self.hir);
+        let mut positions = vec![];
+        for item in items {
+            if !sequence(item, &mut positions) {
+                return None;
+            }
+        }
+
+        let len = SEARCHABLE_LEN + include_first_digit as usize;
+        if positions.len() > len {
+            return Some(0f64);
+        }
+        let at = |offset: usize| -> f64 {
+            positions
+                .iter()
+                .enumerate()
+                .map(|(i, chars)| {
+                    if include_first_digit && offset + i == 0 {
+                        let digits = chars.iter().filter(|c| FIRST_DIGITS.contains(**c));
+                        digits.count() as f64 / FIRST_DIGITS.len() as f64
+                    } else {
+                        chars.len() as f64 / ALPHABET.len() as f64
+                    }
+                })
+                .product()
+        };
+        let last = len - positions.len();
+        Some(match (start, end) {
+            (true, true) if last != 0 => 0f64,
+            (true, _) => at(0),
+            (_, true) => at(last),
+            // Treat each place it could match as independent, which is close enough.
+            (false, false) => 1f64 - (0..=last).map(|o| 1f64 - at(o)).product::<f64>(),
+        })
+    }
+
+    /// Match random addresses. The prefix and suffix are always put in place and their chance is
+    /// worked out separately, so that only the rest of the regex is left up to luck.
+    fn sample(&self, include_first_digit: bool) -> Option<f64> {
+        let len = SEARCHABLE_LEN + include_first_digit as usize;
+        let (prefix, suffix) = (self.prefix.chars().count(), self.suffix.chars().count());
+        if prefix + suffix > len {
+            return Some(f64::INFINITY);
+        }
+        let fixed: f64 = self
+            .prefix
+            .chars()
+            .enumerate()
+            .map(|(i, c)| match (include_first_digit && i == 0, c) {
+                (true, c) if FIRST_DIGITS.contains(c) => 1f64 / FIRST_DIGITS.len() as f64,
+                (true, _) => 0f64,
+                (false, _) => 1f64 / ALPHABET.len() as f64,
+            })
+            .product::<f64>()
+            * (ALPHABET.len() as f64).powi(-(suffix as i32));
+
+        let mut rng = rand::thread_rng();
+        let alphabet: Vec<char> = ALPHABET.chars().collect();
+        let digits: Vec<char> = FIRST_DIGITS.chars().collect();
+        let mut searchable = String::with_capacity(len);
+        let mut hits = 0;
+        for _ in 0..SAMPLES {
+            searchable.clear();
+            searchable.push_str(&self.prefix);
+            for i in prefix..len - suffix {
+                let chars = if include_first_digit && i == 0 {
+                    &digits
+                } else {
+                    &alphabet
+                };
+                searchable.push(*chars.choose(&mut rng).unwrap());
+            }
+            searchable.push_str(&self.suffix);
+            if self.regex.is_match(&searchable) {
+                hits += 1;
+            }
+        }
+        if fixed == 0f64 {
+            Some(f64::INFINITY)
+        } else if hits == 0 {
+            None
+        } else {
+            Some(SAMPLES as f64 / hits as f64 / fixed)
+        }
+    }
+}
+
+/// Whether the regex is anchored at the start and end, and the items in between.
+fn split_anchors(hir: &Hir) -> (bool, &[Hir], bool) {
+    let mut items = match hir.kind() {
+        HirKind::Concat(items) => items.as_slice(),
+        _ => std::slice::from_ref(hir),
+    };
+    let is_anchor = |h: Option<&Hir>, anchor: Anchor| match h.map(Hir::kind) {
+        Some(HirKind::Anchor(a)) => a == &anchor,
+        _ => false,
+    };
+    let start = is_anchor(items.first(), Anchor::StartText);
+    if start {
+        items = &items[1..];
+    }
+    let end = is_anchor(items.last(), Anchor::EndText);
+    if end {
+        items = &items[..items.len() - 1];
+    }
+    (start, items, end)
+}
+
+/// The literal characters at the front of `items`, until something else is found.
+fn literals<'a, I: Iterator<Item = &'a Hir>>(items: I) -> String {
+    items
+        .map(|h| match h.kind() {
+            HirKind::Literal(Literal::Unicode(c)) => Some(*c),
+            HirKind::Literal(Literal::Byte(b)) => Some(*b as char),
+            _ => None,
+        })
+        .take_while(Option::is_some)
+        .flatten()
+        .collect()
+}
+
+/// The address characters a single character expression matches, or `None` if it isn't one.
+fn chars(hir: &Hir) -> Option<Vec<char>> {
+    let alphabet = ALPHABET.chars();
+    Some(match hir.kind() {
+        HirKind::Literal(Literal::Unicode(c)) => alphabet.filter(|a| a == c).collect(),
+        HirKind::Literal(Literal::Byte(b)) => alphabet.filter(|a| *a == *b as char).collect(),
+        HirKind::Class(Class::Unicode(class)) => alphabet
+            .filter(|a| class.iter().any(|r| r.start() <= *a && *a <= r.end()))
+            .collect(),
+        HirKind::Class(Class::Bytes(class)) => alphabet
+            .filter(|a| {
+                let a = *a as u8;
+                class.iter().any(|r| r.start() <= a && a <= r.end())
+            })
+            .collect(),
+        HirKind::Group(group) => return chars(&group.hir),
+        _ => return None,
+    })
+}
+
+fn min_repetitions(kind: &RepetitionKind) -> u32 {
+    match kind {
+        RepetitionKind::ZeroOrOne | RepetitionKind::ZeroOrMore => 0,
+        RepetitionKind::OneOrMore => 1,
+        RepetitionKind::Range(RepetitionRange::Exactly(n))
+        | RepetitionKind::Range(RepetitionRange::AtLeast(n))
+        | RepetitionKind::Range(RepetitionRange::Bounded(n, _)) => *n,
+    }
+}
+
+/// Whether an expression can match some text made of address characters.
+fn can_match(hir: &Hir) -> bool {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Anchor(_) | HirKind::WordBoundary(_) => true,
+        HirKind::Literal(_) | HirKind::Class(_) => !chars(hir).unwrap_or_default().is_empty(),
+        HirKind::Repetition(r) => min_repetitions(&r.kind) == 0 || can_match(&r.hir),
+        HirKind::Group(group) => can_match(&group.hir),
+        HirKind::Concat(items) => items.iter().all(can_match),
+        HirKind::Alternation(items) => items.iter().any(can_match),
+    }
+}
+
+fn invalid_literals(hir: &Hir, invalid: &mut Vec<char>) {
+    match hir.kind() {
+        HirKind::Literal(Literal::Unicode(c)) if !ALPHABET.contains(*c) => invalid.push(*c),
+        HirKind::Literal(Literal::Byte(b)) if !ALPHABET.contains(*b as char) => {
+            invalid.push(*b as char)
+        }
+        // Only a class without any address characters stops a match. Negated classes can cover
+        // most of Unicode, so only the ASCII characters are listed.
+        HirKind::Class(class) if chars(hir).unwrap_or_default().is_empty() => {
+            let ascii = (0..128u8).map(char::from).filter(char::is_ascii_graphic);
+            match class {
+                Class::Unicode(class) => invalid.extend(
+                    ascii.filter(|c| class.iter().any(|r| r.start() <= *c && *c <= r.end())),
+                ),
+                Class::Bytes(class) => invalid.extend(ascii.filter(|c| {
+                    let c = *c as u8;
+                    class.iter().any(|r| r.start() <= c && c <= r.end())
+                })),
+            }
+        }
+        HirKind::Repetition(r) => invalid_literals(&r.hir, invalid),
+        HirKind::Group(group) => invalid_literals(&group.hir, invalid),
+        HirKind::Concat(items) | HirKind::Alternation(items) => {
+            for item in items {
+                invalid_literals(item, invalid);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Add the characters each position can be, if the expression is a fixed number of single
+/// characters.
+fn sequence(hir: &Hir, positions: &mut Vec<Vec<char>>) -> bool {
+    if let Some(chars) = chars(hir) {
+        positions.push(chars);
+        return true;
+    }
+    match hir.kind() {
+        HirKind::Empty => true,
+        HirKind::Group(group) => sequence(&group.hir, positions),
+        HirKind::Concat(items) => items.iter().all(|item| sequence(item, positions)),
+        HirKind::Repetition(r) => match &r.kind {
+            RepetitionKind::Range(RepetitionRange::Exactly(n)) => {
+                (0..*n).all(|_| sequence(&r.hir, positions))
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        assert!(RegexMatch::new("^abc").unwrap().validate().is_ok());
+        assert!(RegexMatch::new("z(0|z)").unwrap().validate().is_ok());
+        assert!(RegexMatch::new("l.*x").unwrap().validate().is_err());
+        assert!(RegexMatch::new("[02v]").unwrap().validate().is_err());
+        assert!(RegexMatch::new("ABC").unwrap().validate().is_err());
+        assert!(RegexMatch::new("(?i)ABC").unwrap().validate().is_ok());
+        assert!(RegexMatch::new("a0?").unwrap().validate().is_ok());
+
+        // The characters to blame, from literals and classes.
+        let invalid = |s: &str| {
+            let err = RegexMatch::new(s).unwrap().validate().unwrap_err();
+            err.to_string().split('"').nth(1).unwrap().to_owned()
+        };
+        assert_eq!(invalid("a0l"), "0l");
+        assert_eq!(invalid("[02v]"), "02v");
+        assert_eq!(invalid("(?-u)[02v]"), "02v");
+        assert_eq!(invalid("a[0-2]v"), "v");
+    }
+
+    #[test]
+    fn prefix_and_suffix() {
+        let re = RegexMatch::new("^ab.*cd$").unwrap();
+        assert_eq!(re.prefix(), "ab");
+        assert_eq!(re.suffix(), "cd");
+        assert!(re.is_match("abxxcd"));
+        assert!(!re.is_match("xbxxcd"));
+
+        let re = RegexMatch::new("ab").unwrap();
+        assert_eq!(re.prefix(), "");
+        assert_eq!(re.suffix(), "");
+    }
+
+    #[test]
+    fn expected_attempts() {
+        let attempts = |s: &str, digit: bool| {
+            RegexMatch::new(s)
+                .unwrap()
+                .expected_attempts(digit)
+                .unwrap()
+        };
+        assert_eq!(attempts("^ab", false), 1024f64);
+        assert_eq!(attempts("^[ab]c$", false), f64::INFINITY);
+        assert_eq!(attempts("z{3}$", false), 32768f64);
+        assert_eq!(attempts("^1z", true), 64f64);
+        assert_eq!(attempts("^[13]", true), 1f64);
+
+        // There are 59 places it could be found.
+        let anywhere = attempts("z", false);
+        assert!(anywhere > 1.1 && anywhere < 1.3, "{}", anywhere);
+        let pair = attempts("zz", false);
+        assert!(pair > 17.5 && pair < 19f64, "{}", pair);
+
+        // Sampled, so only roughly right: 1 in 32 for each of the two positions.
+        let sampled = attempts("^(a|b)x*", false);
+        assert!(sampled > 12f64 && sampled < 20f64, "{}", sampled);
+
+        // The prefix and suffix are exact, and about 7 in 8 have a z in the middle somewhere.
+        let sampled = attempts("^ab.*z.*c$", false);
+        let exact = 32f64.powi(3) / (1f64 - (31f64 / 32f64).powi(56));
+        assert!(
+            (sampled / exact - 1f64).abs() < 0.1,
+            "{} {}",
+            sampled,
+            exact
+        );
+    }
+}
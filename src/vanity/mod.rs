@@ -0,0 +1,630 @@
+//! Search for secrets that generate addresses matching one or more patterns.
+//!
+//! Every generated secret is derived into addresses for a range of account indexes, and each
+//! address is checked against every pattern that still needs results. This is much faster than a
+//! separate search per pattern, since deriving keys is the slow part.
+mod analysis;
+mod split_key;
+
+pub use analysis::RegexMatch;
+pub use split_key::{Offset, SplitKey};
+
+use crate::encoding::ALPHABET;
+use crate::phrase::{Language, MnemonicType};
+use crate::{Address, Phrase, Private, Seed};
+use anyhow::anyhow;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tracing::{info, trace};
+
+/// Characters per address after the prefix and the first digit, each with 32 possibilities.
+const BASE: f64 = 32f64;
+
+#[derive(Clone)]
+pub enum SecretType {
+    Phrase {
+        language: Language,
+        words: MnemonicType,
+    },
+    Seed,
+    Private,
+
+    /// Offsets for someone else's public key. See [SplitKey].
+    SplitKey(SplitKey),
+}
+
+#[derive(Debug, Clone)]
+pub enum Secret {
+    Phrase(Phrase),
+    Seed(Seed),
+    Private(Private),
+    Offset(Offset),
+}
+
+impl Display for Secret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Secret::Phrase(p) => write!(f, "{}", p),
+            Secret::Seed(s) => write!(f, "{}", s),
+            Secret::Private(p) => write!(f, "{}", p),
+            Secret::Offset(o) => write!(f, "{}", o),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SecretResult {
+    pub secret: Secret,
+    pub address: Address,
+
+    /// The account index the address was derived from. Always 0 for private keys and offsets.
+    pub index: u32,
+
+    /// The position of the pattern that matched, in the order they were added.
+    pub pattern: usize,
+}
+
+#[derive(Clone, Copy)]
+enum SearchOffset {
+    FirstDigit = 5,
+    SkipFirstDigit = 6,
+}
+
+/// A [Match] with an optional number of results to stop at.
+#[derive(Clone)]
+pub struct Pattern {
+    pub matches: Match,
+    pub limit: Option<usize>,
+}
+
+impl Pattern {
+    pub fn new(matches: Match) -> Self {
+        Self {
+            matches,
+            limit: None,
+        }
+    }
+
+    /// Stop looking for this pattern after this many results.
+    pub fn limit(&mut self, v: usize) -> &mut Self {
+        self.limit = Some(v);
+        self
+    }
+}
+
+impl From<Match> for Pattern {
+    fn from(matches: Match) -> Self {
+        Self::new(matches)
+    }
+}
+
+#[derive(Clone)]
+pub struct Vanity {
+    secret_type: SecretType,
+    patterns: Vec<Pattern>,
+    index: u32,
+    scan: u32,
+    tasks: Option<usize>,
+    search_offset: SearchOffset,
+
+    /// How many attempts to loop through before checking if the channel is closed.
+    ///
+    /// The bigger the number here, the slower it will be to gracefully quit when requested.
+    check_count: usize,
+}
+
+impl Vanity {
+    pub fn new(secret_type: SecretType, matches: Match) -> Self {
+        Self::with_patterns(secret_type, vec![Pattern::new(matches)])
+    }
+
+    pub fn with_patterns(secret_type: SecretType, patterns: Vec<Pattern>) -> Self {
+        Self {
+            secret_type,
+            patterns,
+            index: 0,
+            scan: 1,
+            tasks: None,
+            check_count: 10000,
+            search_offset: SearchOffset::SkipFirstDigit,
+        }
+    }
+
+    /// Search for more patterns at the same time.
+    pub fn add_pattern<P: Into<Pattern>>(&mut self, v: P) -> &mut Vanity {
+        self.patterns.push(v.into());
+        self
+    }
+
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+
+    /// Number of tasks to spawn.
+    pub fn tasks(&mut self, v: usize) -> &mut Vanity {
+        self.tasks = Some(v);
+        self
+    }
+
+    /// The first account index of seeds and phrases to check. Defaults to 0.
+    pub fn index(&mut self, v: u32) -> &mut Vanity {
+        self.index = v;
+        self
+    }
+
+    /// Check this many account indexes of each seed or phrase, starting from [Vanity::index].
+    /// Private keys and offsets only have one address, so this is ignored for them.
+    pub fn scan(&mut self, v: u32) -> &mut Vanity {
+        self.scan = v.max(1);
+        self
+    }
+
+    /// Should the search include the first number after `nano_` (1 or 3)?
+    pub fn include_first_digit(&mut self, v: bool) -> &mut Vanity {
+        self.search_offset = if v {
+            SearchOffset::FirstDigit
+        } else {
+            SearchOffset::SkipFirstDigit
+        };
+        self
+    }
+
+    /// The average number of addresses to check to find a match for a pattern, if it can be
+    /// estimated.
+    pub fn expected_attempts(&self, pattern: usize) -> Option<f64> {
+        let include_first_digit = matches!(self.search_offset, SearchOffset::FirstDigit);
+        self.patterns[pattern]
+            .matches
+            .expected_attempts(include_first_digit)
+    }
+
+    /// Spawn some tasks to try to find a vanity address.
+    ///
+    /// This returns a [Receiver] containing [SecretResult]s for each found address, and a
+    /// [Arc] [RwLock] counter of addresses checked. The channel is closed once every pattern has
+    /// reached its limit.
+    pub async fn start(self) -> anyhow::Result<(Receiver<SecretResult>, Arc<RwLock<usize>>)> {
+        self.validate()?;
+        let cpus = num_cpus::get();
+        let attempts = Arc::new(RwLock::new(0usize));
+        let found: Arc<Vec<AtomicUsize>> =
+            Arc::new(self.patterns.iter().map(|_| AtomicUsize::new(0)).collect());
+        let tasks = self.tasks.unwrap_or(cpus);
+        let (tx, rx) = tokio::sync::mpsc::channel::<SecretResult>(100);
+        info!("Starting {} vanity tasks", tasks);
+        for _ in 0..tasks {
+            let v = self.clone();
+            let tx_ = tx.clone();
+            let counter_ = attempts.clone();
+            let found_ = found.clone();
+            thread::spawn(move || {
+                v.single_threaded_worker(tx_, counter_, found_);
+            });
+        }
+        Ok((rx, attempts))
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.patterns.is_empty() {
+            return Err(anyhow!("No patterns to search for"));
+        }
+        for pattern in &self.patterns {
+            pattern.matches.validate()?;
+        }
+        Ok(())
+    }
+
+    fn single_threaded_worker(
+        &self,
+        tx: Sender<SecretResult>,
+        counter: Arc<RwLock<usize>>,
+        found: Arc<Vec<AtomicUsize>>,
+    ) {
+        while !tx.is_closed() {
+            if self.all_done(&found) {
+                trace!("Exiting vanity task since all patterns are done.");
+                return;
+            }
+            let mut checked = 0;
+            for _ in 0..self.check_count {
+                checked += self.single_attempt(&found, |result| {
+                    // Only send up to the limit, even if other tasks found one at the same time.
+                    let limit = self.patterns[result.pattern].limit.unwrap_or(usize::MAX);
+                    if found[result.pattern].fetch_add(1, Ordering::Relaxed) >= limit {
+                        return true;
+                    }
+                    tx.blocking_send(result).is_ok()
+                });
+                if tx.is_closed() {
+                    trace!("Exiting vanity task due to closed channel while sending.");
+                    return;
+                }
+            }
+            let mut c = counter.write().expect("Could not lock counter for writing");
+            *c += checked;
+            drop(c);
+        }
+        trace!("Exiting vanity task due to closed channel.");
+    }
+
+    fn is_done(&self, found: &[AtomicUsize], pattern: usize) -> bool {
+        match self.patterns[pattern].limit {
+            Some(limit) => found[pattern].load(Ordering::Relaxed) >= limit,
+            None => false,
+        }
+    }
+
+    fn all_done(&self, found: &[AtomicUsize]) -> bool {
+        (0..self.patterns.len()).all(|p| self.is_done(found, p))
+    }
+
+    /// Generate a secret and check its addresses against the patterns that aren't done, calling
+    /// `send` for each match until it returns false. Returns the number of addresses checked.
+    fn single_attempt<F>(&self, found: &[AtomicUsize], mut send: F) -> usize
+    where
+        F: FnMut(SecretResult) -> bool,
+    {
+        let (secret, addresses): (Secret, Vec<(u32, Address)>) = match &self.secret_type {
+            SecretType::Seed => {
+                let seed = Seed::random();
+                // This should never panic because the public key comes from a legit private key.
+                let addresses = self
+                    .indexes()
+                    .map(|i| (i, seed.derive(i).to_address().unwrap()))
+                    .collect();
+                (Secret::Seed(seed), addresses)
+            }
+            SecretType::Private => {
+                let private = Private::random();
+                // This should never panic because the public key comes from a legit private key.
+                let address = private.to_address().unwrap();
+                (Secret::Private(private), vec![(0, address)])
+            }
+            SecretType::SplitKey(split_key) => {
+                let offset = Offset::random();
+                let address = split_key.public(&offset).to_address();
+                (Secret::Offset(offset), vec![(0, address)])
+            }
+            SecretType::Phrase { language, words } => {
+                // This should never panic because the public key comes from a legit private key.
+                let phrase = Phrase::random(words.to_owned(), language.to_owned());
+                let addresses = self
+                    .indexes()
+                    .map(|i| (i, phrase.to_private(i, "").unwrap().to_address().unwrap()))
+                    .collect();
+                (Secret::Phrase(phrase), addresses)
+            }
+        };
+
+        let checked = addresses.len();
+        let offset = self.search_offset as usize;
+        for (index, address) in addresses {
+            let addr = address.to_string();
+            let searchable = &addr[offset..];
+            for (pattern, p) in self.patterns.iter().enumerate() {
+                if self.is_done(found, pattern) || !p.matches.is_match(searchable) {
+                    continue;
+                }
+                let result = SecretResult {
+                    secret: secret.to_owned(),
+                    address: address.to_owned(),
+                    index,
+                    pattern,
+                };
+                if !send(result) {
+                    return checked;
+                }
+            }
+        }
+        checked
+    }
+
+    fn indexes(&self) -> std::ops::Range<u32> {
+        self.index..self.index.saturating_add(self.scan)
+    }
+
+    /// Block until all results are collected up to a size of `limit`.
+    pub async fn collect(self, mut limit: usize) -> anyhow::Result<Vec<SecretResult>> {
+        let (mut rx, _) = self.start().await?;
+        let mut collected = vec![];
+        while let Some(result) = rx.recv().await {
+            collected.push(result);
+            limit -= 1;
+            if limit == 0 {
+                break;
+            }
+        }
+        Ok(collected)
+    }
+}
+
+#[derive(Clone)]
+pub enum Match {
+    StartOrEnd(String),
+    Start(String),
+    End(String),
+    Regex(RegexMatch),
+}
+
+impl Match {
+    pub fn start_or_end(s: &str) -> Self {
+        Match::StartOrEnd(s.into())
+    }
+
+    pub fn start(s: &str) -> Self {
+        Match::Start(s.into())
+    }
+
+    pub fn end(s: &str) -> Self {
+        Match::End(s.into())
+    }
+
+    pub fn regex(s: &str) -> anyhow::Result<Self> {
+        Ok(Match::Regex(RegexMatch::new(s)?))
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let s = match self {
+            Match::StartOrEnd(s) => s,
+            Match::Start(s) => s,
+            Match::End(s) => s,
+            Match::Regex(re) => return re.validate(),
+        };
+        let re = regex::Regex::new(&format!("^[{}]*$", ALPHABET)).unwrap();
+        if re.is_match(s) {
+            Ok(())
+        } else {
+            Err(anyhow!("Your search won't ever match because it has characters that aren't valid. Valid characters: {}", ALPHABET))
+        }
+    }
+
+    fn is_match(&self, searchable: &str) -> bool {
+        match self {
+            Match::StartOrEnd(s) => searchable.starts_with(s) || searchable.ends_with(s),
+            Match::Start(s) => searchable.starts_with(s),
+            Match::End(s) => searchable.ends_with(s),
+            Match::Regex(re) => re.is_match(searchable),
+        }
+    }
+
+    /// The average number of addresses to check to find one that matches, or `None` if it can't
+    /// be estimated.
+    ///
+    /// Every character after the first digit is one of 32, and the first digit is one of `1` or
+    /// `3`.
+    pub fn expected_attempts(&self, include_first_digit: bool) -> Option<f64> {
+        let start = |s: &str| {
+            let mut chars = s.chars();
+            if !include_first_digit {
+                return BASE.powi(-(s.len() as i32));
+            }
+            match chars.next() {
+                Some('1') | Some('3') => 0.5 * BASE.powi(-(chars.count() as i32)),
+                Some(_) => 0f64,
+                None => 1f64,
+            }
+        };
+        let end = |s: &str| BASE.powi(-(s.len() as i32));
+        let probability = match self {
+            Match::Start(s) => start(s),
+            Match::End(s) => end(s),
+            Match::StartOrEnd(s) => {
+                let (start, end) = (start(s), end(s));
+                start + end - start * end
+            }
+            Match::Regex(re) => return re.expected_attempts(include_first_digit),
+        };
+        Some(1f64 / probability)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn vanitize_start_or_end() {
+        let vanity = Vanity::new(SecretType::Seed, Match::start_or_end("g"));
+        let limit = 20; // Should be enough for 1 in a million chance of this test failing.
+        let results = vanity.collect(limit).await.unwrap();
+        assert_eq!(results.len(), limit);
+        let mut has_start = false;
+        let mut has_end = false;
+        for result in results {
+            let addr = result.address.to_string();
+            if addr[6..].starts_with("g") {
+                has_start = true;
+            }
+            if addr.ends_with("g") {
+                has_end = true;
+            }
+        }
+        assert!(has_start);
+        assert!(has_end);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn vanitize_start() {
+        let results = Vanity::new(SecretType::Seed, Match::start("z"))
+            .collect(1)
+            .await
+            .unwrap();
+        assert_eq!(&results[0].address.to_string()[6..7], "z");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn vanitize_end() {
+        let results = Vanity::new(SecretType::Seed, Match::end("z"))
+            .collect(1)
+            .await
+            .unwrap();
+        assert!(&results[0].address.to_string().ends_with("z"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn vanitize_regex() {
+        let results = Vanity::new(SecretType::Seed, Match::regex("z.*z.*z").unwrap())
+            .collect(1)
+            .await
+            .unwrap();
+        let addr = &results[0].address.to_string();
+        dbg!(&addr);
+        assert!(addr.matches("z").count() >= 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn vanitize_private() {
+        let results = Vanity::new(SecretType::Private, Match::end("zz"))
+            .collect(1)
+            .await
+            .unwrap();
+        let result = &results[0];
+
+        let addr = &result.address.to_string();
+        dbg!(&addr);
+        assert!(addr.ends_with("zz"));
+        if let Secret::Private(private) = &result.secret {
+            assert_eq!(addr, &private.to_address().unwrap().to_string());
+        } else {
+            assert!(false, "Did not get a private key");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn vanitize_first_digit() {
+        let mut vanity = Vanity::new(SecretType::Private, Match::start("1z"));
+        vanity.include_first_digit(true);
+        let results = vanity.collect(1).await.unwrap();
+        let result = &results[0];
+
+        let addr = &result.address.to_string();
+        dbg!(&addr);
+        assert_eq!(&addr[5..7], "1z");
+    }
+
+    // Phrase is waaaay to slow to test.
+    // #[tokio::test(flavor = "multi_thread")]
+    // async fn vanitize_phrase() {
+    //     let results = Vanity::new(
+    //         SecretType::Phrase {
+    //             language: Language::Japanese,
+    //             words: MnemonicType::Words24,
+    //         },
+    //         Match::end("z"),
+    //     )
+    //     .collect(1)
+    //     .await
+    //     .unwrap();
+    //     let result = &results[0];
+    //
+    //     let addr = &result.address.to_string();
+    //     dbg!(&addr);
+    //     assert!(addr.ends_with("z"));
+    //     if let Secret::Phrase(phrase) = &result.secret {
+    //         assert_eq!(
+    //             addr,
+    //             &phrase
+    //                 .to_private(0, "")
+    //                 .unwrap()
+    //                 .to_address()
+    //                 .unwrap()
+    //                 .to_string()
+    //         );
+    //     } else {
+    //         assert!(false, "Did not get a phrase");
+    //     }
+    // }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn multiple_patterns_and_indexes() {
+        let mut start = Pattern::new(Match::start("z"));
+        start.limit(3);
+        let mut end = Pattern::new(Match::end("zz"));
+        end.limit(1);
+        let mut vanity = Vanity::with_patterns(SecretType::Seed, vec![start]);
+        vanity.add_pattern(end).index(5).scan(3);
+        let results = vanity.collect(10).await.unwrap();
+
+        // The channel closes once both patterns reach their limits.
+        assert_eq!(results.iter().filter(|r| r.pattern == 0).count(), 3);
+        assert_eq!(results.iter().filter(|r| r.pattern == 1).count(), 1);
+        for result in results {
+            let addr = result.address.to_string();
+            assert!((5..8).contains(&result.index));
+            match (&result.pattern, &result.secret) {
+                (0, _) => assert_eq!(&addr[6..7], "z"),
+                (1, _) => assert!(addr.ends_with("zz")),
+                _ => unreachable!(),
+            }
+            if let Secret::Seed(seed) = &result.secret {
+                assert_eq!(
+                    seed.derive(result.index).to_address().unwrap(),
+                    result.address
+                );
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn stops_when_all_patterns_are_done() {
+        let mut a = Pattern::new(Match::start("a"));
+        a.limit(2);
+        let mut b = Pattern::new(Match::end("b"));
+        b.limit(1);
+        let vanity = Vanity::with_patterns(SecretType::Private, vec![a, b]);
+        let (mut rx, _) = vanity.start().await.unwrap();
+        let mut results = vec![];
+        while let Some(result) = rx.recv().await {
+            results.push(result.pattern);
+        }
+        results.sort_unstable();
+        assert_eq!(results, vec![0, 0, 1]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn vanitize_split_key() {
+        let private = Seed::random().derive(0);
+        let split_key = SplitKey::new(&private.to_public().unwrap()).unwrap();
+        let results = Vanity::new(SecretType::SplitKey(split_key), Match::end("zz"))
+            .collect(1)
+            .await
+            .unwrap();
+        let result = &results[0];
+        assert!(result.address.to_string().ends_with("zz"));
+        if let Secret::Offset(offset) = &result.secret {
+            let combined = offset.combine(&private).unwrap();
+            assert_eq!(combined.to_address(), result.address);
+        } else {
+            assert!(false, "Did not get an offset");
+        }
+    }
+
+    #[test]
+    fn expected_attempts() {
+        assert_eq!(Match::start("ab").expected_attempts(false), Some(1024f64));
+        assert_eq!(Match::end("a").expected_attempts(true), Some(32f64));
+        assert_eq!(Match::start("1a").expected_attempts(true), Some(64f64));
+        assert_eq!(
+            Match::start("2a").expected_attempts(true),
+            Some(f64::INFINITY)
+        );
+        let either = Match::start_or_end("a").expected_attempts(false).unwrap();
+        assert!(either > 16f64 && either < 17f64);
+        assert_eq!(
+            Match::regex("^ab").unwrap().expected_attempts(false),
+            Some(1024f64)
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let v = Vanity::new(SecretType::Private, Match::start("l"));
+        assert!(v.validate().is_err());
+
+        let v = Vanity::new(SecretType::Private, Match::regex("^z.*l$").unwrap());
+        assert!(v.validate().is_err());
+    }
+}
@@ -0,0 +1,105 @@
+//! Split-key vanity addresses, searched for on behalf of someone else.
+//!
+//! The customer sends their public key `A`, and the searcher looks for an offset `k` where the
+//! address of `A + k·G` matches. The customer adds `k` to their own secret scalar `a` to get the
+//! key for that address. The searcher never sees `a`, so never knows the final key.
+//!
+//! The combined key isn't the hash of a seed, so it can only be used as an [ExpandedPrivate].
+use crate::encoding::blake2b;
+use crate::{hexify, Error, ExpandedPrivate, Private, Public};
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+use curve25519_dalek::edwards::EdwardsPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand::RngCore;
+
+/// A scalar to add to a customer's key, found by a split-key vanity search.
+#[derive(Clone)]
+pub struct Offset([u8; Offset::LEN]);
+
+hexify!(Offset, "offset");
+
+impl Offset {
+    pub const LEN: usize = 32;
+
+    pub fn random() -> Self {
+        let mut wide = [0u8; 64];
+        rand::thread_rng().fill_bytes(&mut wide);
+        Self(Scalar::from_bytes_mod_order_wide(&wide).to_bytes())
+    }
+
+    fn scalar(&self) -> Scalar {
+        Scalar::from_bytes_mod_order(self.0)
+    }
+
+    /// The public key that the customer's key will have after adding this offset.
+    pub fn apply(&self, public: &Public) -> Result<Public, Error> {
+        Ok(SplitKey::new(public)?.public(self))
+    }
+
+    /// Add this offset to the customer's private key.
+    pub fn combine(&self, private: &Private) -> Result<ExpandedPrivate, Error> {
+        self.combine_expanded(&private.to_expanded()?)
+    }
+
+    /// Add this offset to a key that's already expanded, e.g. from an earlier combination.
+    pub fn combine_expanded(&self, expanded: &ExpandedPrivate) -> Result<ExpandedPrivate, Error> {
+        let scalar = expanded.scalar() + self.scalar();
+
+        // Reusing the nonce prefix would sign a message with the same R under both keys, and
+        // since the offset is public, the two signatures would reveal the scalar.
+        let mut data = expanded.nonce().to_vec();
+        data.extend_from_slice(&self.0);
+        let nonce = blake2b(expanded.nonce().len(), &data);
+
+        Ok(ExpandedPrivate::from_parts(scalar, &nonce))
+    }
+}
+
+/// The customer's public key to search from.
+#[derive(Clone)]
+pub struct SplitKey {
+    point: EdwardsPoint,
+}
+
+impl SplitKey {
+    pub fn new(public: &Public) -> Result<Self, Error> {
+        Ok(Self {
+            point: public.to_point()?,
+        })
+    }
+
+    pub(crate) fn public(&self, offset: &Offset) -> Public {
+        let point = self.point + &offset.scalar() * &ED25519_BASEPOINT_TABLE;
+        Public::from(point.compress())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Seed;
+
+    #[test]
+    fn combine() {
+        let message = [1, 2, 3];
+        let private = Seed::random().derive(0);
+        let offset = Offset::random();
+        let public = offset.apply(&private.to_public().unwrap()).unwrap();
+
+        let combined = offset.combine(&private).unwrap();
+        assert_eq!(combined.to_public(), public);
+        let signature = combined.sign(&message).unwrap();
+        assert!(public.verify(&message, &signature).is_ok());
+    }
+
+    #[test]
+    fn fresh_nonce() {
+        let message = [1, 2, 3];
+        let private = Seed::random().derive(0);
+        let combined = Offset::random().combine(&private).unwrap();
+
+        let original = private.sign(&message).unwrap();
+        let signature = combined.sign(&message).unwrap();
+        assert_ne!(original.as_bytes()[..32], signature.as_bytes()[..32]);
+    }
+}
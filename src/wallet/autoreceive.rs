@@ -0,0 +1,344 @@
+//! Automatically receive pending blocks for the accounts of a [Signer].
+//!
+//! Every poll, each account is checked for pending blocks above a threshold. A receive block (or
+//! an open block for new accounts) is built and published with a [BlockBuilder], which generates
+//! work for the account's next block in the background, so that the next receive doesn't have to
+//! wait for it. Give it a [WorkCache] file to keep that work across restarts.
+//!
+//! A journal of received blocks is appended to a file so a restart won't attempt to receive the
+//! same pending block again while the node is still catching up.
+use crate::blocks::{BlockHash, Subtype};
+use crate::wallet::{AccountProvider, AccountState, BlockBuilder, Signer, WorkCache};
+use crate::{Address, Difficulty, Public, Raw};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::fs::OpenOptions;
+use tokio::io::AsyncWriteExt;
+use tracing::{error, info, warn};
+
+/// A single received block, stored as one JSON object per line in the journal.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub account: Address,
+    /// The pending send block that was received.
+    pub source: BlockHash,
+    /// The receive or open block that was published.
+    pub block: BlockHash,
+    pub amount: Raw,
+}
+
+/// Append only record of received blocks.
+pub struct Journal {
+    path: PathBuf,
+    received: HashSet<BlockHash>,
+}
+
+impl Journal {
+    /// Load an existing journal, or start a new one if the file doesn't exist.
+    pub async fn open<P: Into<PathBuf>>(path: P) -> anyhow::Result<Self> {
+        let path = path.into();
+        let mut received = HashSet::new();
+        if path.exists() {
+            let contents = tokio::fs::read_to_string(&path)
+                .await
+                .with_context(|| format!("Reading journal {:?}", &path))?;
+            for (line_no, line) in contents.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let entry: JournalEntry = serde_json::from_str(line)
+                    .with_context(|| format!("Journal {:?} line {}", &path, line_no + 1))?;
+                received.insert(entry.source);
+            }
+        }
+        Ok(Self { path, received })
+    }
+
+    pub fn contains(&self, source: &BlockHash) -> bool {
+        self.received.contains(source)
+    }
+
+    pub async fn append(&mut self, entry: &JournalEntry) -> anyhow::Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .with_context(|| format!("Opening journal {:?}", &self.path))?;
+        file.write_all(line.as_bytes()).await?;
+        file.sync_data().await?;
+        self.received.insert(entry.source.to_owned());
+        Ok(())
+    }
+}
+
+pub struct AutoReceive {
+    provider: Arc<dyn AccountProvider>,
+    signer: Arc<dyn Signer>,
+    builder: BlockBuilder,
+    journal: Journal,
+    accounts: u32,
+    threshold: Raw,
+    count: u64,
+}
+
+impl AutoReceive {
+    pub fn new(
+        provider: Arc<dyn AccountProvider>,
+        signer: Arc<dyn Signer>,
+        journal: Journal,
+    ) -> Self {
+        Self {
+            builder: BlockBuilder::new(provider.clone(), signer.clone()),
+            provider,
+            signer,
+            journal,
+            accounts: 1,
+            threshold: Raw::from(1),
+            count: 10,
+        }
+    }
+
+    /// Watch the first `v` accounts of the signer.
+    pub fn accounts(&mut self, v: u32) -> &mut Self {
+        self.accounts = v;
+        self
+    }
+
+    /// Ignore pending blocks with an amount smaller than this.
+    pub fn threshold(&mut self, v: Raw) -> &mut Self {
+        self.threshold = v;
+        self
+    }
+
+    /// The maximum pending blocks to receive per account per poll.
+    pub fn count(&mut self, v: u64) -> &mut Self {
+        self.count = v;
+        self
+    }
+
+    /// The representative used for open blocks. Defaults to the account itself.
+    pub fn representative(&mut self, v: Public) -> &mut Self {
+        self.builder.representative(v);
+        self
+    }
+
+    /// The work difficulty for generated blocks.
+    pub fn difficulty(&mut self, v: Difficulty) -> &mut Self {
+        self.builder.receive_difficulty(v);
+        self
+    }
+
+    /// Where work for the next block of each account is kept. Defaults to memory.
+    pub fn work_cache(&mut self, v: WorkCache) -> &mut Self {
+        self.builder.work_cache(v);
+        self
+    }
+
+    /// Poll forever, waiting `interval` between each poll. Errors are logged and retried.
+    pub async fn run(&mut self, interval: Duration) -> anyhow::Result<()> {
+        loop {
+            if let Err(err) = self.poll().await {
+                error!("Auto receive failed: {:?}", err);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Receive pending blocks for all accounts once, returning the journal entries written.
+    ///
+    /// An account that fails is logged and skipped, so it doesn't hold up the others.
+    pub async fn poll(&mut self) -> anyhow::Result<Vec<JournalEntry>> {
+        let mut received = vec![];
+        for index in 0..self.accounts {
+            let public = self.signer.public(index).await?;
+            match self.poll_account(index, &public).await {
+                Ok(entries) => received.extend(entries),
+                Err(err) => warn!("Auto receive failed for account {}: {:?}", index, err),
+            }
+        }
+        Ok(received)
+    }
+
+    async fn poll_account(
+        &mut self,
+        index: u32,
+        public: &Public,
+    ) -> anyhow::Result<Vec<JournalEntry>> {
+        let address = public.to_address();
+        let pending = self
+            .provider
+            .pending(&address, &self.threshold, self.count)
+            .await?;
+
+        let mut received = vec![];
+        let mut state = self.provider.account_info(&address).await?;
+        for p in pending {
+            if self.journal.contains(&p.hash) {
+                warn!(
+                    "Skipping {} which was already received according to the journal",
+                    p.hash
+                );
+                continue;
+            }
+
+            let (_, block) = self
+                .builder
+                .receive(index, public, state.as_ref(), &p)
+                .await?;
+            let hash = block.hash.to_owned();
+            info!("Received {} raw from {} into {}", p.amount, p.hash, address);
+
+            let entry = JournalEntry {
+                account: address.to_owned(),
+                source: p.hash,
+                block: hash.to_owned(),
+                amount: p.amount,
+            };
+            self.journal.append(&entry).await?;
+            received.push(entry);
+
+            state = Some(AccountState {
+                frontier: hash,
+                balance: block.balance,
+                representative: block.representative,
+            });
+        }
+
+        // Also covers blocks published before a restart, or by another wallet.
+        if let Some(state) = &state {
+            self.builder
+                .precompute(public, Some(state), Subtype::Receive);
+        }
+        Ok(received)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::{MemoryProvider, Pending, Wallet};
+    use crate::Seed;
+    use std::convert::TryFrom;
+    use std::fs::remove_file;
+
+    /// Remove the journal file when dropped.
+    struct Clean(PathBuf);
+    impl Drop for Clean {
+        fn drop(&mut self) {
+            let _ = remove_file(&self.0);
+        }
+    }
+
+    fn pending(byte: u8, amount: u128) -> Pending {
+        Pending {
+            hash: BlockHash::try_from([byte; BlockHash::LEN].as_ref()).unwrap(),
+            amount: Raw::from(amount),
+            source: None,
+        }
+    }
+
+    async fn prepare(
+        p: &str,
+        provider: Arc<MemoryProvider>,
+        wallet: Wallet,
+    ) -> (Clean, AutoReceive) {
+        let p = PathBuf::from(p);
+        let _ = remove_file(&p);
+        let journal = Journal::open(&p).await.unwrap();
+        let mut auto = AutoReceive::new(provider, Arc::new(wallet), journal);
+        auto.difficulty(Difficulty::new(0));
+        (Clean(p), auto)
+    }
+
+    #[tokio::test]
+    async fn open_then_receive() {
+        let wallet = Wallet::Seed(Seed::zero());
+        let address = wallet.address(0).unwrap();
+        let provider = Arc::new(MemoryProvider::new());
+        provider.add_pending(address.to_owned(), pending(1, 100));
+        provider.add_pending(address.to_owned(), pending(2, 5));
+        provider.add_pending(address.to_owned(), pending(3, 1000));
+
+        let (_clean, mut auto) =
+            prepare("open_then_receive.journal", provider.clone(), wallet).await;
+        auto.threshold(Raw::from(10));
+        let received = auto.poll().await.unwrap();
+        assert_eq!(received.len(), 2);
+
+        let processed = provider.processed();
+        assert_eq!(processed[0].0, Subtype::Open);
+        assert_eq!(processed[0].1.balance, Raw::from(1000));
+        assert_eq!(processed[1].0, Subtype::Receive);
+        assert_eq!(processed[1].1.balance, Raw::from(1100));
+        for (_, block) in processed {
+            block.verify_self_signature().unwrap();
+        }
+
+        // Below the threshold, so nothing else to do.
+        assert!(auto.poll().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_account_is_skipped() {
+        let wallet = Wallet::Seed(Seed::zero());
+        let provider = Arc::new(MemoryProvider::new());
+
+        // Receiving into the first account overflows its balance.
+        let first = wallet.address(0).unwrap();
+        provider.set_account(
+            first.to_owned(),
+            AccountState {
+                frontier: pending(9, 0).hash,
+                balance: Raw::from(u128::MAX),
+                representative: first.to_public(),
+            },
+        );
+        provider.add_pending(first, pending(1, 100));
+        let second = wallet.address(1).unwrap();
+        provider.add_pending(second.to_owned(), pending(2, 100));
+
+        let (_clean, mut auto) = prepare(
+            "failed_account_is_skipped.journal",
+            provider.clone(),
+            wallet,
+        )
+        .await;
+        auto.accounts(2);
+        let received = auto.poll().await.unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].account, second);
+    }
+
+    #[tokio::test]
+    async fn journal_skips_received() {
+        let wallet = Wallet::Seed(Seed::zero());
+        let address = wallet.address(0).unwrap();
+        let provider = Arc::new(MemoryProvider::new());
+
+        let (_clean, mut auto) = prepare(
+            "journal_skips_received.journal",
+            provider.clone(),
+            wallet.clone(),
+        )
+        .await;
+        provider.add_pending(address.to_owned(), pending(1, 100));
+        assert_eq!(auto.poll().await.unwrap().len(), 1);
+
+        // Pretend the node hasn't caught up and still thinks the block is pending.
+        provider.add_pending(address.to_owned(), pending(1, 100));
+        let journal = Journal::open("journal_skips_received.journal")
+            .await
+            .unwrap();
+        assert!(journal.contains(&pending(1, 100).hash));
+        let mut auto = AutoReceive::new(provider.clone(), Arc::new(wallet), journal);
+        assert!(auto.poll().await.unwrap().is_empty());
+        assert_eq!(provider.processed().len(), 1);
+    }
+}
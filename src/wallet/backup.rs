@@ -0,0 +1,305 @@
+//! A portable backup format for every wallet in a [WalletManager](crate::wallet::WalletManager).
+//!
+//! The backup is JSON with a format version, and can optionally be encrypted with a passphrase.
+//! Encrypted backups use Argon2id to derive keys from the passphrase, AES-256-CTR to encrypt
+//! the wallets and a keyed Blake2b MAC to detect a wrong passphrase or tampering.
+use crate::wallet::{Wallet, WalletId};
+use aes::cipher::{NewCipher, StreamCipher};
+use aes::Aes256Ctr;
+use anyhow::{anyhow, Context};
+use blake2::digest::{Update, VariableOutput};
+use blake2::VarBlake2b;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+
+/// The latest backup version. Older versions can be read, newer versions are rejected.
+pub const BACKUP_VERSION: u32 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const MAC_LEN: usize = 32;
+
+/// Limits on the cost parameters read from a backup, so a crafted backup can't use up the memory
+/// or CPU before the passphrase is found to be wrong. The memory cost is in KiB, so this is 1 GiB.
+const MAX_MEM_COST: u32 = 1024 * 1024;
+const MAX_TIME_COST: u32 = 16;
+
+/// A wallet with the account indexes known to be used, so they can be restored elsewhere.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupWallet {
+    pub id: WalletId,
+    pub wallet: Wallet,
+    pub accounts: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Backup {
+    pub wallets: Vec<BackupWallet>,
+}
+
+/// Argon2id cost parameters, stored in encrypted backups so they can be changed later.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Kdf {
+    /// Memory cost in KiB.
+    pub mem_cost: u32,
+    pub time_cost: u32,
+}
+
+impl Kdf {
+    fn check(&self) -> anyhow::Result<()> {
+        if self.mem_cost > MAX_MEM_COST {
+            return Err(anyhow!(
+                "KDF memory cost {} KiB is more than the maximum of {} KiB",
+                self.mem_cost,
+                MAX_MEM_COST
+            ));
+        }
+        if self.time_cost > MAX_TIME_COST {
+            return Err(anyhow!(
+                "KDF time cost {} is more than the maximum of {}",
+                self.time_cost,
+                MAX_TIME_COST
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for Kdf {
+    fn default() -> Self {
+        Self {
+            mem_cost: 64 * 1024,
+            time_cost: 3,
+        }
+    }
+}
+
+/// How the backup appears on disk.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "encryption", rename_all = "snake_case")]
+enum BackupFile {
+    None {
+        version: u32,
+        wallets: Vec<BackupWallet>,
+    },
+    Argon2idAes256Ctr {
+        version: u32,
+        kdf: Kdf,
+        salt: String,
+        nonce: String,
+        mac: String,
+        ciphertext: String,
+    },
+}
+
+impl Backup {
+    pub fn new(wallets: Vec<BackupWallet>) -> Self {
+        Self { wallets }
+    }
+
+    /// Serialize to JSON, encrypting when there's a passphrase.
+    pub fn to_json(&self, passphrase: Option<&str>, kdf: &Kdf) -> anyhow::Result<String> {
+        let file = match passphrase {
+            None => BackupFile::None {
+                version: BACKUP_VERSION,
+                wallets: self.wallets.to_owned(),
+            },
+            Some(passphrase) => {
+                let mut salt = [0u8; SALT_LEN];
+                rand::thread_rng().fill_bytes(&mut salt);
+                let mut nonce = [0u8; NONCE_LEN];
+                rand::thread_rng().fill_bytes(&mut nonce);
+
+                let (key, mac_key) = derive_keys(passphrase, &salt, kdf)?;
+                let mut ciphertext = serde_json::to_vec(&self.wallets)?;
+                Aes256Ctr::new(key.as_ref().into(), nonce.as_ref().into())
+                    .apply_keystream(&mut ciphertext);
+                let mac = mac(&mac_key, &nonce, &ciphertext);
+
+                BackupFile::Argon2idAes256Ctr {
+                    version: BACKUP_VERSION,
+                    kdf: kdf.to_owned(),
+                    salt: hex::encode(salt),
+                    nonce: hex::encode(nonce),
+                    mac: hex::encode(mac),
+                    ciphertext: hex::encode(ciphertext),
+                }
+            }
+        };
+        Ok(serde_json::to_string_pretty(&file)?)
+    }
+
+    /// Parse a backup, decrypting it if needed.
+    pub fn from_json(s: &str, passphrase: Option<&str>) -> anyhow::Result<Self> {
+        let file: BackupFile = serde_json::from_str(s).context("Parsing backup")?;
+        match file {
+            BackupFile::None { version, wallets } => {
+                check_version(version)?;
+                Ok(Self { wallets })
+            }
+            BackupFile::Argon2idAes256Ctr {
+                version,
+                kdf,
+                salt,
+                nonce,
+                mac: expected_mac,
+                ciphertext,
+            } => {
+                check_version(version)?;
+                kdf.check()?;
+                let passphrase =
+                    passphrase.ok_or_else(|| anyhow!("This backup needs a passphrase"))?;
+                let salt = hex::decode(salt).context("Decoding salt")?;
+                let nonce = hex::decode(nonce).context("Decoding nonce")?;
+                let expected_mac = hex::decode(expected_mac).context("Decoding MAC")?;
+                let mut data = hex::decode(ciphertext).context("Decoding ciphertext")?;
+                if nonce.len() != NONCE_LEN {
+                    return Err(anyhow!("Nonce should be {} bytes", NONCE_LEN));
+                }
+
+                let (key, mac_key) = derive_keys(passphrase, &salt, &kdf)?;
+                if !bool::from(mac(&mac_key, &nonce, &data).ct_eq(&expected_mac)) {
+                    return Err(anyhow!("Invalid passphrase or corrupted backup"));
+                }
+                Aes256Ctr::new(key.as_ref().into(), nonce.as_slice().into())
+                    .apply_keystream(&mut data);
+                let wallets = serde_json::from_slice(&data).context("Parsing wallets")?;
+                Ok(Self { wallets })
+            }
+        }
+    }
+}
+
+fn check_version(version: u32) -> anyhow::Result<()> {
+    if version > BACKUP_VERSION {
+        return Err(anyhow!(
+            "Backup version {} is newer than the supported version {}",
+            version,
+            BACKUP_VERSION
+        ));
+    }
+    Ok(())
+}
+
+/// Derive an encryption key and a MAC key from the passphrase.
+fn derive_keys(
+    passphrase: &str,
+    salt: &[u8],
+    kdf: &Kdf,
+) -> anyhow::Result<([u8; KEY_LEN], [u8; KEY_LEN])> {
+    let config = argon2::Config {
+        variant: argon2::Variant::Argon2id,
+        version: argon2::Version::Version13,
+        mem_cost: kdf.mem_cost,
+        time_cost: kdf.time_cost,
+        lanes: 1,
+        thread_mode: argon2::ThreadMode::Sequential,
+        secret: &[],
+        ad: &[],
+        hash_length: (KEY_LEN * 2) as u32,
+    };
+    let hash = argon2::hash_raw(passphrase.as_bytes(), salt, &config)?;
+    let mut key = [0u8; KEY_LEN];
+    let mut mac_key = [0u8; KEY_LEN];
+    key.copy_from_slice(&hash[..KEY_LEN]);
+    mac_key.copy_from_slice(&hash[KEY_LEN..]);
+    Ok((key, mac_key))
+}
+
+fn mac(key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Box<[u8]> {
+    let mut blake = VarBlake2b::new_keyed(key, MAC_LEN);
+    blake.update(nonce);
+    blake.update(ciphertext);
+    blake.finalize_boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phrase::{Language, MnemonicType};
+    use crate::wallet::PhraseWallet;
+    use crate::{Phrase, Private, Seed};
+
+    fn kdf() -> Kdf {
+        Kdf {
+            mem_cost: 8,
+            time_cost: 1,
+        }
+    }
+
+    fn backup() -> Backup {
+        Backup::new(vec![
+            BackupWallet {
+                id: WalletId::zero(),
+                wallet: Wallet::Seed(Seed::random()),
+                accounts: vec![0, 3],
+            },
+            BackupWallet {
+                id: WalletId::random(),
+                wallet: Wallet::Private(Private::random()),
+                accounts: vec![0],
+            },
+            BackupWallet {
+                id: WalletId::random(),
+                wallet: Wallet::Phrase(PhraseWallet::new(Phrase::random(
+                    MnemonicType::Words24,
+                    Language::English,
+                ))),
+                accounts: vec![1],
+            },
+        ])
+    }
+
+    fn assert_same(a: &Backup, b: &Backup) {
+        assert_eq!(a.wallets.len(), b.wallets.len());
+        for (a, b) in a.wallets.iter().zip(b.wallets.iter()) {
+            assert_eq!(a.id, b.id);
+            assert_eq!(a.accounts, b.accounts);
+            for index in &a.accounts {
+                assert_eq!(
+                    a.wallet.address(*index).unwrap(),
+                    b.wallet.address(*index).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn plain_round_trip() {
+        let backup = backup();
+        let json = backup.to_json(None, &kdf()).unwrap();
+        assert_same(&backup, &Backup::from_json(&json, None).unwrap());
+    }
+
+    #[test]
+    fn oversized_kdf() {
+        let json = backup().to_json(Some("secret"), &kdf()).unwrap();
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value["kdf"]["mem_cost"] = serde_json::json!(u32::MAX);
+        let err = Backup::from_json(&value.to_string(), Some("secret")).unwrap_err();
+        assert!(err.to_string().contains("memory cost"));
+
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value["kdf"]["time_cost"] = serde_json::json!(1_000_000);
+        let err = Backup::from_json(&value.to_string(), Some("secret")).unwrap_err();
+        assert!(err.to_string().contains("time cost"));
+    }
+
+    #[test]
+    fn encrypted_round_trip() {
+        let backup = backup();
+        let json = backup.to_json(Some("secret"), &kdf()).unwrap();
+        assert!(!json.contains("Seed"));
+        assert_same(&backup, &Backup::from_json(&json, Some("secret")).unwrap());
+        assert!(Backup::from_json(&json, Some("wrong")).is_err());
+        assert!(Backup::from_json(&json, None).is_err());
+    }
+
+    #[test]
+    fn newer_version() {
+        let json = r#"{"encryption": "none", "version": 2, "wallets": []}"#;
+        assert!(Backup::from_json(json, None).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+//! Build, sign, add work to and publish the next block of an account.
+//!
+//! The block's previous block and balance come from the account's state in an [AccountProvider],
+//! and it's signed by a [Signer]. Work is taken from a [WorkCache], and work for the block after it
+//! is generated in the background straight after publishing, so that the next block doesn't have
+//! to wait for it.
+use crate::blocks::{Block, BlockHash, Link, Previous, StateBlock, Subtype};
+use crate::wallet::{AccountProvider, AccountState, Pending, Signer, WorkCache};
+use crate::{Address, Difficulty, Public, Raw};
+use anyhow::anyhow;
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+pub struct BlockBuilder {
+    provider: Arc<dyn AccountProvider>,
+    signer: Arc<dyn Signer>,
+    work_cache: WorkCache,
+    difficulty: Difficulty,
+    receive_difficulty: Difficulty,
+    representative: Option<Public>,
+}
+
+impl BlockBuilder {
+    pub fn new(provider: Arc<dyn AccountProvider>, signer: Arc<dyn Signer>) -> Self {
+        Self {
+            provider,
+            signer,
+            work_cache: WorkCache::memory(),
+            difficulty: Difficulty::normal(),
+            receive_difficulty: Difficulty::receive(),
+            representative: None,
+        }
+    }
+
+    /// The work difficulty for send and change blocks.
+    pub fn difficulty(&mut self, v: Difficulty) -> &mut Self {
+        self.difficulty = v;
+        self
+    }
+
+    /// The work difficulty for receive and open blocks.
+    pub fn receive_difficulty(&mut self, v: Difficulty) -> &mut Self {
+        self.receive_difficulty = v;
+        self
+    }
+
+    /// Where work for the next block of each account is kept. Defaults to memory.
+    pub fn work_cache(&mut self, v: WorkCache) -> &mut Self {
+        self.work_cache = v;
+        self
+    }
+
+    /// The representative used for open blocks. Defaults to the account itself.
+    pub fn representative(&mut self, v: Public) -> &mut Self {
+        self.representative = Some(v);
+        self
+    }
+
+    /// Send `amount` from an account to `destination`, returning the published block.
+    pub async fn send(
+        &self,
+        index: u32,
+        destination: &Address,
+        amount: &Raw,
+    ) -> anyhow::Result<StateBlock> {
+        let public = self.signer.public(index).await?;
+        let state = self.opened_state(&public).await?;
+        let balance = state.balance.checked_sub(amount).ok_or_else(|| {
+            anyhow!(
+                "Sending {} is more than the balance {}",
+                amount,
+                state.balance
+            )
+        })?;
+        let block = StateBlock::new(
+            public,
+            Previous::Block(state.frontier),
+            state.representative,
+            balance,
+            Link::DestinationAccount(destination.to_public()),
+        );
+        self.publish(index, Subtype::Send, block).await
+    }
+
+    /// Change the representative of an account, returning the published block.
+    pub async fn change(&self, index: u32, representative: &Public) -> anyhow::Result<StateBlock> {
+        let public = self.signer.public(index).await?;
+        let state = self.opened_state(&public).await?;
+        let block = StateBlock::new(
+            public,
+            Previous::Block(state.frontier),
+            representative.to_owned(),
+            state.balance,
+            Link::Nothing,
+        );
+        self.publish(index, Subtype::Change, block).await
+    }
+
+    /// Receive a pending block into an account after `state`, or open the account when it's
+    /// `None`, returning the published block.
+    ///
+    /// Blocks are deterministic, so publishing the same receive twice is seen by the node as a
+    /// duplicate rather than a second receive.
+    pub async fn receive(
+        &self,
+        index: u32,
+        public: &Public,
+        state: Option<&AccountState>,
+        pending: &Pending,
+    ) -> anyhow::Result<(Subtype, StateBlock)> {
+        let link = Link::Source(pending.hash.to_owned());
+        let (subtype, block) = match state {
+            Some(state) => {
+                let balance = state
+                    .balance
+                    .checked_add(&pending.amount)
+                    .ok_or_else(|| anyhow!("Balance overflow receiving {}", pending.hash))?;
+                let block = StateBlock::new(
+                    public.to_owned(),
+                    Previous::Block(state.frontier.to_owned()),
+                    state.representative.to_owned(),
+                    balance,
+                    link,
+                );
+                (Subtype::Receive, block)
+            }
+            None => {
+                let representative = self.representative.as_ref().unwrap_or(public).to_owned();
+                let block = StateBlock::new(
+                    public.to_owned(),
+                    Previous::Open,
+                    representative,
+                    pending.amount.to_owned(),
+                    link,
+                );
+                (Subtype::Open, block)
+            }
+        };
+        let block = self.publish(index, subtype.to_owned(), block).await?;
+        Ok((subtype, block))
+    }
+
+    /// Start generating work for the block after `state` in the background, e.g. for blocks
+    /// published before a restart or by another wallet.
+    pub fn precompute(&self, public: &Public, state: Option<&AccountState>, subtype: Subtype) {
+        let root = match state {
+            Some(state) => state.frontier.to_owned(),
+            None => match BlockHash::try_from(public.as_bytes()) {
+                Ok(root) => root,
+                Err(_) => return,
+            },
+        };
+        self.work_cache
+            .precompute(public, &root, self.difficulty_for(&subtype));
+    }
+
+    /// Sign, add work and publish, then start on the work for the next block.
+    ///
+    /// The next block is guessed to be of the same kind, e.g. another receive.
+    async fn publish(
+        &self,
+        index: u32,
+        subtype: Subtype,
+        mut block: StateBlock,
+    ) -> anyhow::Result<StateBlock> {
+        let root = match &block.previous {
+            Previous::Block(previous) => previous.to_owned(),
+            // Open blocks use the account's public key as the work root.
+            Previous::Open => BlockHash::try_from(block.account.as_bytes())?,
+        };
+        let difficulty = self.difficulty_for(&subtype);
+
+        let signature = self
+            .signer
+            .sign_block(index, &Block::from_state_block(&block))
+            .await?;
+        block.signature = Some(signature);
+        block.work = Some(
+            self.work_cache
+                .work(&block.account, &root, difficulty)
+                .await?,
+        );
+        let hash = self.provider.process(subtype, &block).await?;
+
+        self.work_cache
+            .precompute(&block.account, &hash, difficulty);
+        Ok(block)
+    }
+
+    async fn opened_state(&self, public: &Public) -> anyhow::Result<AccountState> {
+        let address = public.to_address();
+        self.provider
+            .account_info(&address)
+            .await?
+            .ok_or_else(|| anyhow!("Account {} has not been opened", address))
+    }
+
+    fn difficulty_for(&self, subtype: &Subtype) -> &Difficulty {
+        match subtype {
+            Subtype::Receive | Subtype::Open => &self.receive_difficulty,
+            _ => &self.difficulty,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::{MemoryProvider, Wallet};
+    use crate::Seed;
+
+    #[tokio::test]
+    async fn send_and_change() {
+        let wallet = Wallet::Seed(Seed::zero());
+        let public = wallet.public(0).unwrap();
+        let provider = Arc::new(MemoryProvider::new());
+        let mut builder = BlockBuilder::new(provider.clone(), Arc::new(wallet.clone()));
+        builder
+            .difficulty(Difficulty::new(0))
+            .receive_difficulty(Difficulty::new(0));
+
+        let destination = Seed::zero().derive(1).to_address().unwrap();
+        assert!(builder.send(0, &destination, &Raw::from(1)).await.is_err());
+
+        let state = AccountState {
+            frontier: BlockHash::zero(),
+            balance: Raw::from(100),
+            representative: public.to_owned(),
+        };
+        provider.set_account(public.to_address(), state);
+        assert!(builder
+            .send(0, &destination, &Raw::from(101))
+            .await
+            .is_err());
+
+        let send = builder.send(0, &destination, &Raw::from(30)).await.unwrap();
+        assert_eq!(send.balance, Raw::from(70));
+        assert_eq!(send.previous, Previous::Block(BlockHash::zero()));
+        send.verify_self_signature().unwrap();
+
+        let representative = Seed::zero().derive(2).to_public().unwrap();
+        let change = builder.change(0, &representative).await.unwrap();
+        assert_eq!(change.previous, Previous::Block(send.hash.to_owned()));
+        assert_eq!(change.representative, representative);
+        assert_eq!(change.balance, Raw::from(70));
+
+        let processed = provider.processed();
+        assert_eq!(processed[0].0, Subtype::Send);
+        assert_eq!(processed[1].0, Subtype::Change);
+    }
+
+    #[tokio::test]
+    async fn uses_cached_work() {
+        let wallet = Wallet::Seed(Seed::zero());
+        let public = wallet.public(0).unwrap();
+        let provider = Arc::new(MemoryProvider::new());
+        provider.set_account(
+            public.to_address(),
+            AccountState {
+                frontier: BlockHash::zero(),
+                balance: Raw::from(100),
+                representative: public.to_owned(),
+            },
+        );
+
+        let difficulty = Difficulty::new(0xf000000000000000);
+        let cache = WorkCache::memory();
+        let work = cache
+            .work(&public, &BlockHash::zero(), &difficulty)
+            .await
+            .unwrap();
+        cache
+            .insert(crate::wallet::CachedWork {
+                account: public.to_owned(),
+                root: BlockHash::zero(),
+                work: work.to_owned(),
+                difficulty: difficulty.to_owned(),
+            })
+            .await
+            .unwrap();
+
+        let mut builder = BlockBuilder::new(provider, Arc::new(wallet));
+        builder.difficulty(difficulty).work_cache(cache);
+        let destination = Seed::zero().derive(1).to_address().unwrap();
+        let send = builder.send(0, &destination, &Raw::from(1)).await.unwrap();
+        assert_eq!(send.work, Some(work));
+    }
+}
@@ -2,7 +2,11 @@
 //!
 //! # Manager
 //! A [WalletManager] is provided to store multiple [Wallet]s of different types. The supported
-//! wallets are [Wallet::Seed], [Wallet::Private], and (TODO) [Wallet::Phrase].
+//! wallets are [Wallet::Seed], [Wallet::Private], and [Wallet::Phrase].
+//!
+//! Phrase wallets can have a BIP39 passphrase stored with them, or require it every time they are
+//! used with [Wallet::unlock]. They can also use a different [Derivation] scheme to match other
+//! wallets, e.g. the phrase entropy as a Nano seed.
 //!
 //! ## Example usage
 //! ```
@@ -31,7 +35,23 @@
 //! # Ok(())
 //! # }
 //! ```
-use crate::phrase::{Language, MnemonicType};
+mod autoreceive;
+mod backup;
+mod builder;
+mod nano_node;
+mod provider;
+mod signer;
+mod work_cache;
+
+pub use autoreceive::{AutoReceive, Journal, JournalEntry};
+pub use backup::{Backup, BackupWallet, Kdf, BACKUP_VERSION};
+pub use builder::BlockBuilder;
+pub use nano_node::{NanoNodeKeys, NanoNodeWallet};
+pub use provider::{AccountProvider, AccountState, MemoryProvider, Pending};
+pub use signer::{serve as serve_signer, RemoteSigner, Signer, WalletFileSigner};
+pub use work_cache::{CachedWork, WorkCache};
+
+use crate::phrase::{Derivation, Language, MnemonicType};
 use crate::{hexify, Address, Error, Phrase, Private, Public, Seed};
 use anyhow::{anyhow, Context};
 use rand::RngCore;
@@ -102,7 +122,7 @@ impl WalletManager {
         mnemonic_type: MnemonicType,
         lang: Language,
     ) -> anyhow::Result<Wallet> {
-        let wallet = Wallet::Phrase(Phrase::random(mnemonic_type, lang));
+        let wallet = Wallet::Phrase(PhraseWallet::new(Phrase::random(mnemonic_type, lang)));
         self.add(id, wallet.clone()).await?;
         Ok(wallet)
     }
@@ -137,6 +157,44 @@ impl WalletManager {
         Ok(())
     }
 
+    /// Every wallet in the store, with the account indexes known to be used.
+    pub async fn export(&self) -> anyhow::Result<Vec<BackupWallet>> {
+        let storage = self.load_unlocked().await?;
+        let mut wallets: Vec<BackupWallet> = storage
+            .wallets
+            .iter()
+            .map(|(id, wallet)| BackupWallet {
+                id: id.to_owned(),
+                wallet: wallet.to_owned(),
+                accounts: storage.accounts(id),
+            })
+            .collect();
+        wallets.sort_by(|a, b| a.id.as_bytes().cmp(b.id.as_bytes()));
+        Ok(wallets)
+    }
+
+    /// Add many wallets to the store at once.
+    ///
+    /// If any of the wallet references already exist, there will be an error and nothing will be
+    /// added.
+    pub async fn import(&self, wallets: Vec<BackupWallet>) -> anyhow::Result<()> {
+        let mut storage = self.load_unlocked().await?;
+        for backup in &wallets {
+            if storage.wallets.contains_key(&backup.id) {
+                return Err(anyhow!("Wallet reference already exists: {:?}", &backup.id));
+            }
+        }
+        for backup in wallets {
+            storage.accounts.insert(backup.id.clone(), backup.accounts);
+            storage.wallets.insert(backup.id, backup.wallet);
+        }
+        let file = File::create(&self.path)
+            .await
+            .with_context(|| format!("Creating file {:?}", &self.path))?;
+        self.save_unlocked(file, storage).await?;
+        Ok(())
+    }
+
     /// If the wallet reference doesn't exist, there will be an error.
     pub async fn delete(&self, reference: &WalletId) -> anyhow::Result<()> {
         let mut storage = self.load_unlocked().await?;
@@ -144,6 +202,7 @@ impl WalletManager {
             return Err(anyhow!("Wallet reference doesn't exist: {:?}", &reference));
         }
         storage.wallets.remove(reference);
+        storage.accounts.remove(reference);
         let file = File::create(&self.path)
             .await
             .with_context(|| format!("Creating file {:?}", &self.path))?;
@@ -156,7 +215,7 @@ impl WalletManager {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Wallet {
     /// A wallet that derives keys from a phrase.
-    Phrase(Phrase),
+    Phrase(PhraseWallet),
 
     /// A wallet that derives from a seed.
     Seed(Seed),
@@ -176,7 +235,29 @@ impl Wallet {
                 }
                 Ok(private.to_owned())
             }
-            Wallet::Phrase(phrase) => Ok(phrase.to_private(index, "")?),
+            Wallet::Phrase(phrase) => phrase.private(index),
+        }
+    }
+
+    /// If the wallet can't be used until [Wallet::unlock] is called.
+    pub fn needs_passphrase(&self) -> bool {
+        match self {
+            Wallet::Phrase(phrase) => phrase.passphrase == Passphrase::Prompt,
+            _ => false,
+        }
+    }
+
+    /// Give the passphrase to a wallet that requires one.
+    ///
+    /// The passphrase is only kept in memory, so the returned wallet can't be saved.
+    pub fn unlock(&self, passphrase: &str) -> Result<Wallet, Error> {
+        match self {
+            Wallet::Phrase(phrase) if phrase.passphrase == Passphrase::Prompt => {
+                let mut phrase = phrase.to_owned();
+                phrase.passphrase = Passphrase::Unlocked(passphrase.to_string());
+                Ok(Wallet::Phrase(phrase))
+            }
+            _ => Ok(self.to_owned()),
         }
     }
 
@@ -189,20 +270,101 @@ impl Wallet {
     pub fn address(&self, index: u32) -> anyhow::Result<Address> {
         Ok(self.public(index)?.to_address())
     }
+
+    /// A short string of the secret suitable for a QR code.
+    ///
+    /// Seeds and private keys use the `nanoseed:` and `nanokey:` URI schemes. Phrases are the
+    /// mnemonic words.
+    pub fn to_qr_string(&self) -> String {
+        match self {
+            Wallet::Seed(seed) => format!("nanoseed:{}", seed),
+            Wallet::Private(private) => format!("nanokey:{}", private),
+            Wallet::Phrase(phrase) => phrase.phrase.to_string(),
+        }
+    }
+}
+
+/// A [Phrase] with how its passphrase is given and how keys are derived.
+///
+/// Older wallet files only contain the phrase, which loads as no passphrase and BIP44.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PhraseWallet {
+    #[serde(flatten)]
+    pub phrase: Phrase,
+
+    #[serde(default)]
+    pub passphrase: Passphrase,
+
+    #[serde(default)]
+    pub derivation: Derivation,
+}
+
+impl PhraseWallet {
+    /// No passphrase and BIP44 derivation.
+    pub fn new(phrase: Phrase) -> Self {
+        Self {
+            phrase,
+            passphrase: Passphrase::None,
+            derivation: Derivation::Bip44,
+        }
+    }
+
+    pub fn private(&self, index: u32) -> Result<Private, Error> {
+        let passphrase = match &self.passphrase {
+            Passphrase::None => "",
+            Passphrase::Stored(passphrase) | Passphrase::Unlocked(passphrase) => passphrase,
+            Passphrase::Prompt => return Err(Error::PassphraseRequired),
+        };
+        self.phrase.derive(index, passphrase, &self.derivation)
+    }
+}
+
+/// The BIP39 passphrase of a [PhraseWallet].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Passphrase {
+    /// An empty passphrase.
+    None,
+
+    /// Stored in the wallet file. **Warning**: Wallet files are not encrypted.
+    Stored(String),
+
+    /// Needs to be given with [Wallet::unlock] before keys can be derived.
+    Prompt,
+
+    /// Given with [Wallet::unlock]. This can't be serialized so it never ends up in a file.
+    #[serde(skip)]
+    Unlocked(String),
+}
+
+impl Default for Passphrase {
+    fn default() -> Self {
+        Passphrase::None
+    }
 }
 
 /// Storage for all wallets.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WalletStorage {
     wallets: HashMap<WalletId, Wallet>,
+
+    /// Account indexes known to be used by each wallet, e.g. from an import.
+    #[serde(default)]
+    accounts: HashMap<WalletId, Vec<u32>>,
 }
 
 impl WalletStorage {
     pub fn new() -> Self {
         Self {
             wallets: Default::default(),
+            accounts: Default::default(),
         }
     }
+
+    /// Wallets without known indexes use only the first account.
+    fn accounts(&self, id: &WalletId) -> Vec<u32> {
+        self.accounts.get(id).cloned().unwrap_or_else(|| vec![0])
+    }
 }
 
 /// A unique identifier for a wallet. This can be generated randomly and given to the user for
@@ -274,4 +436,46 @@ mod tests {
                 .unwrap()
         );
     }
+
+    fn phrase() -> Phrase {
+        Phrase::from_words(
+            Language::English,
+            "edge defense waste choose enrich upon flee junk siren film clown finish \
+            luggage leader kid quick brick print evidence swap drill paddle truly occur",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn old_phrase_wallet() {
+        let json = serde_json::to_string(&phrase()).unwrap();
+        let wallet: Wallet = serde_json::from_str(&format!(r#"{{"Phrase": {}}}"#, json)).unwrap();
+        assert_eq!(
+            wallet.private(0).unwrap().as_bytes(),
+            phrase().to_private(0, "").unwrap().as_bytes()
+        );
+    }
+
+    #[test]
+    fn phrase_passphrase() {
+        let mut stored = PhraseWallet::new(phrase());
+        stored.passphrase = Passphrase::Stored("secret".into());
+        let stored = Wallet::Phrase(stored);
+        assert!(!stored.needs_passphrase());
+        assert_eq!(
+            stored.private(0).unwrap().as_bytes(),
+            phrase().to_private(0, "secret").unwrap().as_bytes()
+        );
+
+        let mut prompt = PhraseWallet::new(phrase());
+        prompt.passphrase = Passphrase::Prompt;
+        let prompt = Wallet::Phrase(prompt);
+        assert!(prompt.needs_passphrase());
+        assert!(matches!(prompt.private(0), Err(Error::PassphraseRequired)));
+
+        let unlocked = prompt.unlock("secret").unwrap();
+        assert!(!unlocked.needs_passphrase());
+        assert_eq!(unlocked.address(0).unwrap(), stored.address(0).unwrap());
+        assert!(serde_json::to_string(&unlocked).is_err());
+    }
 }
@@ -0,0 +1,407 @@
+//! Reading and writing the JSON produced by nano_node's `wallet_export` RPC.
+//!
+//! The export is a map of 32 byte hex keys to 32 byte hex values. The first few keys are special
+//! entries (version, salt, wallet key, etc.). The rest are public keys of accounts in the wallet.
+//!
+//! Secrets are encrypted with AES-256-CTR using a random "wallet key", which itself is encrypted
+//! with a key derived from the wallet password using Argon2d. Deterministic accounts only store
+//! their index, since their private key can be derived from the seed.
+use crate::wallet::{BackupWallet, Wallet, WalletId};
+use crate::{Network, Private, Public, Seed};
+use aes::cipher::{NewCipher, StreamCipher};
+use aes::Aes256Ctr;
+use anyhow::{anyhow, Context};
+use rand::RngCore;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+const VERSION: u64 = 4;
+const LEN: usize = 32;
+
+type Value = [u8; LEN];
+
+/// Special entries stored before any accounts.
+#[derive(Clone, Copy)]
+enum Special {
+    Version = 0,
+    WalletKey = 1,
+    Salt = 2,
+    Check = 3,
+    Representative = 4,
+    Seed = 5,
+    DeterministicIndex = 6,
+}
+
+const SPECIAL_COUNT: u64 = 7;
+
+impl Special {
+    fn key(self) -> Value {
+        number_value(self as u64)
+    }
+}
+
+/// The decrypted contents of a nano_node wallet.
+#[derive(Clone)]
+pub struct NanoNodeKeys {
+    pub seed: Option<Seed>,
+
+    /// Indexes of accounts derived from the seed.
+    pub deterministic: Vec<u32>,
+
+    /// Private keys that aren't derived from the seed.
+    pub adhoc: Vec<Private>,
+
+    pub representative: Option<Public>,
+}
+
+impl NanoNodeKeys {
+    /// Keys for a feeless wallet and the account indexes in use.
+    ///
+    /// Phrase wallets are rejected since nano_node can't derive their keys, so the account indexes
+    /// would be lost.
+    pub fn from_wallet(wallet: &Wallet, accounts: &[u32]) -> anyhow::Result<Self> {
+        match wallet {
+            Wallet::Seed(seed) => Ok(Self {
+                seed: Some(seed.to_owned()),
+                deterministic: accounts.to_vec(),
+                adhoc: vec![],
+                representative: None,
+            }),
+            Wallet::Private(private) => Ok(Self {
+                seed: None,
+                deterministic: vec![],
+                adhoc: vec![private.to_owned()],
+                representative: None,
+            }),
+            Wallet::Phrase(_) => Err(anyhow!(
+                "Phrase wallets can't be stored in a nano_node wallet, use a feeless backup"
+            )),
+        }
+    }
+
+    /// Convert into feeless wallets.
+    ///
+    /// The seed becomes a wallet with the given `id`. Each ad hoc key becomes a separate wallet
+    /// with a random [WalletId], since a feeless wallet holds a single private key.
+    pub fn to_backup_wallets(&self, id: WalletId) -> Vec<BackupWallet> {
+        let mut wallets = vec![];
+        if let Some(seed) = &self.seed {
+            if !self.deterministic.is_empty() || self.adhoc.is_empty() {
+                wallets.push(BackupWallet {
+                    id,
+                    wallet: Wallet::Seed(seed.to_owned()),
+                    accounts: self.deterministic.to_owned(),
+                });
+            }
+        }
+        for private in &self.adhoc {
+            wallets.push(BackupWallet {
+                id: WalletId::random(),
+                wallet: Wallet::Private(private.to_owned()),
+                accounts: vec![0],
+            });
+        }
+        wallets
+    }
+}
+
+/// An encrypted nano_node wallet, as exported by `wallet_export`.
+pub struct NanoNodeWallet {
+    entries: BTreeMap<Value, Value>,
+}
+
+impl NanoNodeWallet {
+    /// The Argon2d memory cost in KiB used by nano_node to derive a key from the wallet password.
+    ///
+    /// The test network uses a tiny value so wallets can be unlocked quickly.
+    pub fn kdf_work(network: Network) -> u32 {
+        match network {
+            Network::Test => 8,
+            Network::Beta | Network::Live => 64 * 1024,
+        }
+    }
+
+    pub fn from_json(s: &str) -> anyhow::Result<Self> {
+        let map: BTreeMap<String, String> =
+            serde_json::from_str(s).context("Parsing nano_node wallet JSON")?;
+        let mut entries = BTreeMap::new();
+        for (key, value) in map {
+            entries.insert(decode_value(&key)?, decode_value(&value)?);
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn to_json(&self) -> String {
+        let map: BTreeMap<String, String> = self
+            .entries
+            .iter()
+            .map(|(k, v)| (hex::encode_upper(k), hex::encode_upper(v)))
+            .collect();
+        serde_json::to_string_pretty(&map).expect("Could not serialize nano_node wallet")
+    }
+
+    /// Encrypt keys into a new wallet, using `kdf_work` as the Argon2d memory cost.
+    pub fn encrypt(keys: &NanoNodeKeys, password: &str, kdf_work: u32) -> anyhow::Result<Self> {
+        if !keys.deterministic.is_empty() && keys.seed.is_none() {
+            return Err(anyhow!("Deterministic accounts need a seed"));
+        }
+
+        let mut salt = [0u8; LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let mut wallet_key = [0u8; LEN];
+        rand::thread_rng().fill_bytes(&mut wallet_key);
+        let password_key = derive_key(password, &salt, kdf_work)?;
+
+        let mut entries = BTreeMap::new();
+        let mut put = |special: Special, value: Value| entries.insert(special.key(), value);
+        put(Special::Version, number_value(VERSION));
+        put(Special::Salt, salt);
+        put(Special::WalletKey, crypt(&wallet_key, &password_key, &salt));
+        put(Special::Check, crypt(&[0u8; LEN], &wallet_key, &salt));
+        let representative = match &keys.representative {
+            Some(representative) => representative.to_owned(),
+            None => Public::try_from([0u8; LEN].as_ref())?,
+        };
+        put(
+            Special::Representative,
+            to_value(representative.as_bytes())?,
+        );
+
+        let seed = keys.seed.to_owned().unwrap_or_else(Seed::random);
+        put(Special::Seed, crypt(&seed.0, &wallet_key, &salt));
+        let next_index = keys.deterministic.iter().max().map(|i| i + 1).unwrap_or(0);
+        put(Special::DeterministicIndex, number_value(next_index as u64));
+
+        for index in &keys.deterministic {
+            let public = seed.derive(*index).to_public()?;
+            entries.insert(
+                to_value(public.as_bytes())?,
+                number_value((1 << 32) | *index as u64),
+            );
+        }
+        for private in &keys.adhoc {
+            let public = private.to_public()?;
+            let private = to_value(private.as_bytes())?;
+            entries.insert(
+                to_value(public.as_bytes())?,
+                crypt(&private, &wallet_key, public.as_bytes()),
+            );
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// Decrypt the wallet with its password, using `kdf_work` as the Argon2d memory cost.
+    pub fn decrypt(&self, password: &str, kdf_work: u32) -> anyhow::Result<NanoNodeKeys> {
+        let version = value_number(self.special(Special::Version)?)?;
+        if version != VERSION {
+            return Err(anyhow!(
+                "Unsupported nano_node wallet version {}, expected {}",
+                version,
+                VERSION
+            ));
+        }
+
+        let salt = self.special(Special::Salt)?;
+        let encrypted_wallet_key = self.special(Special::WalletKey)?;
+        let check = self.special(Special::Check)?;
+
+        let password_key = derive_key(password, salt, kdf_work)?;
+        let mut wallet_key = crypt(encrypted_wallet_key, &password_key, salt);
+        if &crypt(&[0u8; LEN], &wallet_key, salt) != check {
+            // Wallets that were never opened by a node are still encrypted with a zero key.
+            wallet_key = crypt(encrypted_wallet_key, &[0u8; LEN], salt);
+            if !password.is_empty() || &crypt(&[0u8; LEN], &wallet_key, salt) != check {
+                return Err(anyhow!("Invalid nano_node wallet password"));
+            }
+        }
+
+        let seed = self
+            .entries
+            .get(&Special::Seed.key())
+            .map(|encrypted| Seed(crypt(encrypted, &wallet_key, salt)));
+        let representative = match self.entries.get(&Special::Representative.key()) {
+            Some(value) => Some(Public::try_from(value.as_ref())?),
+            None => None,
+        };
+
+        let mut deterministic = vec![];
+        let mut adhoc = vec![];
+        for (key, value) in &self.entries {
+            if value_number(key)
+                .map(|n| n < SPECIAL_COUNT)
+                .unwrap_or(false)
+            {
+                continue;
+            }
+            let public = Public::try_from(key.as_ref())?;
+            match value_number(value) {
+                Ok(n) if n >> 32 == 1 => {
+                    let index = n as u32;
+                    let seed = seed
+                        .as_ref()
+                        .ok_or_else(|| anyhow!("Deterministic account without a seed"))?;
+                    if seed.derive(index).to_public()? != public {
+                        return Err(anyhow!("Account {} does not match the seed", public));
+                    }
+                    deterministic.push(index);
+                }
+                Ok(_) => return Err(anyhow!("Unknown key type for account {}", public)),
+                Err(_) => {
+                    let private = Private::try_from(crypt(value, &wallet_key, key).as_ref())?;
+                    if private.to_public()? != public {
+                        return Err(anyhow!("Account {} does not match its private key", public));
+                    }
+                    adhoc.push(private);
+                }
+            }
+        }
+        deterministic.sort_unstable();
+
+        Ok(NanoNodeKeys {
+            seed,
+            deterministic,
+            adhoc,
+            representative,
+        })
+    }
+
+    fn special(&self, special: Special) -> anyhow::Result<&Value> {
+        self.entries
+            .get(&special.key())
+            .ok_or_else(|| anyhow!("Special entry {} missing", special as u8))
+    }
+}
+
+/// Argon2d as used by nano_node's `kdf::phs`.
+fn derive_key(password: &str, salt: &[u8], kdf_work: u32) -> anyhow::Result<Value> {
+    let config = argon2::Config {
+        variant: argon2::Variant::Argon2d,
+        version: argon2::Version::Version10,
+        mem_cost: kdf_work,
+        time_cost: 1,
+        lanes: 1,
+        thread_mode: argon2::ThreadMode::Sequential,
+        secret: &[],
+        ad: &[],
+        hash_length: LEN as u32,
+    };
+    let hash = argon2::hash_raw(password.as_bytes(), salt, &config)?;
+    to_value(&hash)
+}
+
+/// AES-256-CTR using the first 16 bytes of `iv` as the initial counter. Encrypts and decrypts.
+fn crypt(data: &[u8], key: &[u8], iv: &[u8]) -> Value {
+    let mut out = [0u8; LEN];
+    out.copy_from_slice(data);
+    let mut cipher = Aes256Ctr::new(key.into(), iv[..16].into());
+    cipher.apply_keystream(&mut out);
+    out
+}
+
+fn to_value(bytes: &[u8]) -> anyhow::Result<Value> {
+    Ok(<Value>::try_from(bytes)?)
+}
+
+fn decode_value(s: &str) -> anyhow::Result<Value> {
+    let mut value = [0u8; LEN];
+    hex::decode_to_slice(s, &mut value).with_context(|| format!("Decoding {}", s))?;
+    Ok(value)
+}
+
+/// A 256 bit big endian number.
+fn number_value(n: u64) -> Value {
+    let mut value = [0u8; LEN];
+    value[LEN - 8..].copy_from_slice(&n.to_be_bytes());
+    value
+}
+
+/// The reverse of [number_value], failing if the number doesn't fit in a u64.
+fn value_number(value: &Value) -> anyhow::Result<u64> {
+    if value[..LEN - 8].iter().any(|b| *b != 0) {
+        return Err(anyhow!("Value is too large"));
+    }
+    let mut n = [0u8; 8];
+    n.copy_from_slice(&value[LEN - 8..]);
+    Ok(u64::from_be_bytes(n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phrase::{Language, MnemonicType};
+    use crate::wallet::PhraseWallet;
+    use crate::Phrase;
+
+    fn keys() -> NanoNodeKeys {
+        NanoNodeKeys {
+            seed: Some(Seed::random()),
+            deterministic: vec![0, 1, 5],
+            adhoc: vec![Private::random()],
+            representative: None,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let keys = keys();
+        let wallet = NanoNodeWallet::encrypt(&keys, "hunter2", 8).unwrap();
+        let wallet = NanoNodeWallet::from_json(&wallet.to_json()).unwrap();
+
+        let decrypted = wallet.decrypt("hunter2", 8).unwrap();
+        assert!(decrypted.seed == keys.seed);
+        assert_eq!(decrypted.deterministic, keys.deterministic);
+        assert_eq!(
+            decrypted.adhoc[0].to_public().unwrap(),
+            keys.adhoc[0].to_public().unwrap()
+        );
+
+        assert!(wallet.decrypt("wrong", 8).is_err());
+    }
+
+    #[test]
+    fn zero_key_wallet() {
+        // A wallet created by nano_node but never opened, with a zero wallet key password.
+        let keys = keys();
+        let mut wallet = NanoNodeWallet::encrypt(&keys, "", 8).unwrap();
+        let salt = wallet.special(Special::Salt).unwrap().to_owned();
+        let password_key = derive_key("", &salt, 8).unwrap();
+        let wallet_key = crypt(
+            wallet.special(Special::WalletKey).unwrap(),
+            &password_key,
+            &salt,
+        );
+        wallet.entries.insert(
+            Special::WalletKey.key(),
+            crypt(&wallet_key, &[0u8; LEN], &salt),
+        );
+
+        let decrypted = wallet.decrypt("", 8).unwrap();
+        assert_eq!(decrypted.deterministic, keys.deterministic);
+    }
+
+    #[test]
+    fn wallets() {
+        let seed = Seed::random();
+        let keys = NanoNodeKeys::from_wallet(&Wallet::Seed(seed), &[2, 4]).unwrap();
+        let id = WalletId::random();
+        let wallets = keys.to_backup_wallets(id.to_owned());
+        assert_eq!(wallets.len(), 1);
+        assert_eq!(wallets[0].id, id);
+        assert_eq!(wallets[0].accounts, vec![2, 4]);
+
+        let phrase = Phrase::random(MnemonicType::Words12, Language::English);
+        assert!(
+            NanoNodeKeys::from_wallet(&Wallet::Phrase(PhraseWallet::new(phrase)), &[0]).is_err()
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(
+            value_number(&number_value(1 << 32 | 7)).unwrap(),
+            1 << 32 | 7
+        );
+        assert!(value_number(&[0xff; LEN]).is_err());
+    }
+}
@@ -0,0 +1,372 @@
+//! Where a wallet gets account state from, and where it publishes blocks to.
+//!
+//! Building a block needs the account's frontier, balance and representative. These can come from
+//! a remote node over RPC ([RPCClient]), from an in-process node, or from a [MemoryProvider] when
+//! testing wallet logic offline.
+#[cfg(feature = "node")]
+use crate::blocks::Block;
+use crate::blocks::{BlockHash, Previous, StateBlock, Subtype};
+#[cfg(feature = "node")]
+use crate::node::Node;
+#[cfg(feature = "rpc_client")]
+use crate::rpc::calls::{
+    AccountInfoRequest, AccountsPendingRequest, AccountsPendingResponse, ProcessRequest,
+};
+#[cfg(feature = "rpc_client")]
+use crate::rpc::client::{RPCClient, RPCRequest};
+use crate::{Address, Public, Raw};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Mutex;
+
+/// The latest known state of an opened account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountState {
+    pub frontier: BlockHash,
+    pub balance: Raw,
+    pub representative: Public,
+}
+
+/// A block sent to an account which hasn't been received yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pending {
+    /// The hash of the send block, used as the link of the receive block.
+    pub hash: BlockHash,
+    pub amount: Raw,
+    /// The sending account, if known.
+    pub source: Option<Address>,
+}
+
+#[async_trait]
+pub trait AccountProvider: Send + Sync {
+    /// The latest state of an account, or `None` if the account hasn't been opened yet.
+    async fn account_info(&self, account: &Address) -> anyhow::Result<Option<AccountState>>;
+
+    /// Up to `count` pending blocks for an account with an amount of at least `threshold`.
+    async fn pending(
+        &self,
+        account: &Address,
+        threshold: &Raw,
+        count: u64,
+    ) -> anyhow::Result<Vec<Pending>>;
+
+    /// Publish a signed block with work attached, returning its hash.
+    async fn process(&self, subtype: Subtype, block: &StateBlock) -> anyhow::Result<BlockHash>;
+}
+
+#[cfg(feature = "rpc_client")]
+#[async_trait]
+impl AccountProvider for RPCClient {
+    async fn account_info(&self, account: &Address) -> anyhow::Result<Option<AccountState>> {
+        let request = AccountInfoRequest::new(account.to_owned());
+        let response = match (&request).call(self).await {
+            Ok(response) => response,
+            Err(crate::Error::RPCError(err)) if err == "Account not found" => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let representative = response
+            .representative
+            .ok_or_else(|| anyhow!("Representative missing from account_info"))?;
+        Ok(Some(AccountState {
+            frontier: response.frontier,
+            balance: response.balance,
+            representative: representative.to_public(),
+        }))
+    }
+
+    async fn pending(
+        &self,
+        account: &Address,
+        threshold: &Raw,
+        count: u64,
+    ) -> anyhow::Result<Vec<Pending>> {
+        let mut request = AccountsPendingRequest::new(vec![account.to_owned()], count);
+        request.threshold = Some(threshold.to_owned());
+        request.source = true;
+        let response = (&request).call(self).await?;
+
+        let mut pending: Vec<Pending> = match response {
+            AccountsPendingResponse::OnlyBlockHash { .. } => {
+                return Err(anyhow!("accounts_pending did not return amounts"))
+            }
+            AccountsPendingResponse::Threshold { mut blocks } => blocks
+                .remove(account)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(hash, amount)| Pending {
+                    hash,
+                    amount,
+                    source: None,
+                })
+                .collect(),
+            AccountsPendingResponse::Source { mut blocks } => blocks
+                .remove(account)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(hash, entry)| Pending {
+                    hash,
+                    amount: entry.amount,
+                    source: Some(entry.source),
+                })
+                .collect(),
+        };
+        sort_pending(&mut pending);
+        Ok(pending)
+    }
+
+    async fn process(&self, subtype: Subtype, block: &StateBlock) -> anyhow::Result<BlockHash> {
+        let request = ProcessRequest::new(subtype, block.to_owned());
+        Ok((&request).call(self).await?.hash)
+    }
+}
+
+#[cfg(feature = "node")]
+#[async_trait]
+impl AccountProvider for Node {
+    async fn account_info(&self, account: &Address) -> anyhow::Result<Option<AccountState>> {
+        let state = self.state().lock().await;
+        let frontier = match state
+            .get_latest_block_hash_for_account(&account.to_public())
+            .await?
+        {
+            Some(frontier) => frontier,
+            None => return Ok(None),
+        };
+        let block = state
+            .get_block_by_hash(&frontier)
+            .await?
+            .ok_or_else(|| anyhow!("Frontier {} missing from state", frontier))?;
+        Ok(Some(AccountState {
+            frontier,
+            balance: block.balance().to_owned(),
+            representative: block.representative().to_owned(),
+        }))
+    }
+
+    async fn pending(
+        &self,
+        _account: &Address,
+        _threshold: &Raw,
+        _count: u64,
+    ) -> anyhow::Result<Vec<Pending>> {
+        Err(anyhow!("The node does not track pending blocks yet"))
+    }
+
+    async fn process(&self, _subtype: Subtype, block: &StateBlock) -> anyhow::Result<BlockHash> {
+        block.verify_self_signature()?;
+        let block = Block::from_state_block(block);
+        self.state().lock().await.add_block(&block).await?;
+        Ok(block.hash()?.to_owned())
+    }
+}
+
+/// An in memory [AccountProvider] for testing wallet logic without a node.
+///
+/// Processed blocks update the account state and remove any pending block they receive.
+#[derive(Debug, Default)]
+pub struct MemoryProvider {
+    accounts: Mutex<HashMap<Address, AccountState>>,
+    pending: Mutex<HashMap<Address, Vec<Pending>>>,
+    processed: Mutex<Vec<(Subtype, StateBlock)>>,
+}
+
+impl MemoryProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_account(&self, account: Address, state: AccountState) {
+        self.accounts.lock().unwrap().insert(account, state);
+    }
+
+    pub fn add_pending(&self, account: Address, pending: Pending) {
+        self.pending
+            .lock()
+            .unwrap()
+            .entry(account)
+            .or_default()
+            .push(pending);
+    }
+
+    /// Every block given to [AccountProvider::process], in order.
+    pub fn processed(&self) -> Vec<(Subtype, StateBlock)> {
+        self.processed.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl AccountProvider for MemoryProvider {
+    async fn account_info(&self, account: &Address) -> anyhow::Result<Option<AccountState>> {
+        Ok(self.accounts.lock().unwrap().get(account).cloned())
+    }
+
+    async fn pending(
+        &self,
+        account: &Address,
+        threshold: &Raw,
+        count: u64,
+    ) -> anyhow::Result<Vec<Pending>> {
+        let mut pending: Vec<Pending> = self
+            .pending
+            .lock()
+            .unwrap()
+            .get(account)
+            .map(|p| {
+                p.iter()
+                    .filter(|p| &p.amount >= threshold)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        sort_pending(&mut pending);
+        pending.truncate(count as usize);
+        Ok(pending)
+    }
+
+    async fn process(&self, subtype: Subtype, block: &StateBlock) -> anyhow::Result<BlockHash> {
+        if block.signature.is_none() {
+            return Err(anyhow!("Block is not signed"));
+        }
+        if block.work.is_none() {
+            return Err(anyhow!("Block has no work"));
+        }
+
+        let account = block.account.to_address();
+        let mut accounts = self.accounts.lock().unwrap();
+        let expected_previous = match accounts.get(&account) {
+            Some(state) => Previous::Block(state.frontier.to_owned()),
+            None => Previous::Open,
+        };
+        if block.previous != expected_previous {
+            return Err(anyhow!("Fork: previous is not the account frontier"));
+        }
+
+        if subtype == Subtype::Receive || subtype == Subtype::Open {
+            let source = BlockHash::try_from(block.link.as_bytes())?;
+            if let Some(pending) = self.pending.lock().unwrap().get_mut(&account) {
+                pending.retain(|p| p.hash != source);
+            }
+        }
+
+        accounts.insert(
+            account,
+            AccountState {
+                frontier: block.hash.to_owned(),
+                balance: block.balance.to_owned(),
+                representative: block.representative.to_owned(),
+            },
+        );
+        self.processed
+            .lock()
+            .unwrap()
+            .push((subtype, block.to_owned()));
+        Ok(block.hash.to_owned())
+    }
+}
+
+/// Largest amounts first.
+fn sort_pending(pending: &mut [Pending]) {
+    pending.sort_by_key(|p| std::cmp::Reverse(p.amount.0));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::Link;
+    use crate::{Seed, Signature, Work};
+
+    fn pending(byte: u8, amount: u128) -> Pending {
+        Pending {
+            hash: BlockHash::try_from([byte; BlockHash::LEN].as_ref()).unwrap(),
+            amount: Raw::from(amount),
+            source: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn pending_is_filtered_and_sorted() {
+        let account = Seed::zero().derive(0).to_address().unwrap();
+        let provider = MemoryProvider::new();
+        provider.add_pending(account.to_owned(), pending(1, 5));
+        provider.add_pending(account.to_owned(), pending(2, 100));
+        provider.add_pending(account.to_owned(), pending(3, 50));
+
+        let found = provider
+            .pending(&account, &Raw::from(10), 10)
+            .await
+            .unwrap();
+        assert_eq!(found, vec![pending(2, 100), pending(3, 50)]);
+
+        let found = provider.pending(&account, &Raw::zero(), 1).await.unwrap();
+        assert_eq!(found, vec![pending(2, 100)]);
+    }
+
+    #[tokio::test]
+    async fn process_open_block() {
+        let public = Seed::zero().derive(0).to_public().unwrap();
+        let account = public.to_address();
+        let provider = MemoryProvider::new();
+        provider.add_pending(account.to_owned(), pending(1, 100));
+        assert!(provider.account_info(&account).await.unwrap().is_none());
+
+        let mut block = StateBlock::new(
+            public.to_owned(),
+            Previous::Open,
+            public.to_owned(),
+            Raw::from(100),
+            Link::Source(pending(1, 100).hash),
+        );
+        assert!(provider.process(Subtype::Open, &block).await.is_err());
+
+        block.signature = Some(Signature::zero());
+        block.work = Some(Work::zero());
+        let hash = provider.process(Subtype::Open, &block).await.unwrap();
+
+        let state = provider.account_info(&account).await.unwrap().unwrap();
+        assert_eq!(state.frontier, hash);
+        assert_eq!(state.balance, Raw::from(100));
+        assert!(provider
+            .pending(&account, &Raw::zero(), 10)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(provider.processed().len(), 1);
+
+        // Opening the account again is a fork.
+        assert!(provider.process(Subtype::Open, &block).await.is_err());
+    }
+
+    #[cfg(feature = "node")]
+    #[tokio::test]
+    async fn node_provider() {
+        let private = Seed::zero().derive(0);
+        let public = private.to_public().unwrap();
+        let account = public.to_address();
+        let node = Node::new(crate::Network::Live);
+        assert!(node.account_info(&account).await.unwrap().is_none());
+        assert!(node.pending(&account, &Raw::zero(), 10).await.is_err());
+
+        let mut block = StateBlock::new(
+            public.to_owned(),
+            Previous::Open,
+            public.to_owned(),
+            Raw::from(100),
+            Link::Source(pending(1, 100).hash),
+        );
+        block.signature = Some(Signature::zero());
+        assert!(node.process(Subtype::Open, &block).await.is_err());
+
+        block.signature = Some(private.sign(block.hash.as_bytes()).unwrap());
+        let hash = node.process(Subtype::Open, &block).await.unwrap();
+        assert_eq!(
+            node.account_info(&account).await.unwrap(),
+            Some(AccountState {
+                frontier: hash,
+                balance: Raw::from(100),
+                representative: public,
+            })
+        );
+    }
+}
@@ -0,0 +1,330 @@
+//! Signing blocks without holding private keys in the same process.
+//!
+//! A [Signer] gives out public keys and signs blocks for account indexes. Private keys and
+//! [Wallet]s sign in memory, a [WalletFileSigner] only loads the wallet file while signing, and a
+//! [RemoteSigner] asks another process that holds the keys.
+//!
+//! # Remote protocol
+//! The remote protocol is one JSON object per line in each direction, over a child process'
+//! stdin/stdout or a Unix socket. Requests are answered in order:
+//!
+//! ```text
+//! > {"action": "public", "index": 0}
+//! < {"public": "<public key hex>"}
+//! > {"action": "sign_block", "index": 0, "block": {<state block>}}
+//! < {"signature": "<signature hex>"}
+//! < {"error": "<message>"}
+//! ```
+//!
+//! The signing process recalculates the block hash from the block's fields, so it never signs a
+//! hash it hasn't seen the contents of. Use [serve] to answer requests with any other [Signer].
+use crate::blocks::{Block, BlockType, StateBlock};
+use crate::wallet::{Wallet, WalletId, WalletManager};
+use crate::{Private, Public, Signature};
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+#[cfg(unix)]
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// The public key of an account, so blocks can be built without the private key.
+    async fn public(&self, index: u32) -> anyhow::Result<Public>;
+
+    /// Sign the hash of a block owned by an account.
+    async fn sign_block(&self, index: u32, block: &Block) -> anyhow::Result<Signature>;
+}
+
+/// Sign with a private key, making sure the block belongs to it.
+fn sign(private: &Private, block: &Block) -> anyhow::Result<Signature> {
+    if &private.to_public()? != block.account() {
+        return Err(anyhow!(
+            "Block account {} does not match the signing key",
+            block.account().to_address()
+        ));
+    }
+    Ok(private.sign(block.hash()?.as_bytes())?)
+}
+
+/// A single key, which only has the account index 0.
+#[async_trait]
+impl Signer for Private {
+    async fn public(&self, index: u32) -> anyhow::Result<Public> {
+        Ok(Wallet::Private(self.to_owned()).public(index)?)
+    }
+
+    async fn sign_block(&self, index: u32, block: &Block) -> anyhow::Result<Signature> {
+        Signer::sign_block(&Wallet::Private(self.to_owned()), index, block).await
+    }
+}
+
+#[async_trait]
+impl Signer for Wallet {
+    async fn public(&self, index: u32) -> anyhow::Result<Public> {
+        Ok(Wallet::public(self, index)?)
+    }
+
+    async fn sign_block(&self, index: u32, block: &Block) -> anyhow::Result<Signature> {
+        sign(&self.private(index)?, block)
+    }
+}
+
+/// Load a wallet from a [WalletManager] for every request, so its keys are only in memory while
+/// signing.
+pub struct WalletFileSigner {
+    manager: WalletManager,
+    id: WalletId,
+    passphrase: Option<String>,
+}
+
+impl WalletFileSigner {
+    pub fn new(manager: WalletManager, id: WalletId) -> Self {
+        Self {
+            manager,
+            id,
+            passphrase: None,
+        }
+    }
+
+    /// Unlock phrase wallets which need a passphrase.
+    pub fn passphrase(&mut self, v: String) -> &mut Self {
+        self.passphrase = Some(v);
+        self
+    }
+
+    async fn wallet(&self) -> anyhow::Result<Wallet> {
+        let wallet = self.manager.wallet(&self.id).await?;
+        match &self.passphrase {
+            Some(passphrase) => Ok(wallet.unlock(passphrase)?),
+            None => Ok(wallet),
+        }
+    }
+}
+
+#[async_trait]
+impl Signer for WalletFileSigner {
+    async fn public(&self, index: u32) -> anyhow::Result<Public> {
+        Ok(self.wallet().await?.public(index)?)
+    }
+
+    async fn sign_block(&self, index: u32, block: &Block) -> anyhow::Result<Signature> {
+        sign(&self.wallet().await?.private(index)?, block)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum Request {
+    Public { index: u32 },
+    SignBlock { index: u32, block: Box<StateBlock> },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Response {
+    Public(Public),
+    Signature(Signature),
+    Error(String),
+}
+
+type Reader = Lines<BufReader<Box<dyn AsyncRead + Send + Unpin>>>;
+type Writer = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// Asks another process to sign blocks using the [remote protocol](self#remote-protocol).
+pub struct RemoteSigner {
+    connection: Mutex<(Reader, Writer)>,
+
+    /// Kept so the signer process is killed when this is dropped.
+    _child: Option<Child>,
+}
+
+impl RemoteSigner {
+    pub fn new<R, W>(reader: R, writer: W) -> Self
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        let reader: Box<dyn AsyncRead + Send + Unpin> = Box::new(reader);
+        Self {
+            connection: Mutex::new((BufReader::new(reader).lines(), Box::new(writer))),
+            _child: None,
+        }
+    }
+
+    /// Start a signer process, talking to it over its stdin and stdout.
+    pub fn spawn(program: &str, args: &[String]) -> anyhow::Result<Self> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Starting signer {}", program))?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let mut signer = Self::new(stdout, stdin);
+        signer._child = Some(child);
+        Ok(signer)
+    }
+
+    /// Connect to a signer listening on a Unix socket.
+    #[cfg(unix)]
+    pub async fn connect<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let stream = tokio::net::UnixStream::connect(path.as_ref())
+            .await
+            .with_context(|| format!("Connecting to signer {:?}", path.as_ref()))?;
+        let (reader, writer) = tokio::io::split(stream);
+        Ok(Self::new(reader, writer))
+    }
+
+    async fn request(&self, request: &Request) -> anyhow::Result<Response> {
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+
+        let mut connection = self.connection.lock().await;
+        let (reader, writer) = &mut *connection;
+        writer.write_all(line.as_bytes()).await?;
+        writer.flush().await?;
+        let line = reader
+            .next_line()
+            .await?
+            .ok_or_else(|| anyhow!("Signer closed the connection"))?;
+        match serde_json::from_str(&line).context("Parsing signer response")? {
+            Response::Error(err) => Err(anyhow!("Signer error: {}", err)),
+            response => Ok(response),
+        }
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    async fn public(&self, index: u32) -> anyhow::Result<Public> {
+        match self.request(&Request::Public { index }).await? {
+            Response::Public(public) => Ok(public),
+            response => Err(anyhow!("Unexpected signer response: {:?}", response)),
+        }
+    }
+
+    async fn sign_block(&self, index: u32, block: &Block) -> anyhow::Result<Signature> {
+        if block.block_type() != &BlockType::State {
+            return Err(anyhow!(
+                "Only state blocks can be signed remotely, not {:?}",
+                block.block_type()
+            ));
+        }
+        let block = StateBlock::new(
+            block.account().to_owned(),
+            block.previous().to_owned(),
+            block.representative().to_owned(),
+            block.balance().to_owned(),
+            block.link().to_unsure(),
+        );
+        let block = Box::new(block);
+        match self.request(&Request::SignBlock { index, block }).await? {
+            Response::Signature(signature) => Ok(signature),
+            response => Err(anyhow!("Unexpected signer response: {:?}", response)),
+        }
+    }
+}
+
+/// Answer [remote protocol](self#remote-protocol) requests with a [Signer] until the reader is
+/// closed.
+pub async fn serve<R, W>(signer: &dyn Signer, reader: R, mut writer: W) -> anyhow::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match handle(signer, &line).await {
+            Ok(response) => response,
+            Err(err) => Response::Error(format!("{:#}", err)),
+        };
+        let mut line = serde_json::to_string(&response)?;
+        line.push('\n');
+        writer.write_all(line.as_bytes()).await?;
+        writer.flush().await?;
+    }
+    Ok(())
+}
+
+async fn handle(signer: &dyn Signer, line: &str) -> anyhow::Result<Response> {
+    let request: Request = serde_json::from_str(line).context("Parsing signer request")?;
+    Ok(match request {
+        Request::Public { index } => Response::Public(signer.public(index).await?),
+        Request::SignBlock { index, block } => {
+            // Don't trust the hash that was sent, calculate it from the fields being signed.
+            let block = StateBlock::new(
+                block.account,
+                block.previous,
+                block.representative,
+                block.balance,
+                block.link,
+            );
+            let block = Block::from_state_block(&block);
+            Response::Signature(signer.sign_block(index, &block).await?)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::{BlockHash, Link, Previous};
+    use crate::{Raw, Seed};
+
+    fn block(account: &Public) -> Block {
+        Block::from_state_block(&StateBlock::new(
+            account.to_owned(),
+            Previous::Block(BlockHash::zero()),
+            account.to_owned(),
+            Raw::from(100),
+            Link::DestinationAccount(account.to_owned()),
+        ))
+    }
+
+    #[tokio::test]
+    async fn wallet_signer() {
+        let wallet = Wallet::Seed(Seed::zero());
+        let public = Signer::public(&wallet, 1).await.unwrap();
+        let mut block = block(&public);
+        block.set_signature(wallet.sign_block(1, &block).await.unwrap());
+        block.verify_signature(&public).unwrap();
+
+        // Index 0 is a different account.
+        assert!(wallet.sign_block(0, &block).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn remote_signer() {
+        let (client, server) = tokio::io::duplex(1024);
+        let wallet = Wallet::Seed(Seed::zero());
+        let serving = wallet.clone();
+        tokio::spawn(async move {
+            let (reader, writer) = tokio::io::split(server);
+            serve(&serving, reader, writer).await.unwrap();
+        });
+
+        let (reader, writer) = tokio::io::split(client);
+        let remote = RemoteSigner::new(reader, writer);
+        let public = remote.public(2).await.unwrap();
+        assert_eq!(public, Signer::public(&wallet, 2).await.unwrap());
+
+        let mut block = block(&public);
+        let signature = remote.sign_block(2, &block).await.unwrap();
+        block.set_signature(signature);
+        block.verify_signature(&public).unwrap();
+
+        // Errors come back without closing the connection.
+        assert!(remote.sign_block(0, &block).await.is_err());
+        assert!(remote.public(0).await.is_ok());
+    }
+}
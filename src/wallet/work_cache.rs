@@ -0,0 +1,332 @@
+//! Work generated ahead of time for the next block of each account.
+//!
+//! The next block of an account needs work on its current frontier, or on its public key if the
+//! account hasn't been opened. Generating that on a CPU can take minutes, so [WorkCache] starts it
+//! in the background as soon as the frontier is known and keeps the result in a file, surviving
+//! restarts.
+//!
+//! Each account has at most one entry. An entry for a root other than the one asked for is stale,
+//! since the frontier has moved on, and is dropped.
+use crate::blocks::BlockHash;
+use crate::{Difficulty, Public, Subject, Work, WorkGenerator};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Work for the block after `root` on `account`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CachedWork {
+    pub account: Public,
+    pub root: BlockHash,
+    pub work: Work,
+    pub difficulty: Difficulty,
+}
+
+/// The entries, shared with the background tasks filling them.
+struct Store {
+    path: Option<PathBuf>,
+    entries: Mutex<HashMap<Public, CachedWork>>,
+
+    /// Held while writing so saves don't overtake each other.
+    saving: tokio::sync::Mutex<()>,
+}
+
+impl Store {
+    fn insert(&self, entry: CachedWork) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(entry.account.to_owned(), entry);
+    }
+
+    fn remove(&self, account: &Public) -> Option<CachedWork> {
+        self.entries.lock().unwrap().remove(account)
+    }
+
+    async fn save(&self) -> anyhow::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let _saving = self.saving.lock().await;
+        let entries: Vec<CachedWork> = self.entries.lock().unwrap().values().cloned().collect();
+        let json = serde_json::to_string_pretty(&entries)?;
+
+        // Write a temporary file first so a crash doesn't leave a half written cache.
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, json)
+            .await
+            .with_context(|| format!("Writing work cache {:?}", &tmp))?;
+        tokio::fs::rename(&tmp, path).await?;
+        Ok(())
+    }
+}
+
+type Generating = JoinHandle<anyhow::Result<Work>>;
+
+/// Persistent work for the next block of each account.
+///
+/// ```
+/// use feeless::wallet::WorkCache;
+/// use feeless::blocks::BlockHash;
+/// use feeless::{Difficulty, Seed, Subject};
+///
+/// # #[tokio::main]
+/// # async fn main() -> anyhow::Result<()> {
+/// let cache = WorkCache::memory();
+/// let account = Seed::zero().derive(0).to_public()?;
+/// let frontier = BlockHash::zero();
+/// let difficulty = Difficulty::new(0xff00000000000000);
+///
+/// // Start generating as soon as the frontier is known...
+/// cache.precompute(&account, &frontier, &difficulty);
+///
+/// // ...and use it when the next block is built.
+/// let work = cache.work(&account, &frontier, &difficulty).await?;
+/// assert!(work.verify(&Subject::Hash(frontier), &difficulty)?);
+/// # Ok(())
+/// # }
+/// ```
+pub struct WorkCache {
+    store: Arc<Store>,
+    generator: WorkGenerator,
+    generating: Mutex<HashMap<Public, (BlockHash, Generating)>>,
+}
+
+impl WorkCache {
+    /// A cache that is lost when dropped.
+    pub fn memory() -> Self {
+        Self::new(None, HashMap::new())
+    }
+
+    /// Load a cache file, or start an empty one if it doesn't exist.
+    pub async fn open<P: Into<PathBuf>>(path: P) -> anyhow::Result<Self> {
+        let path = path.into();
+        let mut entries = HashMap::new();
+        if path.exists() {
+            let json = tokio::fs::read_to_string(&path)
+                .await
+                .with_context(|| format!("Reading work cache {:?}", &path))?;
+            let list: Vec<CachedWork> = serde_json::from_str(&json)
+                .with_context(|| format!("Parsing work cache {:?}", &path))?;
+            for entry in list {
+                entries.insert(entry.account.to_owned(), entry);
+            }
+        }
+        Ok(Self::new(Some(path), entries))
+    }
+
+    fn new(path: Option<PathBuf>, entries: HashMap<Public, CachedWork>) -> Self {
+        Self {
+            store: Arc::new(Store {
+                path,
+                entries: Mutex::new(entries),
+                saving: tokio::sync::Mutex::new(()),
+            }),
+            generator: WorkGenerator::new(),
+            generating: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The generator used for background and on demand work, e.g. to limit threads.
+    pub fn generator(&mut self, v: WorkGenerator) -> &mut Self {
+        self.generator = v;
+        self
+    }
+
+    /// Cached work for `root` that meets `difficulty`. A stale entry for another root is dropped.
+    pub async fn get(
+        &self,
+        account: &Public,
+        root: &BlockHash,
+        difficulty: &Difficulty,
+    ) -> anyhow::Result<Option<Work>> {
+        let stale = match self.store.entries.lock().unwrap().get(account) {
+            Some(entry) => &entry.root != root,
+            None => return Ok(None),
+        };
+        if stale {
+            debug!("Dropping stale work for {}", account.to_address());
+            self.store.remove(account);
+            self.store.save().await?;
+            return Ok(None);
+        }
+        Ok(self.cached(account, root, difficulty))
+    }
+
+    /// Like [WorkCache::get] but leaves a stale entry, which is replaced once new work is stored.
+    fn cached(&self, account: &Public, root: &BlockHash, difficulty: &Difficulty) -> Option<Work> {
+        let entries = self.store.entries.lock().unwrap();
+        let entry = entries.get(account)?;
+        if &entry.root != root || &entry.difficulty < difficulty {
+            return None;
+        }
+        Some(entry.work.to_owned())
+    }
+
+    /// Store work that was generated elsewhere.
+    pub async fn insert(&self, entry: CachedWork) -> anyhow::Result<()> {
+        self.store.insert(entry);
+        self.store.save().await
+    }
+
+    /// Forget the work of an account, e.g. when its frontier changed without this cache knowing.
+    pub async fn invalidate(&self, account: &Public) -> anyhow::Result<()> {
+        self.stop(account);
+        if self.store.remove(account).is_some() {
+            self.store.save().await?;
+        }
+        Ok(())
+    }
+
+    /// Start generating work for `root` in the background, unless it is already cached or being
+    /// generated. Work being generated for an older root is cancelled.
+    pub fn precompute(&self, account: &Public, root: &BlockHash, difficulty: &Difficulty) {
+        if self.cached(account, root, difficulty).is_some() {
+            return;
+        }
+        let mut generating = self.generating.lock().unwrap();
+        if let Some((generating_root, _)) = generating.get(account) {
+            if generating_root == root {
+                return;
+            }
+        }
+        if let Some((_, handle)) = generating.remove(account) {
+            handle.abort();
+        }
+        let handle = self.spawn(account, root, difficulty);
+        generating.insert(account.to_owned(), (root.to_owned(), handle));
+    }
+
+    /// Take the work for `root`, waiting for it if it's being generated, or generating it now.
+    ///
+    /// The entry is removed since the frontier changes once the block is published.
+    pub async fn work(
+        &self,
+        account: &Public,
+        root: &BlockHash,
+        difficulty: &Difficulty,
+    ) -> anyhow::Result<Work> {
+        if let Some(work) = self.get(account, root, difficulty).await? {
+            self.invalidate(account).await?;
+            return Ok(work);
+        }
+
+        let generating = self.generating.lock().unwrap().remove(account);
+        let precomputed = match generating {
+            Some((generating_root, handle)) if &generating_root == root => {
+                handle.await.ok().and_then(|r| r.ok())
+            }
+            Some((_, handle)) => {
+                handle.abort();
+                None
+            }
+            None => None,
+        };
+        let work = match precomputed {
+            // It may have been started for a lower difficulty than is needed now.
+            Some(work) if &work.difficulty(&Subject::Hash(root.to_owned()))? >= difficulty => work,
+            _ => self.spawn(account, root, difficulty).await??,
+        };
+        self.invalidate(account).await?;
+        Ok(work)
+    }
+
+    /// Stop any background generation for an account.
+    fn stop(&self, account: &Public) {
+        if let Some((_, handle)) = self.generating.lock().unwrap().remove(account) {
+            handle.abort();
+        }
+    }
+
+    /// Generate in a task that stores the result. Aborting the handle stops the generation.
+    fn spawn(&self, account: &Public, root: &BlockHash, difficulty: &Difficulty) -> Generating {
+        let store = self.store.clone();
+        let generator = self.generator.clone();
+        let account = account.to_owned();
+        let root = root.to_owned();
+        let difficulty = difficulty.to_owned();
+        tokio::spawn(async move {
+            let generated = generator
+                .generate_async(Subject::Hash(root.to_owned()), difficulty)
+                .await?;
+            store.insert(CachedWork {
+                account,
+                root,
+                work: generated.work.to_owned(),
+                difficulty: generated.difficulty,
+            });
+            if let Err(err) = store.save().await {
+                warn!("Could not save work cache: {:?}", err);
+            }
+            Ok(generated.work)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Seed;
+    use std::convert::TryFrom;
+    use std::fs::remove_file;
+
+    fn hash(byte: u8) -> BlockHash {
+        BlockHash::try_from([byte; BlockHash::LEN].as_ref()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn persist_and_invalidate() {
+        let path = PathBuf::from("persist_and_invalidate.work_cache");
+        let _ = remove_file(&path);
+        let account = Seed::zero().derive(0).to_public().unwrap();
+        let difficulty = Difficulty::new(0xf000000000000000);
+
+        let cache = WorkCache::open(&path).await.unwrap();
+        cache.precompute(&account, &hash(1), &difficulty);
+        let (_, handle) = cache.generating.lock().unwrap().remove(&account).unwrap();
+        let work = handle.await.unwrap().unwrap();
+
+        // Still there after reopening.
+        let cache = WorkCache::open(&path).await.unwrap();
+        let get = cache.get(&account, &hash(1), &difficulty).await.unwrap();
+        assert_eq!(get, Some(work));
+        let get = cache
+            .get(&account, &hash(1), &Difficulty::new(u64::MAX))
+            .await;
+        assert_eq!(get.unwrap(), None);
+
+        // The frontier moved on, so the entry is stale, even after reopening.
+        let get = cache.get(&account, &hash(2), &difficulty).await.unwrap();
+        assert_eq!(get, None);
+        let get = cache.get(&account, &hash(1), &difficulty).await.unwrap();
+        assert_eq!(get, None);
+        let cache = WorkCache::open(&path).await.unwrap();
+        assert_eq!(
+            cache.get(&account, &hash(1), &difficulty).await.unwrap(),
+            None
+        );
+        remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn work_is_consumed() {
+        let account = Seed::zero().derive(0).to_public().unwrap();
+        let difficulty = Difficulty::new(0xf000000000000000);
+        let cache = WorkCache::memory();
+
+        cache.precompute(&account, &hash(1), &difficulty);
+        let work = cache.work(&account, &hash(1), &difficulty).await.unwrap();
+        assert!(work.verify(&Subject::Hash(hash(1)), &difficulty).unwrap());
+        let get = cache.get(&account, &hash(1), &difficulty).await.unwrap();
+        assert_eq!(get, None);
+
+        // Nothing precomputed, so it's generated on demand.
+        let work = cache.work(&account, &hash(2), &difficulty).await.unwrap();
+        assert!(work.verify(&Subject::Hash(hash(2)), &difficulty).unwrap());
+    }
+}